@@ -26,8 +26,95 @@ pub enum Commands {
     #[command(about = "Sync vault with remote (fetch, commit, push)")]
     Sync(SyncArgs),
 
+    #[command(about = "Show pending changes and how sync would reconcile with the remote")]
+    Status(StatusArgs),
+
     #[command(about = "Print the resolved vault root path")]
     Root,
+
+    #[command(about = "Drop a quick note into today's inbox paste zone, or triage the inbox")]
+    Inbox(InboxArgs),
+
+    #[command(about = "Create project hubs and append to their logs")]
+    Project {
+        #[command(subcommand)]
+        command: ProjectCommands,
+    },
+
+    #[command(about = "Ingest a source note under sources/")]
+    Ingest {
+        #[command(subcommand)]
+        command: IngestCommands,
+    },
+
+    #[command(about = "Write roll-ups of captured blocks into collections/")]
+    Rollup {
+        #[command(subcommand)]
+        command: RollupCommands,
+    },
+
+    #[command(about = "Report vault statistics and activity")]
+    Stats(StatsArgs),
+
+    #[command(about = "List checkbox tasks from daily notes, or carry open ones into today")]
+    Tasks(TasksArgs),
+
+    #[command(about = "Search notes for a phrase (case-insensitive)")]
+    Search(SearchArgs),
+
+    #[command(about = "Render a note with its ![[...]] embeds resolved")]
+    Stitch(StitchArgs),
+
+    #[command(about = "Stitch and redact a note for a static site, with front matter and media")]
+    Publish(PublishArgs),
+
+    #[command(about = "Export the vault to other formats")]
+    Export {
+        #[command(subcommand)]
+        command: ExportCommands,
+    },
+
+    #[command(about = "Import events from other formats as anchored blocks")]
+    Import {
+        #[command(subcommand)]
+        command: ImportCommands,
+    },
+
+    #[command(about = "Check and convert for use alongside Obsidian")]
+    Obsidian {
+        #[command(subcommand)]
+        command: ObsidianCommands,
+    },
+
+    #[command(about = "Serve a local JSON API for editor and launcher integrations")]
+    Serve(ServeArgs),
+
+    #[command(about = "Run a Model Context Protocol server on stdio")]
+    Mcp(McpArgs),
+
+    #[command(about = "Watch the vault and sync automatically after changes settle")]
+    Watch(WatchArgs),
+
+    #[command(about = "Clone a vault onto this device and print the environment to use it")]
+    Clone(CloneArgs),
+
+    #[command(about = "Choose which capture months are checked out (sparse checkout)")]
+    Sparse {
+        #[command(subcommand)]
+        command: SparseCommands,
+    },
+
+    #[command(about = "Manage files in the assets/ companion repository")]
+    Assets {
+        #[command(subcommand)]
+        command: AssetsCommands,
+    },
+
+    #[command(about = "Encrypt private notes in git with age")]
+    Crypt {
+        #[command(subcommand)]
+        command: CryptCommands,
+    },
 }
 
 #[derive(Parser)]
@@ -58,6 +145,7 @@ pub struct AppendArgs {
 
 #[derive(Parser)]
 pub struct SyncArgs {
+    /// Commit message; generated from the pending changes by default
     #[arg(long, value_name = "MSG")]
     pub message: Option<String>,
 
@@ -69,4 +157,394 @@ pub struct SyncArgs {
 
     #[arg(long, default_value = "true")]
     pub ff_only: bool,
+
+    /// Report what would be committed and how the remote would be reconciled, without changing anything
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Parser)]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct InboxArgs {
+    #[command(subcommand)]
+    pub command: Option<InboxCommands>,
+
+    #[arg(long, value_name = "PREFIX", default_value = "inbox")]
+    pub anchor: String,
+
+    #[arg(
+        long,
+        value_name = "TEXT",
+        conflicts_with = "stdin",
+        allow_hyphen_values = true
+    )]
+    pub text: Option<String>,
+
+    #[arg(long, conflicts_with = "text")]
+    pub stdin: bool,
+}
+
+#[derive(Subcommand)]
+pub enum InboxCommands {
+    #[command(about = "List untriaged inbox blocks, or move one into a note with --anchor")]
+    Triage(TriageArgs),
+}
+
+#[derive(Parser)]
+pub struct TriageArgs {
+    #[arg(long, value_name = "TOKEN")]
+    pub anchor: Option<String>,
+
+    #[arg(long, value_name = "YYYY-MM-DD", requires = "anchor")]
+    pub day: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        requires = "anchor",
+        conflicts_with = "project"
+    )]
+    pub file: Option<PathBuf>,
+
+    #[arg(long, value_name = "SLUG", requires = "anchor")]
+    pub project: Option<String>,
+
+    /// Defaults to "Inbox", or today's date when moving into a project log
+    #[arg(long, value_name = "HEADING")]
+    pub heading: Option<String>,
+}
+
+#[derive(Subcommand)]
+pub enum ProjectCommands {
+    #[command(about = "Create projects/<slug>/index.md and log.md from templates")]
+    New {
+        #[arg(value_name = "SLUG")]
+        slug: String,
+    },
+
+    #[command(about = "Append a dated, anchored entry to a project's log.md")]
+    Log(ProjectLogArgs),
+
+    #[command(about = "List projects with their last log activity")]
+    List,
+}
+
+#[derive(Parser)]
+pub struct ProjectLogArgs {
+    #[arg(value_name = "SLUG")]
+    pub slug: String,
+
+    #[arg(long, value_name = "PREFIX", default_value = "log")]
+    pub anchor: String,
+
+    #[arg(
+        long,
+        value_name = "TEXT",
+        conflicts_with = "stdin",
+        allow_hyphen_values = true
+    )]
+    pub text: Option<String>,
+
+    #[arg(long, conflicts_with = "text")]
+    pub stdin: bool,
+}
+
+#[derive(Subcommand)]
+pub enum IngestCommands {
+    #[command(about = "Create sources/articles/<slug>.md; --text/--stdin become highlights")]
+    Article(IngestArgs),
+
+    #[command(about = "Create sources/transcripts/<slug>.md; --text/--stdin become the content")]
+    Transcript(IngestArgs),
+}
+
+#[derive(Parser)]
+pub struct IngestArgs {
+    #[arg(long, value_name = "TITLE", required_unless_present = "html")]
+    pub title: Option<String>,
+
+    #[arg(long, value_name = "URL")]
+    pub url: Option<String>,
+
+    /// Defaults to a slug derived from the title
+    #[arg(long, value_name = "SLUG")]
+    pub slug: Option<String>,
+
+    /// Local HTML file to convert to Markdown (no network access)
+    #[arg(long, value_name = "PATH")]
+    pub html: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "TEXT",
+        conflicts_with = "stdin",
+        allow_hyphen_values = true
+    )]
+    pub text: Option<String>,
+
+    #[arg(long, conflicts_with = "text")]
+    pub stdin: bool,
+
+    /// Also append a ^read-HHMM block linking the source to today's daily note
+    #[arg(long)]
+    pub daily: bool,
+}
+
+#[derive(Subcommand)]
+pub enum RollupCommands {
+    #[command(about = "Gather a month's journal blocks into collections/journals/YYYY/YYYY-MM.md")]
+    Month(RollupMonthArgs),
+}
+
+#[derive(Parser)]
+pub struct RollupMonthArgs {
+    /// Defaults to the current month
+    #[arg(value_name = "YYYY-MM")]
+    pub month: Option<String>,
+
+    /// Anchor prefix to gather; repeatable. Defaults to [rollup] prefixes in .a4/config.toml
+    #[arg(long = "prefix", value_name = "PREFIX")]
+    pub prefixes: Vec<String>,
+
+    /// Copy block content instead of writing ![[...]] transclusion markers
+    #[arg(long)]
+    pub inline: bool,
+}
+
+#[derive(Parser)]
+pub struct StatsArgs {
+    /// Only count dated notes on or after this day
+    #[arg(long, value_name = "YYYY-MM-DD")]
+    pub since: Option<String>,
+
+    /// Print the report as JSON instead of a table
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Parser)]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct TasksArgs {
+    #[command(subcommand)]
+    pub command: Option<TasksCommands>,
+
+    /// Only list unchecked tasks
+    #[arg(long)]
+    pub open: bool,
+
+    /// Only scan daily notes on or after this day
+    #[arg(long, value_name = "YYYY-MM-DD")]
+    pub since: Option<String>,
+}
+
+#[derive(Subcommand)]
+pub enum TasksCommands {
+    #[command(about = "Append open tasks from previous days to today's note as a new block")]
+    Carry(CarryArgs),
+}
+
+#[derive(Parser)]
+pub struct CarryArgs {
+    /// Defaults to [tasks] heading in .a4/config.toml
+    #[arg(long, value_name = "HEADING")]
+    pub heading: Option<String>,
+}
+
+#[derive(Parser)]
+pub struct SearchArgs {
+    pub query: String,
+
+    #[arg(long, default_value_t = 50)]
+    pub limit: usize,
+
+    /// Print hits as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Parser)]
+pub struct StitchArgs {
+    /// Note path, or a wikilink target such as collections/memos/w36
+    #[arg(value_name = "NOTE")]
+    pub note: String,
+
+    /// Write the rendered Markdown here instead of stdout
+    #[arg(long, short, value_name = "PATH")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Parser)]
+pub struct PublishArgs {
+    /// Note path, or a wikilink target such as collections/memos/w36
+    #[arg(value_name = "NOTE")]
+    pub note: String,
+
+    /// Directory to write <slug>.md and the media folder into
+    #[arg(long, value_name = "DIR")]
+    pub out: PathBuf,
+
+    /// Publication date when the note's front matter has none (YYYY-MM-DD, defaults to today)
+    #[arg(long, value_name = "DATE")]
+    pub date: Option<String>,
+}
+
+#[derive(Subcommand)]
+pub enum ExportCommands {
+    #[command(
+        about = "Render every note to a static HTML site with backlinks and a calendar index"
+    )]
+    Html {
+        /// Directory to write the site into
+        #[arg(long, value_name = "DIR")]
+        out: PathBuf,
+    },
+
+    #[command(about = "Write anchored blocks from daily notes as iCalendar events")]
+    Ics(ExportIcsArgs),
+}
+
+#[derive(Parser)]
+pub struct ExportIcsArgs {
+    /// Anchor prefix to export; repeatable. Defaults to every prefix
+    #[arg(long = "prefix", value_name = "PREFIX")]
+    pub prefixes: Vec<String>,
+
+    /// Only export daily notes on or after this day
+    #[arg(long, value_name = "YYYY-MM-DD")]
+    pub since: Option<String>,
+
+    /// Write the calendar here instead of stdout
+    #[arg(long, short, value_name = "PATH")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Subcommand)]
+pub enum ImportCommands {
+    #[command(about = "Append a day's events from an .ics file to the daily note under Schedule")]
+    Ics(ImportIcsArgs),
+}
+
+#[derive(Parser)]
+pub struct ImportIcsArgs {
+    /// Local iCalendar file
+    #[arg(value_name = "FILE")]
+    pub file: PathBuf,
+
+    /// Local date of the events to import (defaults to today)
+    #[arg(long, value_name = "YYYY-MM-DD")]
+    pub day: Option<String>,
+}
+
+#[derive(Subcommand)]
+pub enum ObsidianCommands {
+    #[command(about = "Show detected Obsidian settings and list anchors Obsidian cannot link to")]
+    Check,
+
+    #[command(about = "Print an Obsidian or Templater template with A4 template variables")]
+    ConvertTemplate {
+        /// Template note to convert
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+    },
+}
+
+#[derive(Parser)]
+pub struct ServeArgs {
+    /// Address to listen on; use port 0 to pick a free port
+    #[arg(long, value_name = "ADDR", default_value = "127.0.0.1:4747")]
+    pub listen: String,
+}
+
+#[derive(Parser)]
+pub struct McpArgs {
+    /// Only expose tools that never write to the vault
+    #[arg(long)]
+    pub read_only: bool,
+}
+
+#[derive(Parser)]
+pub struct WatchArgs {
+    /// Quiet period after the last change before syncing
+    #[arg(long, value_name = "MS", default_value_t = 5000)]
+    pub debounce_ms: u64,
+
+    #[arg(long, value_name = "MSG")]
+    pub message: Option<String>,
+
+    #[arg(long, value_name = "NAME", default_value = "origin")]
+    pub remote: String,
+
+    #[arg(long, value_name = "NAME")]
+    pub branch: Option<String>,
+}
+
+#[derive(Parser)]
+pub struct StatusArgs {
+    #[arg(long, value_name = "NAME", default_value = "origin")]
+    pub remote: String,
+
+    #[arg(long, value_name = "NAME")]
+    pub branch: Option<String>,
+
+    /// Print the report as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Parser)]
+pub struct CloneArgs {
+    /// Remote URL or path of the vault repository
+    pub url: String,
+
+    /// Directory to clone into (defaults to the repository name)
+    pub path: Option<PathBuf>,
+
+    /// Only check out the most recent N capture months (sparse checkout)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub keep_months: Option<u32>,
+}
+
+#[derive(Subcommand)]
+pub enum SparseCommands {
+    #[command(about = "Check out only the most recent N capture months")]
+    Keep {
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+        months: u32,
+    },
+
+    #[command(about = "Also check out capture/YYYY or capture/YYYY/YYYY-MM")]
+    Add {
+        #[arg(value_name = "DIR")]
+        dir: String,
+    },
+
+    #[command(about = "List checked-out and trimmed capture months")]
+    Show,
+}
+
+#[derive(Subcommand)]
+pub enum AssetsCommands {
+    #[command(about = "Copy a file into assets/img/YYYY/YYYY-MM and print the Markdown link")]
+    Add {
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum CryptCommands {
+    #[command(about = "Create this device's key and install the git filter for [crypt] paths")]
+    Init,
+
+    /// Git clean filter: encrypt stdin for the index
+    #[command(hide = true)]
+    Clean { path: String },
+
+    /// Git smudge filter: decrypt stdin for the working copy
+    #[command(hide = true)]
+    Smudge { path: String },
+
+    /// Git diff textconv: print a blob decrypted
+    #[command(hide = true)]
+    Textconv { file: PathBuf },
 }
@@ -0,0 +1,55 @@
+// Helpers shared by the subcommands and the serve, mcp and watch front ends.
+
+use a4_core::sync::GuardedReport;
+use a4_core::verify;
+use a4_core::{AnchorToken, LocalClock};
+use anyhow::Result;
+
+pub fn parse_anchor_with_auto_hhmm(anchor_str: &str) -> Result<AnchorToken> {
+    // First try to parse as-is
+    match AnchorToken::parse(anchor_str) {
+        Ok(token) => Ok(token),
+        Err(_) => {
+            // If it fails, try to auto-append HHMM
+            let hhmm = LocalClock::now_local_hhmm();
+
+            // Check if the anchor has a suffix separator
+            let modified_anchor = if let Some(pos) = anchor_str.find("__") {
+                // Insert HHMM before the suffix
+                let (prefix, suffix) = anchor_str.split_at(pos);
+                format!("{prefix}-{hhmm}{suffix}")
+            } else {
+                // Just append HHMM
+                format!("{anchor_str}-{hhmm}")
+            };
+
+            // Try to parse the modified anchor
+            AnchorToken::parse(&modified_anchor).map_err(|e| {
+                // If it still fails, provide a helpful error
+                if anchor_str.len() < 2 || anchor_str.len() > 25 {
+                    anyhow::anyhow!("Invalid anchor prefix '{anchor_str}': must be between 2 and 25 characters")
+                } else if !anchor_str.chars().next().is_some_and(|c| c.is_ascii_lowercase()) {
+                    anyhow::anyhow!("Invalid anchor prefix '{anchor_str}': must start with a lowercase letter")
+                } else if !anchor_str.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_') {
+                    anyhow::anyhow!("Invalid anchor prefix '{anchor_str}': must contain only lowercase letters, digits, hyphens, and underscores")
+                } else {
+                    anyhow::anyhow!("Invalid anchor: {e}")
+                }
+            })
+        }
+    }
+}
+
+/// Report what a guarded sync did beyond the core repository: rewrites it
+/// let through, and how the assets submodule fared.
+pub fn print_guarded_report(report: &GuardedReport) {
+    if !report.rewrites.is_empty() {
+        eprintln!("Warning: capture notes were rewritten, not appended to:");
+        eprint!("{}", verify::render(&report.rewrites));
+    }
+    if report.assets_not_checked_out {
+        eprintln!("Note: assets/ submodule is not checked out; syncing the core repository only");
+    } else if report.assets.is_some() {
+        println!("Synced assets/");
+    }
+}
@@ -1,2 +1,16 @@
-// Module for environment-related utilities
-// Currently empty but reserved for future use
+// Environment variables the CLI reads, and how to print them for a shell.
+
+use std::path::Path;
+
+/// Overrides vault discovery, like the global `--vault` flag.
+pub const VAULT_DIR_VAR: &str = "A4_VAULT_DIR";
+
+/// Names this device in anchor suffixes (`^meet-0930__iphone`) when an
+/// imported block's anchor is already taken.
+pub const DEVICE_VAR: &str = "A4_DEVICE";
+
+/// A POSIX shell line exporting `A4_VAULT_DIR` as `root`.
+pub fn vault_export(root: &Path) -> String {
+    let value = root.display().to_string().replace('\'', r"'\''");
+    format!("export {VAULT_DIR_VAR}='{value}'")
+}
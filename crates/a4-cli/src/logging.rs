@@ -12,6 +12,8 @@ pub fn init(verbosity: u8) {
 
     tracing_subscriber::fmt()
         .with_env_filter(filter)
+        // stdout carries command output that scripts parse, e.g. paths.
+        .with_writer(std::io::stderr)
         .with_target(false)
         .with_thread_ids(false)
         .with_thread_names(false)
@@ -1,13 +1,44 @@
 mod cli;
+mod common;
 mod env;
 mod logging;
-
+mod mcp;
+mod serve;
+mod watch;
+
+use a4_core::assets::{self, Submodule};
+use a4_core::calendar;
+use a4_core::clone::{self, CloneOptions};
+use a4_core::config::RewritePolicy;
+use a4_core::crypt::{self, Keyring};
 use a4_core::git_backend::{GitBackend, GixBackend};
-use a4_core::{append_block, AnchorToken, AppendOptions, LocalClock, Vault};
+use a4_core::html::html_to_markdown;
+use a4_core::ingest::{self, SourceKind, SourceNote};
+use a4_core::obsidian::{self, ObsidianSettings};
+use a4_core::publish;
+use a4_core::rollup::{self, RollupOptions};
+use a4_core::search::search;
+use a4_core::secrets;
+use a4_core::site;
+use a4_core::slug::{slugify, validate_slug};
+use a4_core::sparse::{self, CaptureScope};
+use a4_core::stats::{self, VaultStats};
+use a4_core::status::{self, StatusReport, SyncAction};
+use a4_core::stitch::stitch;
+use a4_core::sync::{self, Preflight, SyncOptions};
+use a4_core::tasks;
+use a4_core::template::{self, TemplateContext};
+use a4_core::verify;
+use a4_core::{
+    append_block, append_block_suffixed, inbox, projects, A4Error, AnchorToken, AppendOptions,
+    Config, LocalClock, UtcDay, UtcMonth, Vault,
+};
 use anyhow::Result;
 use clap::Parser;
 use cli::{Cli, Commands};
-use std::io::{self, Read};
+use common::{parse_anchor_with_auto_hhmm, print_guarded_report};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
@@ -18,7 +49,27 @@ fn main() -> Result<()> {
         Commands::Today => handle_today(cli.vault),
         Commands::Append(args) => handle_append(cli.vault, args),
         Commands::Sync(args) => handle_sync(cli.vault, args),
+        Commands::Status(args) => handle_status(cli.vault, args),
         Commands::Root => handle_root(cli.vault),
+        Commands::Inbox(args) => handle_inbox(cli.vault, args),
+        Commands::Project { command } => handle_project(cli.vault, command),
+        Commands::Ingest { command } => handle_ingest(cli.vault, command),
+        Commands::Rollup { command } => handle_rollup(cli.vault, command),
+        Commands::Stats(args) => handle_stats(cli.vault, args),
+        Commands::Tasks(args) => handle_tasks(cli.vault, args),
+        Commands::Search(args) => handle_search(cli.vault, args),
+        Commands::Stitch(args) => handle_stitch(cli.vault, args),
+        Commands::Publish(args) => handle_publish(cli.vault, args),
+        Commands::Export { command } => handle_export(cli.vault, command),
+        Commands::Import { command } => handle_import(cli.vault, command),
+        Commands::Obsidian { command } => handle_obsidian(cli.vault, command),
+        Commands::Serve(args) => handle_serve(cli.vault, args),
+        Commands::Mcp(args) => handle_mcp(cli.vault, args),
+        Commands::Watch(args) => handle_watch(cli.vault, args),
+        Commands::Clone(args) => handle_clone(args),
+        Commands::Sparse { command } => handle_sparse(cli.vault, command),
+        Commands::Assets { command } => handle_assets(cli.vault, command),
+        Commands::Crypt { command } => handle_crypt(cli.vault, command),
     };
 
     if let Err(e) = result {
@@ -31,200 +82,853 @@ fn main() -> Result<()> {
 
 fn handle_today(vault_override: Option<std::path::PathBuf>) -> Result<()> {
     let (vault, _) = Vault::resolve_with_override(vault_override)?;
-    let today = LocalClock::today_utc();
-    let daily_path = vault.capture_day_path(today.clone());
+    let (daily_path, _) = vault.ensure_daily(LocalClock::today_utc(), &LocalClock)?;
 
-    vault.ensure_parents(&daily_path)?;
+    println!("{}", daily_path.display());
+    Ok(())
+}
 
-    if !daily_path.exists() {
-        let template_path = vault.template_path();
+fn handle_append(vault_override: Option<std::path::PathBuf>, args: cli::AppendArgs) -> Result<()> {
+    let (vault, _) = Vault::resolve_with_override(vault_override)?;
 
-        let mut content = if template_path.exists() {
-            std::fs::read_to_string(&template_path)?
-        } else {
-            "\n".to_string()
-        };
+    let target_path = if args.today {
+        vault.daily_note_path(LocalClock::today_utc())?
+    } else if let Some(file) = args.file {
+        resolve_vault_file(&vault, file)
+    } else {
+        anyhow::bail!("Must specify either --file or --today");
+    };
 
-        // Fill in template variables
-        content = fill_templates(content, &today);
+    let content = read_content(args.text, args.stdin)?;
 
-        std::fs::write(&daily_path, content)?;
-    }
+    let anchor = parse_anchor_with_auto_hhmm(&args.anchor)?;
+
+    let opts = AppendOptions {
+        heading: &args.heading,
+        anchor,
+        content: &content,
+    };
+
+    append_block(&vault, &target_path, opts)?;
 
-    println!("{}", daily_path.display());
     Ok(())
 }
 
-fn parse_anchor_with_auto_hhmm(anchor_str: &str) -> Result<AnchorToken> {
-    // First try to parse as-is
-    match AnchorToken::parse(anchor_str) {
-        Ok(token) => Ok(token),
-        Err(_) => {
-            // If it fails, try to auto-append HHMM
-            let hhmm = LocalClock::now_local_hhmm();
+fn resolve_vault_file(vault: &Vault, file: PathBuf) -> PathBuf {
+    if file.is_absolute() {
+        file
+    } else {
+        vault.root().join(file)
+    }
+}
 
-            // Check if the anchor has a suffix separator
-            let modified_anchor = if let Some(pos) = anchor_str.find("__") {
-                // Insert HHMM before the suffix
-                let (prefix, suffix) = anchor_str.split_at(pos);
-                format!("{prefix}-{hhmm}{suffix}")
-            } else {
-                // Just append HHMM
-                format!("{anchor_str}-{hhmm}")
-            };
+fn read_content(text: Option<String>, stdin: bool) -> Result<String> {
+    if stdin {
+        let mut buffer = String::new();
+        io::stdin().read_to_string(&mut buffer)?;
+        Ok(buffer)
+    } else if let Some(text) = text {
+        Ok(text)
+    } else {
+        anyhow::bail!("Must specify either --text or --stdin");
+    }
+}
 
-            // Try to parse the modified anchor
-            AnchorToken::parse(&modified_anchor).map_err(|e| {
-                // If it still fails, provide a helpful error
-                if anchor_str.len() < 2 || anchor_str.len() > 25 {
-                    anyhow::anyhow!("Invalid anchor prefix '{}': must be between 2 and 25 characters", anchor_str)
-                } else if !anchor_str.chars().next().is_some_and(|c| c.is_ascii_lowercase()) {
-                    anyhow::anyhow!("Invalid anchor prefix '{}': must start with a lowercase letter", anchor_str)
-                } else if !anchor_str.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_') {
-                    anyhow::anyhow!("Invalid anchor prefix '{}': must contain only lowercase letters, digits, hyphens, and underscores", anchor_str)
+fn handle_sync(vault_override: Option<std::path::PathBuf>, args: cli::SyncArgs) -> Result<()> {
+    let (vault, _) = Vault::resolve_with_override(vault_override)?;
+
+    let mut backend = GixBackend::open(vault.root())?;
+    let opts = SyncOptions {
+        message: args.message.as_deref(),
+        remote: args.remote.as_deref().unwrap_or(sync::DEFAULT_REMOTE),
+        branch: args.branch.as_deref(),
+    };
+    let config = Config::load(&vault)?;
+
+    if args.dry_run {
+        let report = status::status(&vault, &backend, &opts)?;
+        print_status(&report);
+        let preflight = Preflight::run(&vault, &backend, &config)?;
+        if preflight.crypt_missing {
+            println!("Encryption: [crypt] paths are not set up in this clone (sync would refuse)");
+        }
+        if !preflight.rewrites.is_empty() {
+            let outcome = match preflight.on_rewrite {
+                RewritePolicy::Refuse => "sync would refuse",
+                RewritePolicy::Warn => "sync would warn",
+            };
+            println!("Capture notes rewritten ({outcome}):");
+            print!("{}", verify::render(&preflight.rewrites));
+        }
+        if !preflight.secrets.is_empty() {
+            println!("Possible secrets (sync would refuse):");
+            print!("{}", secrets::render(&preflight.secrets));
+        }
+        match &preflight.assets {
+            Submodule::CheckedOut(dir) => {
+                let changed = GixBackend::open(dir)?.status_entries()?.len();
+                if changed == 0 {
+                    println!("Assets: nothing to commit");
                 } else {
-                    anyhow::anyhow!("Invalid anchor: {}", e)
+                    println!("Assets: would commit {changed} file(s) in assets/");
                 }
-            })
+            }
+            Submodule::NotCheckedOut => println!("Assets: submodule not checked out; skipped"),
+            Submodule::Absent => {}
+        }
+        if !report.changes.is_empty() {
+            println!("Commit message:");
+            for line in sync::commit_message(&backend, &opts)?.lines() {
+                if line.is_empty() {
+                    println!();
+                } else {
+                    println!("  {line}");
+                }
+            }
         }
+        return Ok(());
     }
+
+    let report = sync::guarded_sync(&vault, &mut backend, &config, &opts)?;
+    print_guarded_report(&report);
+    println!("Sync completed successfully");
+
+    Ok(())
 }
 
-fn fill_templates(mut content: String, today: &a4_core::date::UtcDay) -> String {
-    use time::OffsetDateTime;
+fn handle_status(vault_override: Option<PathBuf>, args: cli::StatusArgs) -> Result<()> {
+    let (vault, _) = Vault::resolve_with_override(vault_override)?;
 
-    // Replace {{now_utc}} with current UTC timestamp in ISO 8601 format
-    let now_utc = OffsetDateTime::now_utc();
-    let now_utc_str = now_utc
-        .format(&time::format_description::well_known::Iso8601::DEFAULT)
-        .unwrap_or_else(|_| now_utc.to_string());
-    content = content.replace("{{now_utc}}", &now_utc_str);
+    let backend = GixBackend::open(vault.root())?;
+    let opts = SyncOptions {
+        remote: &args.remote,
+        branch: args.branch.as_deref(),
+        ..Default::default()
+    };
 
-    // Replace {{hhmm}} with current local time as HHMM
-    let hhmm = LocalClock::now_local_hhmm();
-    content = content.replace("{{hhmm}}", &hhmm);
+    let report = status::status(&vault, &backend, &opts)?;
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_status(&report);
+    }
+    Ok(())
+}
 
-    // Replace {{YYYY-MM-DD}} with today's date
-    let date_str = format!("{:04}-{:02}-{:02}", today.year, today.month, today.day);
-    content = content.replace("{{YYYY-MM-DD}}", &date_str);
+fn print_status(report: &StatusReport) {
+    let changes = &report.changes;
+    if changes.is_empty() {
+        println!("Nothing to commit");
+    } else {
+        println!("Would commit:");
+        for (label, paths) in [
+            ("new daily notes", &changes.new_daily_notes),
+            ("new files", &changes.new_files),
+            ("appended", &changes.appended),
+            ("modified (not append-only)", &changes.modified),
+            ("deleted", &changes.deleted),
+        ] {
+            if paths.is_empty() {
+                continue;
+            }
+            println!("  {label}:");
+            for path in paths {
+                println!("    {path}");
+            }
+        }
+    }
 
-    content
+    let remote_ref = &report.remote_ref;
+    if report.action != SyncAction::NoRemoteRef {
+        println!(
+            "{remote_ref}: ahead {}, behind {} (as of last fetch)",
+            report.ahead, report.behind
+        );
+    }
+    match report.action {
+        SyncAction::UpToDate => println!("Sync would: nothing (up to date)"),
+        SyncAction::Push => println!("Sync would: push"),
+        SyncAction::FastForward => println!("Sync would: fast-forward to {remote_ref}"),
+        SyncAction::Rebase => println!("Sync would: rebase onto {remote_ref}, then push"),
+        SyncAction::NoRemoteRef => println!("Sync would: push ({remote_ref} not fetched yet)"),
+    }
 }
 
-fn handle_append(vault_override: Option<std::path::PathBuf>, args: cli::AppendArgs) -> Result<()> {
+fn handle_root(vault_override: Option<std::path::PathBuf>) -> Result<()> {
     let (vault, _) = Vault::resolve_with_override(vault_override)?;
+    println!("{}", vault.root().display());
+    Ok(())
+}
 
-    let target_path = if args.today {
-        let today = LocalClock::today_utc();
-        vault.capture_day_path(today)
+fn handle_inbox(vault_override: Option<PathBuf>, args: cli::InboxArgs) -> Result<()> {
+    let (vault, _) = Vault::resolve_with_override(vault_override)?;
+
+    if let Some(cli::InboxCommands::Triage(triage)) = args.command {
+        return handle_triage(&vault, triage);
+    }
+
+    let content = read_content(args.text, args.stdin)?;
+    let anchor = parse_anchor_with_auto_hhmm(&args.anchor)?;
+    let today = LocalClock::today_utc();
+
+    let anchor = inbox::drop_block(&vault, today, anchor, &content)?;
+
+    println!(
+        "{}#{}",
+        vault.inbox_day_path(today).display(),
+        anchor.to_marker()
+    );
+    Ok(())
+}
+
+fn handle_triage(vault: &Vault, args: cli::TriageArgs) -> Result<()> {
+    let Some(token) = args.anchor else {
+        for item in inbox::pending_items(vault)? {
+            let preview = item.block.content.lines().next().unwrap_or("");
+            println!(
+                "{}  {}  {}",
+                item.day,
+                item.block.anchor.to_marker(),
+                preview
+            );
+        }
+        return Ok(());
+    };
+
+    let day = match args.day {
+        Some(day) => UtcDay::parse(&day)?,
+        None => LocalClock::today_utc(),
+    };
+
+    let today = LocalClock::today_utc();
+    let (target, default_heading) = if let Some(slug) = args.project {
+        (projects::log_path(vault, &slug)?, today.to_string())
     } else if let Some(file) = args.file {
-        if file.is_absolute() {
-            file
-        } else {
-            vault.root().join(file)
+        (
+            resolve_vault_file(vault, file),
+            inbox::INBOX_HEADING.to_string(),
+        )
+    } else {
+        (
+            vault.daily_note_path(today)?,
+            inbox::INBOX_HEADING.to_string(),
+        )
+    };
+    let heading = args.heading.unwrap_or(default_heading);
+
+    let hhmm = LocalClock::now_local_hhmm();
+    let anchor = inbox::triage(vault, day, &token, &target, &heading, &hhmm)?;
+
+    println!("{}#{}", target.display(), anchor.to_marker());
+    Ok(())
+}
+
+fn handle_project(vault_override: Option<PathBuf>, command: cli::ProjectCommands) -> Result<()> {
+    let (vault, _) = Vault::resolve_with_override(vault_override)?;
+
+    match command {
+        cli::ProjectCommands::New { slug } => {
+            let ctx = TemplateContext::new(&vault, LocalClock::today_utc()).with_var("slug", &slug);
+            let index =
+                template::render_named("project-index", projects::DEFAULT_INDEX_TEMPLATE, &ctx)?;
+            let log = template::render_named("project-log", projects::DEFAULT_LOG_TEMPLATE, &ctx)?;
+            let dir = projects::create_project(&vault, &slug, &index, &log)?;
+            println!("{}", dir.join(projects::INDEX_FILE).display());
+        }
+        cli::ProjectCommands::Log(args) => {
+            let content = read_content(args.text, args.stdin)?;
+            let anchor = parse_anchor_with_auto_hhmm(&args.anchor)?;
+            let today = LocalClock::today_utc();
+
+            let anchor = projects::append_log_entry(&vault, &args.slug, today, anchor, &content)?;
+            println!(
+                "{}#{}",
+                projects::log_path(&vault, &args.slug)?.display(),
+                anchor.to_marker()
+            );
         }
+        cli::ProjectCommands::List => {
+            for project in projects::list_projects(&vault)? {
+                let last = project
+                    .last_activity
+                    .map(|day| day.to_string())
+                    .unwrap_or_else(|| "-".to_string());
+                println!("{:<12}  {}", last, project.slug);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_ingest(vault_override: Option<PathBuf>, command: cli::IngestCommands) -> Result<()> {
+    let (vault, _) = Vault::resolve_with_override(vault_override)?;
+
+    let (kind, args) = match command {
+        cli::IngestCommands::Article(args) => (SourceKind::Article, args),
+        cli::IngestCommands::Transcript(args) => (SourceKind::Transcript, args),
+    };
+
+    let document = match &args.html {
+        Some(path) => Some(html_to_markdown(&std::fs::read_to_string(path)?)),
+        None => None,
+    };
+
+    let Some(title) = args
+        .title
+        .clone()
+        .or_else(|| document.as_ref().and_then(|doc| doc.title.clone()))
+    else {
+        anyhow::bail!("No --title given and the HTML file has no <title>");
+    };
+
+    let slug = match args.slug {
+        Some(slug) => slug,
+        None => slugify(&title)?,
+    };
+
+    let text = if args.stdin || args.text.is_some() {
+        Some(read_content(args.text, args.stdin)?)
     } else {
-        anyhow::bail!("Must specify either --file or --today");
+        None
     };
 
-    let content = if args.stdin {
-        let mut buffer = String::new();
-        io::stdin().read_to_string(&mut buffer)?;
-        buffer
-    } else if let Some(text) = args.text {
-        text
+    // Transcripts pasted on stdin are the content itself; for articles they are highlights.
+    let (highlights, content) = match kind {
+        SourceKind::Article => (text, document.map(|doc| doc.markdown)),
+        SourceKind::Transcript => (None, text.or(document.map(|doc| doc.markdown))),
+    };
+
+    let today = LocalClock::today_utc();
+    let daily_path = vault.daily_note_path(today)?;
+    let read_anchor = if args.daily {
+        // Checked up front, so the daily note never links to a source that
+        // could not be written.
+        validate_slug(&slug)?;
+        let path = ingest::source_path(&vault, kind, &slug);
+        if path.exists() {
+            return Err(A4Error::SourceExists { path }.into());
+        }
+
+        let content = format!("- [[{}]] — {title}", ingest::source_link(kind, &slug));
+        Some(append_block_suffixed(
+            &vault,
+            &daily_path,
+            AppendOptions {
+                heading: "Reading",
+                anchor: AnchorToken::parse(&format!("read-{}", LocalClock::now_local_hhmm()))?,
+                content: &content,
+            },
+        )?)
     } else {
-        anyhow::bail!("Must specify either --text or --stdin");
+        None
     };
 
-    let anchor = parse_anchor_with_auto_hhmm(&args.anchor)?;
+    let referenced_in = read_anchor.as_ref().and_then(|anchor| {
+        vault
+            .note_link(&daily_path)
+            .map(|link| format!("{link}#{}", anchor.to_marker()))
+    });
+
+    let created = LocalClock::now_utc_rfc3339();
+    let path = ingest::write_source(
+        &vault,
+        &SourceNote {
+            kind,
+            slug: &slug,
+            title: &title,
+            url: args.url.as_deref(),
+            via: "a4 ingest",
+            created: &created,
+            highlights: highlights.as_deref(),
+            content: content.as_deref(),
+            referenced_in: referenced_in.as_deref(),
+        },
+    )?;
+
+    println!("{}", path.display());
+    Ok(())
+}
 
-    let opts = AppendOptions {
-        heading: &args.heading,
-        anchor,
-        content: &content,
+fn handle_rollup(vault_override: Option<PathBuf>, command: cli::RollupCommands) -> Result<()> {
+    let (vault, _) = Vault::resolve_with_override(vault_override)?;
+
+    let cli::RollupCommands::Month(args) = command;
+    let month = match args.month {
+        Some(month) => UtcMonth::parse(&month)?,
+        None => LocalClock::today_utc().month(),
     };
 
-    append_block(&vault, &target_path, opts)?;
+    let prefixes = if args.prefixes.is_empty() {
+        Config::load(&vault)?.rollup.prefixes
+    } else {
+        args.prefixes
+    };
 
+    let rollup = rollup::write_month(
+        &vault,
+        month,
+        &RollupOptions {
+            prefixes: &prefixes,
+            inline: args.inline,
+        },
+    )?;
+
+    tracing::info!("Rolled up {} blocks for {}", rollup.blocks, month);
+    println!("{}", rollup.path.display());
     Ok(())
 }
 
-fn handle_sync(vault_override: Option<std::path::PathBuf>, args: cli::SyncArgs) -> Result<()> {
+fn handle_stats(vault_override: Option<PathBuf>, args: cli::StatsArgs) -> Result<()> {
     let (vault, _) = Vault::resolve_with_override(vault_override)?;
 
-    let mut backend = GixBackend::open(vault.root())?;
+    let since = args.since.as_deref().map(UtcDay::parse).transpose()?;
+    let stats = stats::compute(&vault, since, LocalClock::today_utc())?;
 
-    // Stage and commit any local changes
-    backend.stage_all()?;
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+    } else {
+        print_stats_table(&stats);
+    }
+    Ok(())
+}
 
-    let message = args.message.as_deref().unwrap_or("a4: sync");
-    let committed = backend.commit_if_needed(message)?;
+fn print_stats_table(stats: &VaultStats) {
+    let row = |label: &str, value: &dyn std::fmt::Display| println!("  {label:<18} {value}");
+
+    println!("Notes");
+    row("total", &stats.notes);
+    for (dir, count) in &stats.notes_per_directory {
+        row(dir, count);
+    }
 
-    if committed {
-        tracing::info!("Created commit: {}", message);
+    println!("Daily notes");
+    let daily = &stats.daily;
+    row("first", &daily.first_day.as_deref().unwrap_or("-"));
+    row("last", &daily.last_day.as_deref().unwrap_or("-"));
+    row("created", &daily.created);
+    row("missing", &daily.missing);
+    row("current streak", &daily.current_streak);
+    row("longest streak", &daily.longest_streak);
+
+    println!("Anchors by prefix");
+    row("total", &stats.anchors.total);
+    for (prefix, count) in &stats.anchors.by_prefix {
+        row(prefix, count);
     }
 
-    let remote = args.remote.as_deref().unwrap_or("origin");
-    let branch = args.branch.as_deref();
+    println!("Anchors by device");
+    for (suffix, count) in &stats.anchors.by_suffix {
+        row(suffix, count);
+    }
 
-    // Fetch latest from remote
-    backend.fetch(remote, branch)?;
+    println!("Words");
+    row("total", &stats.words.total);
+    for (dir, count) in &stats.words.by_directory {
+        row(dir, count);
+    }
 
-    let remote_ref = if let Some(branch) = branch {
-        format!("refs/remotes/{remote}/{branch}")
-    } else {
-        let current_branch = backend.head_branch()?;
-        format!("refs/remotes/{remote}/{current_branch}")
+    println!("Busiest hours");
+    for hour in &stats.busiest_hours {
+        row(&format!("{hour}:00"), &stats.hours[hour]);
+    }
+}
+
+fn handle_tasks(vault_override: Option<PathBuf>, args: cli::TasksArgs) -> Result<()> {
+    let (vault, _) = Vault::resolve_with_override(vault_override)?;
+
+    if let Some(cli::TasksCommands::Carry(carry)) = args.command {
+        let heading = match carry.heading {
+            Some(heading) => heading,
+            None => Config::load(&vault)?.tasks.heading,
+        };
+        let today = LocalClock::today_utc();
+        let hhmm = LocalClock::now_local_hhmm();
+
+        match tasks::carry(&vault, today, &heading, &hhmm)? {
+            Some((path, anchor, count)) => {
+                tracing::info!("Carried {} open tasks", count);
+                println!("{}#{}", path.display(), anchor.to_marker());
+            }
+            None => tracing::info!("No open tasks to carry"),
+        }
+        return Ok(());
+    }
+
+    let since = args.since.as_deref().map(UtcDay::parse).transpose()?;
+    for task in tasks::latest(tasks::scan(&vault, since)?) {
+        if args.open && task.done {
+            continue;
+        }
+        let checkbox = if task.done { "[x]" } else { "[ ]" };
+        println!(
+            "{}  {}  {}  {}",
+            task.day,
+            task.anchor.to_marker(),
+            checkbox,
+            task.text
+        );
+    }
+    Ok(())
+}
+
+fn handle_search(vault_override: Option<PathBuf>, args: cli::SearchArgs) -> Result<()> {
+    let (vault, _) = Vault::resolve_with_override(vault_override)?;
+
+    let hits = search(&vault, &args.query, args.limit)?;
+    if let Some(note) = sparse::unsearched_note(&vault)? {
+        eprintln!("Note: {note}");
+    }
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&hits)?);
+        return Ok(());
+    }
+
+    for hit in hits {
+        let location = match &hit.anchor {
+            Some(anchor) => format!("{}#{anchor}", hit.path),
+            None => hit.path.clone(),
+        };
+        println!("{location}:{}  {}", hit.line, hit.text);
+    }
+    Ok(())
+}
+
+fn handle_stitch(vault_override: Option<PathBuf>, args: cli::StitchArgs) -> Result<()> {
+    let (vault, _) = Vault::resolve_with_override(vault_override)?;
+
+    let path = resolve_note(&vault, &args.note)?;
+    let rendered = stitch(&vault, &path)?;
+    match args.output {
+        Some(output) => {
+            std::fs::write(&output, rendered)?;
+            println!("{}", output.display());
+        }
+        None => print!("{rendered}"),
+    }
+    Ok(())
+}
+
+/// A note given as a path or as a wikilink target.
+fn resolve_note(vault: &Vault, note: &str) -> Result<PathBuf> {
+    let file = resolve_vault_file(vault, PathBuf::from(note));
+    if file.is_file() {
+        return Ok(file);
+    }
+    vault
+        .resolve_link(note)
+        .ok_or_else(|| anyhow::anyhow!("Note not found: {note}"))
+}
+
+fn handle_publish(vault_override: Option<PathBuf>, args: cli::PublishArgs) -> Result<()> {
+    let (vault, _) = Vault::resolve_with_override(vault_override)?;
+    let config = Config::load(&vault)?;
+
+    let path = resolve_note(&vault, &args.note)?;
+    let date = match args.date {
+        Some(date) => UtcDay::parse(&date)?,
+        None => LocalClock::today_utc(),
     };
 
-    // Try fast-forward first
-    let fast_forwarded = backend.fast_forward_current_branch(&remote_ref)?;
+    let publication = publish::publish(&vault, &path, &args.out, &config.publish, date)?;
+    for media in &publication.media {
+        eprintln!("Copied {}", media.display());
+    }
+    println!("{}", publication.path.display());
+    Ok(())
+}
 
-    if fast_forwarded {
-        tracing::info!("Fast-forwarded to {}", remote_ref);
-    } else if backend.diverged(&remote_ref)? {
-        // We have diverged - try to rebase
-        tracing::info!("Detected divergence, attempting automatic rebase...");
+fn handle_export(vault_override: Option<PathBuf>, command: cli::ExportCommands) -> Result<()> {
+    let (vault, _) = Vault::resolve_with_override(vault_override)?;
 
-        match backend.rebase_onto(&remote_ref)? {
-            a4_core::git_backend::RebaseResult::Success => {
-                tracing::info!("Successfully rebased onto {}", remote_ref);
-                // Force push after successful rebase
-                backend.push(remote, branch, true)?;
-                tracing::info!("Pushed rebased changes to {}", remote);
-                return Ok(());
+    match command {
+        cli::ExportCommands::Html { out } => {
+            let report = site::export(&vault, &out)?;
+            for path in &report.skipped {
+                eprintln!("Skipped {} (still encrypted)", path.display());
+            }
+            eprintln!(
+                "Exported {} notes and {} assets",
+                report.pages,
+                report.assets.len()
+            );
+            println!("{}", report.index.display());
+        }
+        cli::ExportCommands::Ics(args) => {
+            let config = Config::load(&vault)?;
+            let tz = calendar::timezone(&config.calendar)?;
+            let since = args.since.as_deref().map(UtcDay::parse).transpose()?;
+
+            let events = calendar::anchored_events(&vault, tz, &args.prefixes, since)?;
+            let ics = calendar::to_ics(&events, time::OffsetDateTime::now_utc());
+            match args.output {
+                Some(path) => {
+                    std::fs::write(&path, ics)?;
+                    eprintln!("Wrote {} events to {}", events.len(), path.display());
+                }
+                None => print!("{ics}"),
             }
-            a4_core::git_backend::RebaseResult::Conflict => {
-                // There's a conflict - inform the user
+        }
+    }
+    Ok(())
+}
+
+fn handle_import(vault_override: Option<PathBuf>, command: cli::ImportCommands) -> Result<()> {
+    let (vault, _) = Vault::resolve_with_override(vault_override)?;
+    let config = Config::load(&vault)?;
+    let tz = calendar::timezone(&config.calendar)?;
+
+    match command {
+        cli::ImportCommands::Ics(args) => {
+            let date = match args.day {
+                Some(day) => UtcDay::parse(&day)?.to_date(),
+                None => calendar::local_today(tz),
+            };
+            let text = std::fs::read_to_string(&args.file)?;
+            let events = calendar::parse_ics(&text, tz)?;
+            let occurrences = calendar::occurrences_on(&events, date, tz);
+
+            let device = std::env::var(env::DEVICE_VAR)
+                .ok()
+                .filter(|device| !device.is_empty());
+            let report = calendar::import(
+                &vault,
+                &occurrences,
+                date,
+                &config.calendar,
+                tz,
+                device.as_deref(),
+            )?;
+            for imported in &report.added {
+                println!("{} {}", imported.anchor.to_marker(), imported.summary);
+            }
+            if report.skipped > 0 {
+                eprintln!("Skipped {} already imported", report.skipped);
+            }
+            if report.added.is_empty() && report.skipped == 0 {
+                eprintln!("No events on {}", UtcDay::from_date(date));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn handle_obsidian(vault_override: Option<PathBuf>, command: cli::ObsidianCommands) -> Result<()> {
+    let (vault, _) = Vault::resolve_with_override(vault_override)?;
+    let settings = ObsidianSettings::detect(&vault)?;
+
+    match command {
+        cli::ObsidianCommands::Check => {
+            let config = Config::load(&vault)?;
+            match &settings.daily_notes {
+                Some(daily) => println!(
+                    "Daily notes: folder {}, format {}, template {}",
+                    daily.folder.as_deref().unwrap_or("/"),
+                    settings.daily_format(),
+                    daily.template.as_deref().unwrap_or("none"),
+                ),
+                None => println!("Daily notes: no .obsidian/daily-notes.json"),
+            }
+            match &settings.templates {
+                Some(templates) => println!(
+                    "Templates: folder {}",
+                    templates.folder.as_deref().unwrap_or("/")
+                ),
+                None => println!("Templates: no .obsidian/templates.json"),
+            }
+            let today = obsidian::daily_note(&vault, &config, LocalClock::today_utc())?;
+            println!("a4 today: {}", today.path.display());
+
+            let invalid = obsidian::invalid_block_ids(&vault)?;
+            if !invalid.is_empty() {
+                for id in &invalid {
+                    println!(
+                        "{}:{}: {} is not an Obsidian block ID",
+                        id.path.display(),
+                        id.line,
+                        id.marker
+                    );
+                }
                 anyhow::bail!(
-                    "Rebase conflict detected when syncing with {}. \
-                    Please resolve the conflicts manually:\n\
-                    1. Run 'git rebase {}' in the vault directory\n\
-                    2. Resolve any conflicts\n\
-                    3. Run 'git rebase --continue' after resolving\n\
-                    4. Run 'a4 sync' again to push changes",
-                    remote_ref,
-                    remote_ref
+                    "{} anchor(s) cannot be linked from Obsidian; block IDs allow only letters, digits and '-'",
+                    invalid.len()
                 );
             }
-            a4_core::git_backend::RebaseResult::NoRebaseNeeded => {
-                // We're already up to date or ahead
-                tracing::info!("No rebase needed");
+            println!("Anchors: all are valid Obsidian block IDs");
+        }
+        cli::ObsidianCommands::ConvertTemplate { file } => {
+            let text = std::fs::read_to_string(resolve_vault_file(&vault, file))?;
+            let converted = obsidian::convert_template(&text, &settings);
+            print!("{}", converted.text);
+            for placeholder in &converted.unmapped {
+                eprintln!("Warning: no A4 equivalent for {placeholder}; left as is");
             }
         }
     }
+    Ok(())
+}
 
-    // Push changes (normal push, not force)
-    backend.push(remote, branch, false)?;
+fn handle_serve(vault_override: Option<PathBuf>, args: cli::ServeArgs) -> Result<()> {
+    let (vault, _) = Vault::resolve_with_override(vault_override)?;
+    serve::run(vault, &args.listen)
+}
 
-    println!("Sync completed successfully");
+fn handle_mcp(vault_override: Option<PathBuf>, args: cli::McpArgs) -> Result<()> {
+    let (vault, _) = Vault::resolve_with_override(vault_override)?;
+    mcp::run(vault, args.read_only)
+}
+
+fn handle_watch(vault_override: Option<PathBuf>, args: cli::WatchArgs) -> Result<()> {
+    let (vault, _) = Vault::resolve_with_override(vault_override)?;
 
+    let opts = SyncOptions {
+        message: args.message.as_deref(),
+        remote: &args.remote,
+        branch: args.branch.as_deref(),
+    };
+    watch::run(
+        vault,
+        opts,
+        std::time::Duration::from_millis(args.debounce_ms),
+    )
+}
+
+fn handle_clone(args: cli::CloneArgs) -> Result<()> {
+    let dest = match args.path {
+        Some(path) => path,
+        None => PathBuf::from(default_clone_dir(&args.url)?),
+    };
+
+    let report = clone::clone(
+        &args.url,
+        &dest,
+        &CloneOptions {
+            keep_months: args.keep_months,
+            today: None,
+        },
+    )?;
+    let root = report.root.canonicalize()?;
+
+    println!("Cloned {} into {}", args.url, root.display());
+    match &report.version {
+        Some(version) => println!("Protocol version: {version}"),
+        None => println!("Protocol version: not recorded ({})", clone::VERSION_FILE),
+    }
+    if let Some(months) = args.keep_months {
+        println!("Sparse checkout: keeping the last {months} capture months");
+    }
+    println!("To use this vault, add to your shell profile:");
+    println!("{}", env::vault_export(&root));
     Ok(())
 }
 
-fn handle_root(vault_override: Option<std::path::PathBuf>) -> Result<()> {
+/// Repository name from a URL, like `git clone` picks: `.../notes.git` -> `notes`.
+fn default_clone_dir(url: &str) -> Result<String> {
+    let name = url
+        .trim_end_matches('/')
+        .rsplit(['/', ':', '\\'])
+        .next()
+        .unwrap_or("")
+        .trim_end_matches(".git");
+    if name.is_empty() {
+        anyhow::bail!("Cannot derive a directory name from {url}; pass a path");
+    }
+    Ok(name.to_string())
+}
+
+fn handle_sparse(vault_override: Option<PathBuf>, command: cli::SparseCommands) -> Result<()> {
     let (vault, _) = Vault::resolve_with_override(vault_override)?;
-    println!("{}", vault.root().display());
+    let root = vault.root();
+
+    match command {
+        cli::SparseCommands::Keep { months } => {
+            let scope = CaptureScope::recent(LocalClock::today_utc(), months);
+            sparse::apply(root, &scope)?;
+            println!("Keeping {}", scope.dirs().join(", "));
+        }
+        cli::SparseCommands::Add { dir } => {
+            let mut scope = sparse::load(root)?.ok_or_else(|| {
+                anyhow::anyhow!("Sparse checkout is off; every month is already checked out")
+            })?;
+            scope.add(&dir)?;
+            sparse::apply(root, &scope)?;
+            println!("Checked out {}", dir.trim_matches('/'));
+        }
+        cli::SparseCommands::Show => {
+            let Some(scope) = sparse::load(root)? else {
+                println!("Sparse checkout is off; every month is checked out");
+                return Ok(());
+            };
+            println!("Checked out:");
+            for dir in scope.dirs() {
+                println!("  {dir}");
+            }
+            let hidden = sparse::hidden_months(root, &scope)?;
+            if !hidden.is_empty() {
+                println!("Not checked out:");
+                for month in hidden {
+                    println!("  capture/{:04}/{month}", month.year);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn handle_assets(vault_override: Option<PathBuf>, command: cli::AssetsCommands) -> Result<()> {
+    let (vault, _) = Vault::resolve_with_override(vault_override)?;
+
+    match command {
+        cli::AssetsCommands::Add { file } => {
+            let asset = assets::add(&vault, &file, LocalClock::today_utc())?;
+            eprintln!("Copied {} to {}", file.display(), asset.path.display());
+            println!("{}", asset.link);
+        }
+    }
+    Ok(())
+}
+
+fn handle_crypt(vault_override: Option<PathBuf>, command: cli::CryptCommands) -> Result<()> {
+    let (vault, _) = Vault::resolve_with_override(vault_override)?;
+    let config = Config::load(&vault)?;
+
+    match command {
+        cli::CryptCommands::Init => {
+            let report = crypt::init(&vault, &config.crypt, &std::env::current_exe()?)?;
+            if report.created_key {
+                println!(
+                    "Created {} (copy it to your other devices; it is not committed)",
+                    vault.private_file_path(crypt::KEY_FILE).display()
+                );
+            }
+            println!("Public key: {}", report.recipient);
+            for path in &report.decrypted {
+                println!("Decrypted {path}");
+            }
+            println!(
+                "Notes matching {} are now encrypted when committed",
+                config.crypt.paths.join(", ")
+            );
+        }
+        cli::CryptCommands::Clean { path } => {
+            let mut plaintext = Vec::new();
+            io::stdin().read_to_end(&mut plaintext)?;
+            let staged = crypt::staged_blob(vault.root(), &path)?;
+            let blob = Keyring::load(&vault)?.clean(&config.crypt, plaintext, staged)?;
+            io::stdout().write_all(&blob)?;
+        }
+        cli::CryptCommands::Smudge { path } => {
+            let mut blob = Vec::new();
+            io::stdin().read_to_end(&mut blob)?;
+            let content = Keyring::load(&vault)?.smudge(Path::new(&path), blob);
+            io::stdout().write_all(&content)?;
+        }
+        cli::CryptCommands::Textconv { file } => {
+            let blob = std::fs::read(&file)?;
+            let keyring = Keyring::load(&vault)?;
+            let content = keyring.smudge(&file, blob);
+            io::stdout().write_all(&content)?;
+        }
+    }
     Ok(())
 }
@@ -0,0 +1,281 @@
+// `a4 mcp`: a Model Context Protocol server over stdio, so assistants read and
+// append through the same append-only paths as the CLI instead of raw writes.
+
+use a4_core::blocks::{find_block, heading_section};
+use a4_core::notes::read_note;
+use a4_core::search::search;
+use a4_core::sparse;
+use a4_core::{append_block, AnchorToken, AppendOptions, LocalClock, Vault};
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+
+pub const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// Versions `initialize` agrees to; clients asking for any other get
+/// `PROTOCOL_VERSION` and decide whether to go on.
+const SUPPORTED_VERSIONS: &[&str] = &[PROTOCOL_VERSION];
+
+const DEFAULT_SEARCH_LIMIT: usize = 50;
+
+// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+pub struct McpServer {
+    vault: Vault,
+    read_only: bool,
+}
+
+pub fn run(vault: Vault, read_only: bool) -> Result<()> {
+    let server = McpServer { vault, read_only };
+    let mut stdout = io::stdout().lock();
+
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = server.handle_message(&line) {
+            writeln!(stdout, "{response}")?;
+            stdout.flush()?;
+        }
+    }
+
+    Ok(())
+}
+
+impl McpServer {
+    /// Handle one JSON-RPC message. Notifications get no response.
+    pub fn handle_message(&self, line: &str) -> Option<Value> {
+        let message: Value = match serde_json::from_str(line) {
+            Ok(message) => message,
+            Err(e) => return Some(error_response(Value::Null, PARSE_ERROR, e.to_string())),
+        };
+
+        let id = message.get("id").cloned()?;
+        let method = message["method"].as_str().unwrap_or("");
+        let params = &message["params"];
+
+        let result = match method {
+            "initialize" => Ok(json!({
+                "protocolVersion": params["protocolVersion"]
+                    .as_str()
+                    .filter(|version| SUPPORTED_VERSIONS.contains(version))
+                    .unwrap_or(PROTOCOL_VERSION),
+                "capabilities": { "tools": {} },
+                "serverInfo": { "name": "a4", "version": env!("CARGO_PKG_VERSION") },
+            })),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": self.tools() })),
+            "tools/call" => self.call_tool(params),
+            _ => Err((METHOD_NOT_FOUND, format!("Method not found: {method}"))),
+        };
+
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => error_response(id, code, message),
+        })
+    }
+
+    fn tools(&self) -> Vec<Value> {
+        let mut tools = vec![
+            json!({
+                "name": "today",
+                "description": if self.read_only {
+                    "Resolve the path of today's daily note"
+                } else {
+                    "Resolve the path of today's daily note, creating it from the template if absent"
+                },
+                "inputSchema": { "type": "object", "properties": {} },
+            }),
+            json!({
+                "name": "search",
+                "description": "Case-insensitive phrase search across vault notes",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "query": { "type": "string" },
+                        "limit": { "type": "integer", "minimum": 1 },
+                    },
+                    "required": ["query"],
+                },
+            }),
+            json!({
+                "name": "read",
+                "description": "Read a note, or only one of its sections or anchored blocks",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "path": { "type": "string", "description": "Wikilink target or vault-relative path, e.g. capture/2025/2025-09/2025-09-14" },
+                        "heading": { "type": "string", "description": "Only return this section" },
+                        "block": { "type": "string", "description": "Only return this block, e.g. ^focus-0930" },
+                    },
+                    "required": ["path"],
+                },
+            }),
+            json!({
+                "name": "list",
+                "description": "List notes as wikilink targets, optionally under a directory",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "dir": { "type": "string", "description": "Vault-relative directory, e.g. capture/2025" },
+                    },
+                },
+            }),
+        ];
+
+        if !self.read_only {
+            tools.insert(
+                1,
+                json!({
+                    "name": "append",
+                    "description": "Append an anchored block under a heading (created if missing). Notes are append-only: existing content is never edited.",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "heading": { "type": "string" },
+                            "anchor": { "type": "string", "description": "<prefix>-<HHMM>[__<suffix>], e.g. focus-0930" },
+                            "content": { "type": "string" },
+                            "file": { "type": "string", "description": "Vault-relative path; defaults to today's daily note" },
+                        },
+                        "required": ["heading", "anchor", "content"],
+                    },
+                }),
+            );
+        }
+
+        tools
+    }
+
+    fn call_tool(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let name = params["name"].as_str().unwrap_or("");
+        let args = &params["arguments"];
+
+        let outcome = match name {
+            "today" => self.today(),
+            "append" if self.read_only => Err(anyhow::anyhow!(
+                "a4 mcp is running read-only; append is disabled"
+            )),
+            "append" => self.append(args),
+            "search" => self.search(args),
+            "read" => self.read(args),
+            "list" => self.list(args),
+            _ => return Err((INVALID_PARAMS, format!("Unknown tool: {name}"))),
+        };
+
+        Ok(match outcome {
+            Ok(text) => json!({ "content": [{ "type": "text", "text": text }], "isError": false }),
+            Err(e) => {
+                json!({ "content": [{ "type": "text", "text": e.to_string() }], "isError": true })
+            }
+        })
+    }
+
+    fn today(&self) -> Result<String> {
+        let today = LocalClock::today_utc();
+        let path = if self.read_only {
+            self.vault.daily_note_path(today)?
+        } else {
+            self.vault.ensure_daily(today, &LocalClock)?.0
+        };
+        Ok(path.display().to_string())
+    }
+
+    fn append(&self, args: &Value) -> Result<String> {
+        let path = match args["file"].as_str() {
+            Some(file) => self.vault.resolve_appendable(file)?,
+            None => self.vault.daily_note_path(LocalClock::today_utc())?,
+        };
+        let anchor = AnchorToken::parse(required(args, "anchor")?)?;
+
+        append_block(
+            &self.vault,
+            &path,
+            AppendOptions {
+                heading: required(args, "heading")?,
+                anchor: anchor.clone(),
+                content: required(args, "content")?,
+            },
+        )?;
+
+        Ok(format!("{}#{}", path.display(), anchor.to_marker()))
+    }
+
+    fn search(&self, args: &Value) -> Result<String> {
+        let limit = args["limit"]
+            .as_u64()
+            .map_or(DEFAULT_SEARCH_LIMIT, |limit| limit as usize);
+        let hits = search(&self.vault, required(args, "query")?, limit)?;
+
+        let mut lines = hits
+            .iter()
+            .map(|hit| match &hit.anchor {
+                Some(anchor) => format!("{}#{anchor}:{}  {}", hit.path, hit.line, hit.text),
+                None => format!("{}:{}  {}", hit.path, hit.line, hit.text),
+            })
+            .collect::<Vec<_>>();
+        if let Some(note) = sparse::unsearched_note(&self.vault)? {
+            lines.push(format!("Note: {note}"));
+        }
+        Ok(lines.join("\n"))
+    }
+
+    fn read(&self, args: &Value) -> Result<String> {
+        let target = required(args, "path")?;
+        let path = self
+            .vault
+            .resolve_link(target)
+            .ok_or_else(|| anyhow::anyhow!("Note not found: {target}"))?;
+
+        if let Some(block) = args["block"].as_str() {
+            let body = read_note(&path)?.body;
+            return find_block(&body, block)
+                .map(|block| block.content)
+                .ok_or_else(|| {
+                    a4_core::A4Error::BlockNotFound {
+                        path,
+                        anchor: block.trim_start_matches('^').to_string(),
+                    }
+                    .into()
+                });
+        }
+
+        if let Some(heading) = args["heading"].as_str() {
+            let body = read_note(&path)?.body;
+            return heading_section(&body, heading)
+                .ok_or_else(|| anyhow::anyhow!("Heading not found: {heading}"));
+        }
+
+        Ok(std::fs::read_to_string(path)?)
+    }
+
+    fn list(&self, args: &Value) -> Result<String> {
+        let dir = match args["dir"].as_str() {
+            Some(dir) => self.vault.resolve_relative(dir.trim_end_matches('/'))?,
+            None => PathBuf::from(self.vault.root()),
+        };
+
+        Ok(self
+            .vault
+            .note_paths()?
+            .iter()
+            .filter(|path| path.starts_with(&dir))
+            .filter_map(|path| self.vault.note_link(path))
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+}
+
+fn required<'a>(args: &'a Value, key: &str) -> Result<&'a str> {
+    args[key]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("Missing required argument: {key}"))
+}
+
+fn error_response(id: Value, code: i64, message: String) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
@@ -0,0 +1,329 @@
+// `a4 serve`: a local JSON API so editor and launcher plugins can talk to one
+// long-running process instead of re-resolving the vault on every call.
+
+use crate::common;
+use a4_core::git_backend::{GitBackend, GixBackend};
+use a4_core::search::search;
+use a4_core::sparse;
+use a4_core::stitch::stitch;
+use a4_core::sync::{self, SyncOptions};
+use a4_core::{append_block, A4Error, AppendOptions, Config, LocalClock, Vault};
+use anyhow::Result;
+use rand::RngCore;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::io::{Read, Write};
+use std::panic::AssertUnwindSafe;
+use std::path::Path;
+use std::sync::{Arc, PoisonError, RwLock};
+use subtle::ConstantTimeEq;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+/// Bearer token file under `.a4/` (gitignored).
+pub const TOKEN_FILE: &str = "serve-token";
+
+const DEFAULT_SEARCH_LIMIT: usize = 50;
+
+/// Requests are handled on this many threads; further clients queue.
+const WORKERS: usize = 8;
+
+/// Largest request body accepted; larger ones get 413.
+const MAX_BODY: u64 = 1024 * 1024;
+
+/// The lock guards no data, so a handler that panicked while holding it
+/// leaves nothing inconsistent: poisoning is ignored. Appends to one note are
+/// serialized by its note lock, which also covers other processes.
+struct State {
+    vault: Vault,
+    token: String,
+    /// Writes hold this shared; sync holds it exclusively.
+    sync_lock: RwLock<()>,
+}
+
+struct ApiError {
+    status: u16,
+    message: String,
+}
+
+impl ApiError {
+    fn new(status: u16, message: impl Into<String>) -> Self {
+        ApiError {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<A4Error> for ApiError {
+    fn from(e: A4Error) -> Self {
+        let status = match e {
+            A4Error::InvalidAnchorToken { .. }
+            | A4Error::InvalidDate { .. }
+            | A4Error::PathTraversal { .. }
+            | A4Error::NotANote { .. }
+            | A4Error::BrokenEmbed { .. } => 400,
+            A4Error::RebaseConflict { .. }
+            | A4Error::GitDivergence { .. }
+            | A4Error::CaptureRewritten { .. }
+            | A4Error::SecretsFound { .. }
+            | A4Error::AssetsDetached
+            | A4Error::CryptNotInitialized => 409,
+            _ => 500,
+        };
+        ApiError::new(status, e.to_string())
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(e: anyhow::Error) -> Self {
+        match e.downcast::<A4Error>() {
+            Ok(e) => e.into(),
+            Err(e) => ApiError::new(500, e.to_string()),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct AppendRequest {
+    heading: String,
+    anchor: String,
+    content: String,
+    /// Vault-relative path; defaults to today's daily note.
+    file: Option<String>,
+}
+
+#[derive(Default, Deserialize)]
+struct SyncRequest {
+    message: Option<String>,
+    remote: Option<String>,
+    branch: Option<String>,
+}
+
+pub fn run(vault: Vault, listen: &str) -> Result<()> {
+    let token = load_or_create_token(&vault)?;
+    let server =
+        Server::http(listen).map_err(|e| anyhow::anyhow!("Failed to listen on {listen}: {e}"))?;
+
+    tracing::info!(
+        "Bearer token in {}",
+        vault.private_file_path(TOKEN_FILE).display()
+    );
+    println!("Listening on http://{}", server.server_addr());
+    std::io::stdout().flush()?;
+
+    let state = Arc::new(State {
+        vault,
+        token,
+        sync_lock: RwLock::new(()),
+    });
+
+    let server = Arc::new(server);
+    let workers: Vec<_> = (0..WORKERS)
+        .map(|_| {
+            let server = Arc::clone(&server);
+            let state = Arc::clone(&state);
+            std::thread::spawn(move || loop {
+                let request = match server.recv() {
+                    Ok(request) => request,
+                    Err(e) => {
+                        tracing::warn!("Failed to receive request: {}", e);
+                        break;
+                    }
+                };
+                // A panicking handler drops its connection, not the worker.
+                if std::panic::catch_unwind(AssertUnwindSafe(|| handle(&state, request))).is_err() {
+                    tracing::warn!("Request handler panicked");
+                }
+            })
+        })
+        .collect();
+    for worker in workers {
+        let _ = worker.join();
+    }
+
+    Ok(())
+}
+
+/// Read the bearer token from `.a4/serve-token`, generating one on first use.
+pub fn load_or_create_token(vault: &Vault) -> Result<String> {
+    let path = vault.private_file_path(TOKEN_FILE);
+    if path.exists() {
+        let token = std::fs::read_to_string(&path)?.trim().to_string();
+        if !token.is_empty() {
+            return Ok(token);
+        }
+        std::fs::remove_file(&path)?;
+    }
+
+    vault.ignore_private(TOKEN_FILE)?;
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    let token: String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+
+    // Created owner-only, so the token is never readable by others.
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&path)?;
+    file.write_all(format!("{token}\n").as_bytes())?;
+    file.sync_all()?;
+
+    Ok(token)
+}
+
+fn handle(state: &State, mut request: Request) {
+    let result = if authorized(state, &request) {
+        route(state, &mut request)
+    } else {
+        Err(ApiError::new(401, "Missing or invalid bearer token"))
+    };
+
+    let (status, body) = match result {
+        Ok(body) => (200, body),
+        Err(e) => (e.status, json!({ "error": e.message })),
+    };
+
+    let response = Response::from_string(body.to_string())
+        .with_status_code(status)
+        .with_header(Header::from_bytes("Content-Type", "application/json").unwrap());
+    if let Err(e) = request.respond(response) {
+        tracing::warn!("Failed to send response: {}", e);
+    }
+}
+
+fn authorized(state: &State, request: &Request) -> bool {
+    let expected = format!("Bearer {}", state.token);
+    request.headers().iter().any(|h| {
+        h.field.equiv("Authorization") && bool::from(h.value.as_bytes().ct_eq(expected.as_bytes()))
+    })
+}
+
+fn route(state: &State, request: &mut Request) -> Result<Value, ApiError> {
+    let url = request.url().to_string();
+    let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+
+    match (request.method(), path) {
+        (Method::Get, "/today") => today(state),
+        (Method::Post, "/append") => append(state, read_json(request)?),
+        (Method::Get, "/search") => {
+            let q = query_param(query, "q").ok_or_else(|| ApiError::new(400, "Missing q"))?;
+            let limit = match query_param(query, "limit") {
+                Some(limit) => limit
+                    .parse()
+                    .map_err(|_| ApiError::new(400, "Invalid limit"))?,
+                None => DEFAULT_SEARCH_LIMIT,
+            };
+            let hits = search(&state.vault, &q, limit)?;
+            match sparse::unsearched_note(&state.vault)? {
+                Some(note) => Ok(json!({ "hits": hits, "note": note })),
+                None => Ok(json!({ "hits": hits })),
+            }
+        }
+        (Method::Get, "/stitch") => {
+            let note =
+                query_param(query, "path").ok_or_else(|| ApiError::new(400, "Missing path"))?;
+            let path = state
+                .vault
+                .resolve_link(&note)
+                .ok_or_else(|| ApiError::new(404, format!("Note not found: {note}")))?;
+            Ok(json!({ "path": display(&path), "markdown": stitch(&state.vault, &path)? }))
+        }
+        (Method::Post, "/sync") => {
+            let body = read_body(request)?;
+            let req = if body.trim().is_empty() {
+                SyncRequest::default()
+            } else {
+                serde_json::from_str(&body).map_err(|e| ApiError::new(400, e.to_string()))?
+            };
+            sync_vault(state, req)
+        }
+        _ => Err(ApiError::new(404, format!("No route for {path}"))),
+    }
+}
+
+fn today(state: &State) -> Result<Value, ApiError> {
+    let today = LocalClock::today_utc();
+    let _writes = state
+        .sync_lock
+        .read()
+        .unwrap_or_else(PoisonError::into_inner);
+
+    let path = state.vault.ensure_daily(today, &LocalClock)?.0;
+    Ok(json!({ "path": display(&path) }))
+}
+
+fn append(state: &State, req: AppendRequest) -> Result<Value, ApiError> {
+    let path = match &req.file {
+        Some(file) => state.vault.resolve_appendable(file)?,
+        None => state.vault.daily_note_path(LocalClock::today_utc())?,
+    };
+    let anchor = common::parse_anchor_with_auto_hhmm(&req.anchor)
+        .map_err(|e| ApiError::new(400, e.to_string()))?;
+
+    let _writes = state
+        .sync_lock
+        .read()
+        .unwrap_or_else(PoisonError::into_inner);
+
+    append_block(
+        &state.vault,
+        &path,
+        AppendOptions {
+            heading: &req.heading,
+            anchor: anchor.clone(),
+            content: &req.content,
+        },
+    )?;
+
+    Ok(json!({ "path": display(&path), "anchor": anchor.to_marker() }))
+}
+
+fn sync_vault(state: &State, req: SyncRequest) -> Result<Value, ApiError> {
+    let _exclusive = state
+        .sync_lock
+        .write()
+        .unwrap_or_else(PoisonError::into_inner);
+
+    let mut backend = GixBackend::open(state.vault.root())?;
+    let config = Config::load(&state.vault)?;
+    let opts = SyncOptions {
+        message: req.message.as_deref(),
+        remote: req.remote.as_deref().unwrap_or(sync::DEFAULT_REMOTE),
+        branch: req.branch.as_deref(),
+    };
+    let report = sync::guarded_sync(&state.vault, &mut backend, &config, &opts)?;
+
+    Ok(json!(report))
+}
+
+fn read_body(request: &mut Request) -> Result<String, ApiError> {
+    let mut body = Vec::new();
+    Read::take(request.as_reader(), MAX_BODY + 1)
+        .read_to_end(&mut body)
+        .map_err(|e| ApiError::new(400, e.to_string()))?;
+    if body.len() as u64 > MAX_BODY {
+        return Err(ApiError::new(
+            413,
+            format!("Request body is larger than {MAX_BODY} bytes"),
+        ));
+    }
+    String::from_utf8(body).map_err(|e| ApiError::new(400, e.to_string()))
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(request: &mut Request) -> Result<T, ApiError> {
+    serde_json::from_str(&read_body(request)?).map_err(|e| ApiError::new(400, e.to_string()))
+}
+
+fn query_param(query: &str, key: &str) -> Option<String> {
+    form_urlencoded::parse(query.as_bytes())
+        .find(|(k, _)| k == key)
+        .map(|(_, v)| v.into_owned())
+}
+
+fn display(path: &Path) -> String {
+    path.display().to_string()
+}
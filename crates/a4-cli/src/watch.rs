@@ -0,0 +1,134 @@
+// `a4 watch`: sync automatically after edits settle, for people who write in
+// Obsidian or Neovim and forget to run `a4 sync`.
+
+use crate::common;
+use a4_core::git_backend::{GitBackend, GixBackend};
+use a4_core::sync::{self, SyncOptions};
+use a4_core::watch::{is_ignored, Backoff};
+use a4_core::{A4Error, Config, Vault};
+use anyhow::Result;
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+/// How long to wait between checks while nothing is scheduled.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(3600);
+
+pub fn run(vault: Vault, opts: SyncOptions, debounce: Duration) -> Result<()> {
+    let root = vault.root().to_path_buf();
+    let mut backend = GixBackend::open(&root)?;
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(move |event| {
+        let _ = tx.send(event);
+    })?;
+    watcher.watch(&root, RecursiveMode::Recursive)?;
+    println!("Watching {}", root.display());
+
+    let mut backoff = Backoff::default();
+    let mut changed_at: Option<Instant> = None;
+    let mut retry_at: Option<Instant> = None;
+    let mut paused = false;
+
+    loop {
+        // A pending retry is never brought forward by new edits: they only
+        // push it back until they have settled.
+        let deadline = [changed_at.map(|at| at + debounce), retry_at]
+            .into_iter()
+            .flatten()
+            .max();
+        let timeout = deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+            .unwrap_or(IDLE_TIMEOUT);
+
+        match rx.recv_timeout(timeout) {
+            Ok(Ok(event)) => {
+                if is_relevant(&root, &event) {
+                    changed_at = Some(Instant::now());
+                }
+                continue;
+            }
+            Ok(Err(e)) => tracing::warn!("Watch error: {}", e),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+
+        if deadline.is_none_or(|deadline| Instant::now() < deadline) {
+            continue;
+        }
+        changed_at = None;
+        retry_at = None;
+
+        if paused {
+            // Resume once the user has reconciled the branches by hand.
+            match still_diverged(&backend, &opts) {
+                Ok(true) => {
+                    tracing::info!("Still diverged; auto-sync stays paused");
+                    continue;
+                }
+                Ok(false) => {
+                    println!("Divergence resolved; resuming auto-sync");
+                    paused = false;
+                }
+                Err(e) => {
+                    let wait = backoff.failure();
+                    tracing::warn!(
+                        "Divergence check failed: {}; retrying in {}s",
+                        e,
+                        wait.as_secs()
+                    );
+                    retry_at = Some(Instant::now() + wait);
+                    continue;
+                }
+            }
+        }
+
+        // Reloaded on every attempt so config edits apply without a restart.
+        let synced = Config::load(&vault)
+            .and_then(|config| sync::guarded_sync(&vault, &mut backend, &config, &opts));
+        match synced {
+            Ok(report) => {
+                backoff.reset();
+                common::print_guarded_report(&report);
+                let sync = &report.sync;
+                if sync.committed || sync.fast_forwarded || sync.rebased {
+                    println!("Synced");
+                }
+            }
+            Err(e @ A4Error::RebaseConflict { .. }) => {
+                paused = true;
+                eprintln!("Auto-sync paused: {e}");
+                eprintln!("a4 watch resumes syncing once the branches no longer diverge.");
+            }
+            Err(
+                e @ (A4Error::CaptureRewritten { .. }
+                | A4Error::SecretsFound { .. }
+                | A4Error::AssetsDetached
+                | A4Error::CryptNotInitialized),
+            ) => {
+                // These need the user to act: fix or allowlist the notes, run
+                // `a4 crypt init`, or check out the assets. Their next change
+                // to the vault triggers another attempt.
+                eprintln!("Auto-sync skipped: {e}");
+            }
+            Err(e) => {
+                let wait = backoff.failure();
+                tracing::warn!("Sync failed: {}; retrying in {}s", e, wait.as_secs());
+                retry_at = Some(Instant::now() + wait);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn still_diverged(backend: &GixBackend, opts: &SyncOptions) -> Result<bool, A4Error> {
+    backend.diverged(&sync::remote_ref(backend, opts)?)
+}
+
+fn is_relevant(root: &std::path::Path, event: &Event) -> bool {
+    matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+    ) && event.paths.iter().any(|path| !is_ignored(root, path))
+}
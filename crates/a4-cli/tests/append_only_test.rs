@@ -0,0 +1,143 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+const DAY: &str = "capture/2025/2025-09/2025-09-14.md";
+const NOTE: &str =
+    "## Focus\n\n^focus-0930\n\nRefactor the parser\n\n## End of Day\n\n^eod-2215\n\nShipped\n";
+
+fn git(dir: &Path, args: &[&str]) -> String {
+    let output = std::process::Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "git {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// A vault with one committed daily note, pushed to a bare remote.
+fn setup() -> (TempDir, PathBuf) {
+    let temp_dir = TempDir::new().unwrap();
+    let remote = temp_dir.path().join("remote.git");
+    let vault = temp_dir.path().join("vault");
+
+    std::fs::create_dir(&remote).unwrap();
+    git(&remote, &["init", "-q", "--bare", "-b", "main"]);
+
+    std::fs::create_dir_all(vault.join("capture/2025/2025-09")).unwrap();
+    git(&vault, &["init", "-q", "-b", "main"]);
+    git(&vault, &["config", "user.email", "test@example.com"]);
+    git(&vault, &["config", "user.name", "Test User"]);
+    git(
+        &vault,
+        &["remote", "add", "origin", remote.to_str().unwrap()],
+    );
+    std::fs::write(vault.join(DAY), NOTE).unwrap();
+    git(&vault, &["add", "-A"]);
+    git(&vault, &["commit", "-q", "-m", "initial"]);
+    git(&vault, &["push", "-q", "-u", "origin", "main"]);
+
+    (temp_dir, vault)
+}
+
+fn a4(vault: &Path) -> Command {
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    cmd.env("A4_VAULT_DIR", vault);
+    cmd
+}
+
+#[test]
+fn test_sync_refuses_rewritten_capture_note() {
+    let (_temp_dir, vault) = setup();
+    std::fs::write(
+        vault.join(DAY),
+        NOTE.replace("Refactor the parser", "Rewrite the lexer"),
+    )
+    .unwrap();
+    let head = git(&vault, &["rev-parse", "HEAD"]);
+
+    a4(&vault)
+        .arg("sync")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Refusing to sync"))
+        .stderr(predicate::str::contains(format!(
+            "{DAY}\n  @@ line 5 @@\n  -Refactor the parser\n  +Rewrite the lexer\n"
+        )));
+    assert_eq!(git(&vault, &["rev-parse", "HEAD"]), head);
+
+    a4(&vault)
+        .args(["sync", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Capture notes rewritten (sync would refuse):",
+        ));
+}
+
+#[test]
+fn test_sync_refuses_deleted_capture_note() {
+    let (_temp_dir, vault) = setup();
+    std::fs::remove_file(vault.join(DAY)).unwrap();
+    let head = git(&vault, &["rev-parse", "HEAD"]);
+
+    a4(&vault)
+        .arg("sync")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Refusing to sync"))
+        .stderr(predicate::str::contains(format!(
+            "{DAY}\n  @@ line 1 @@\n  -## Focus\n"
+        )));
+    assert_eq!(git(&vault, &["rev-parse", "HEAD"]), head);
+}
+
+#[test]
+fn test_sync_warns_when_configured() {
+    let (_temp_dir, vault) = setup();
+    std::fs::create_dir_all(vault.join(".a4")).unwrap();
+    std::fs::write(
+        vault.join(".a4/config.toml"),
+        "[sync]\non_rewrite = \"warn\"\n",
+    )
+    .unwrap();
+    std::fs::write(vault.join(DAY), NOTE.replace("Shipped\n", "")).unwrap();
+
+    a4(&vault)
+        .arg("sync")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "Warning: capture notes were rewritten",
+        ))
+        .stderr(predicate::str::contains("  -Shipped\n"));
+    assert!(git(&vault, &["status", "--porcelain"]).is_empty());
+}
+
+#[test]
+fn test_sync_allows_appends_and_heading_inserts() {
+    let (_temp_dir, vault) = setup();
+    let note = NOTE.replace(
+        "## End of Day",
+        "## Plans\n\n^plan-1200\n\nAfternoon\n\n## End of Day",
+    );
+    std::fs::write(
+        vault.join(DAY),
+        format!("{note}\n^eod-2230\n\nAlso tests\n"),
+    )
+    .unwrap();
+    // Rewrites outside capture/ are not guarded
+    std::fs::write(vault.join("README.md"), "# Vault\n").unwrap();
+    git(&vault, &["add", "README.md"]);
+    git(&vault, &["commit", "-q", "-m", "readme"]);
+    std::fs::write(vault.join("README.md"), "# My vault\n").unwrap();
+
+    a4(&vault).arg("sync").assert().success();
+    assert!(git(&vault, &["status", "--porcelain"]).is_empty());
+}
@@ -0,0 +1,151 @@
+use a4_core::LocalClock;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+fn git(dir: &Path, args: &[&str]) -> String {
+    let output = std::process::Command::new("git")
+        .args(["-c", "protocol.file.allow=always"])
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "git {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn configure(dir: &Path) {
+    git(dir, &["config", "user.email", "test@example.com"]);
+    git(dir, &["config", "user.name", "Test User"]);
+}
+
+/// Bare core and assets remotes, and a vault clone with the assets
+/// submodule checked out at `assets/`.
+fn setup() -> (TempDir, PathBuf) {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+
+    for name in ["core.git", "assets.git"] {
+        std::fs::create_dir(root.join(name)).unwrap();
+        git(&root.join(name), &["init", "-q", "--bare", "-b", "main"]);
+    }
+
+    let seed = root.join("assets-seed");
+    std::fs::create_dir(&seed).unwrap();
+    git(&seed, &["init", "-q", "-b", "main"]);
+    configure(&seed);
+    std::fs::write(seed.join("README.md"), "# Assets\n").unwrap();
+    git(&seed, &["add", "-A"]);
+    git(&seed, &["commit", "-q", "-m", "initial"]);
+    git(&seed, &["push", "-q", "../assets.git", "main"]);
+
+    let vault = root.join("vault");
+    std::fs::create_dir(&vault).unwrap();
+    git(&vault, &["init", "-q", "-b", "main"]);
+    configure(&vault);
+    git(&vault, &["remote", "add", "origin", "../core.git"]);
+    std::fs::write(vault.join("README.md"), "# Vault\n").unwrap();
+    git(
+        &vault,
+        &["submodule", "add", "-q", "../assets.git", "assets"],
+    );
+    git(&vault, &["add", "-A"]);
+    git(&vault, &["commit", "-q", "-m", "initial"]);
+    git(&vault, &["push", "-q", "-u", "origin", "main"]);
+    configure(&vault.join("assets"));
+
+    (temp_dir, vault)
+}
+
+fn a4(vault: &Path) -> Command {
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    cmd.env("A4_VAULT_DIR", vault);
+    cmd
+}
+
+#[test]
+fn test_add_and_sync_assets_first() {
+    let (temp_dir, vault) = setup();
+    let image = temp_dir.path().join("Diagram.png");
+    std::fs::write(&image, "png").unwrap();
+
+    let today = LocalClock::today_utc();
+    let relative = format!("img/{:04}/{}/diagram.png", today.year, today.month());
+    a4(&vault)
+        .args(["assets", "add"])
+        .arg(&image)
+        .assert()
+        .success()
+        .stdout(format!("![diagram](assets/{relative})\n"));
+    assert!(vault.join("assets").join(&relative).exists());
+
+    a4(&vault)
+        .args(["sync", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Assets: would commit 1 file(s) in assets/",
+        ));
+
+    a4(&vault)
+        .arg("sync")
+        .assert()
+        .success()
+        .stdout("Synced assets/\nSync completed successfully\n");
+
+    let assets_remote = temp_dir.path().join("assets.git");
+    let core_remote = temp_dir.path().join("core.git");
+    let files = git(&assets_remote, &["ls-tree", "-r", "--name-only", "main"]);
+    assert!(files.lines().any(|line| line == relative));
+
+    // The core commit records the pushed assets commit
+    let assets_head = git(&assets_remote, &["rev-parse", "main"]);
+    let pointer = git(&core_remote, &["ls-tree", "main", "assets"]);
+    assert!(pointer.contains(assets_head.trim()), "{pointer}");
+}
+
+#[test]
+fn test_sync_without_checked_out_assets() {
+    let (temp_dir, _vault) = setup();
+    let root = temp_dir.path();
+    git(root, &["clone", "-q", "core.git", "phone"]);
+    let phone = root.join("phone");
+    configure(&phone);
+    std::fs::write(phone.join("note.md"), "from the phone\n").unwrap();
+
+    a4(&phone)
+        .arg("sync")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "assets/ submodule is not checked out; syncing the core repository only",
+        ));
+
+    let image = root.join("photo.jpg");
+    std::fs::write(&image, "jpg").unwrap();
+    a4(&phone)
+        .args(["assets", "add"])
+        .arg(&image)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "run 'git submodule update --init assets'",
+        ));
+}
+
+#[test]
+fn test_sync_refuses_detached_assets() {
+    let (_temp_dir, vault) = setup();
+    git(&vault.join("assets"), &["checkout", "-q", "--detach"]);
+
+    a4(&vault)
+        .arg("sync")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("detached HEAD"));
+}
@@ -0,0 +1,132 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn a4(vault: &Path) -> Command {
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    cmd.env("A4_VAULT_DIR", vault);
+    cmd
+}
+
+fn write(root: &Path, relative: &str, content: &str) {
+    let path = root.join(relative);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, content).unwrap();
+}
+
+fn setup() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    let vault = temp_dir.path();
+    write(
+        vault,
+        ".a4/config.toml",
+        "[calendar]\ntimezone = \"America/New_York\"\n",
+    );
+    write(
+        vault,
+        "capture/2025/2025-09/2025-09-12.md",
+        "## Focus\n\n^focus-1400\n\nPlanning\n",
+    );
+    write(
+        vault,
+        "capture/2025/2025-09/2025-09-14.md",
+        "## Focus\n\n^focus-1410\n\n- 14:10–14:45 Parser review\n\n^jrnl-2200\n\nTired\n",
+    );
+    temp_dir
+}
+
+#[test]
+fn test_export_ics_filters_by_prefix_and_day() {
+    let temp_dir = setup();
+
+    a4(temp_dir.path())
+        .args(["export", "ics", "--prefix", "focus", "--since", "2025-09-13"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("BEGIN:VCALENDAR\r\n"))
+        .stdout(predicate::str::contains(
+            "UID:2025-09-14-focus-1410@a4\r\nDTSTAMP:",
+        ))
+        .stdout(predicate::str::contains(
+            "DTSTART:20250914T181000Z\r\nDTEND:20250914T184500Z\r\nSUMMARY:14:10–14:45 Parser review\r\n",
+        ))
+        .stdout(predicate::str::contains("Planning").not())
+        .stdout(predicate::str::contains("Tired").not());
+}
+
+#[test]
+fn test_export_ics_unknown_timezone() {
+    let temp_dir = setup();
+    write(
+        temp_dir.path(),
+        ".a4/config.toml",
+        "[calendar]\ntimezone = \"Mars/Olympus\"\n",
+    );
+
+    a4(temp_dir.path())
+        .args(["export", "ics"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown time zone 'Mars/Olympus'"));
+}
+
+const MEETINGS: &str = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\n\
+BEGIN:VEVENT\r\nUID:standup-1\r\nDTSTART;TZID=America/New_York:20250908T093000\r\n\
+DTEND;TZID=America/New_York:20250908T094500\r\nRRULE:FREQ=DAILY;BYDAY=MO,TU,WE,TH,FR\r\n\
+SUMMARY:Standup\r\nEND:VEVENT\r\n\
+BEGIN:VEVENT\r\nUID:one-on-one\r\nDTSTART:20250915T180000Z\r\nDTEND:20250915T183000Z\r\n\
+SUMMARY:1:1 with Sam\r\nLOCATION:Zoom\r\nEND:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+#[test]
+fn test_import_ics_appends_schedule_once() {
+    let temp_dir = setup();
+    let ics = temp_dir.path().join("work.ics");
+    std::fs::write(&ics, MEETINGS).unwrap();
+    write(
+        temp_dir.path(),
+        "capture/2025/2025-09/2025-09-15.md",
+        "## Schedule\n\n^meet-0930\n\nDentist\n",
+    );
+
+    a4(temp_dir.path())
+        .env("A4_DEVICE", "laptop")
+        .args(["import", "ics", "--day", "2025-09-15"])
+        .arg(&ics)
+        .assert()
+        .success()
+        .stdout("^meet-0930__laptop Standup\n^meet-1400 1:1 with Sam\n");
+
+    let note = std::fs::read_to_string(temp_dir.path().join("capture/2025/2025-09/2025-09-15.md"))
+        .unwrap();
+    assert!(note.starts_with(
+        "## Schedule\n\n^meet-0930\n\nDentist\n\n^meet-0930__laptop\n\n09:30–09:45 Standup\n<!-- ics:"
+    ));
+    assert!(note.contains("\n\n^meet-1400\n\n14:00–14:30 1:1 with Sam\nLocation: Zoom\n<!-- ics:"));
+
+    a4(temp_dir.path())
+        .args(["import", "ics", "--day", "2025-09-15"])
+        .arg(&ics)
+        .assert()
+        .success()
+        .stdout("")
+        .stderr(predicate::str::contains("Skipped 2 already imported"));
+
+    // Weekend: no standup
+    a4(temp_dir.path())
+        .args(["import", "ics", "--day", "2025-09-13"])
+        .arg(&ics)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("No events on 2025-09-13"));
+
+    // The imported blocks export with their time range
+    a4(temp_dir.path())
+        .args(["export", "ics", "--prefix", "meet", "--since", "2025-09-15"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "DTSTART:20250915T133000Z\r\nDTEND:20250915T134500Z\r\nSUMMARY:09:30–09:45 Standup\r\n",
+        ));
+}
@@ -0,0 +1,158 @@
+use a4_core::LocalClock;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+fn git(dir: &Path, args: &[&str]) {
+    let output = std::process::Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "git {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+fn git_stdout(dir: &Path, args: &[&str]) -> String {
+    let output = std::process::Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// A bare remote holding a vault with `.a4/version`, this month's daily note
+/// and one from long ago.
+fn remote_with_vault(temp_dir: &Path, version: &str) -> (PathBuf, String) {
+    let remote = temp_dir.join("notes.git");
+    let seed = temp_dir.join("seed");
+
+    std::fs::create_dir(&remote).unwrap();
+    git(&remote, &["init", "-q", "--bare", "-b", "main"]);
+
+    let today = LocalClock::today_utc();
+    let recent = format!(
+        "capture/{:04}/{}/{}",
+        today.year,
+        today.month(),
+        today.filename()
+    );
+    for (path, content) in [
+        (".a4/version", format!("{version}\n")),
+        ("README.md", "# Vault\n".to_string()),
+        (recent.as_str(), "## Focus\n".to_string()),
+        ("capture/2001/2001-01/2001-01-15.md", "## Old\n".to_string()),
+    ] {
+        let path = seed.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+    git(&seed, &["init", "-q", "-b", "main"]);
+    git(&seed, &["config", "user.email", "test@example.com"]);
+    git(&seed, &["config", "user.name", "Test User"]);
+    git(&seed, &["add", "-A"]);
+    git(&seed, &["commit", "-q", "-m", "initial"]);
+    git(&seed, &["push", "-q", remote.to_str().unwrap(), "main"]);
+
+    (remote, recent)
+}
+
+#[test]
+fn test_clone_full_vault_and_print_export() {
+    let temp_dir = TempDir::new().unwrap();
+    let (remote, recent) = remote_with_vault(temp_dir.path(), "1.0.0");
+
+    let output = Command::cargo_bin("a4")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["clone", remote.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "Command failed with output: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let vault = temp_dir.path().join("notes").canonicalize().unwrap();
+    assert!(vault.join(&recent).exists());
+    assert!(vault.join("capture/2001/2001-01/2001-01-15.md").exists());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Protocol version: 1.0.0"));
+    assert!(stdout.ends_with(&format!("export A4_VAULT_DIR='{}'\n", vault.display())));
+
+    // The clone is a working vault
+    Command::cargo_bin("a4")
+        .unwrap()
+        .env("A4_VAULT_DIR", &vault)
+        .arg("root")
+        .assert()
+        .success()
+        .stdout(format!("{}\n", vault.display()));
+}
+
+#[test]
+fn test_clone_keeps_recent_months() {
+    let temp_dir = TempDir::new().unwrap();
+    let (remote, recent) = remote_with_vault(temp_dir.path(), "1.2.0");
+    let dest = temp_dir.path().join("laptop");
+
+    Command::cargo_bin("a4")
+        .unwrap()
+        .args(["clone", remote.to_str().unwrap()])
+        .arg(&dest)
+        .args(["--keep-months", "2"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Sparse checkout: keeping the last 2 capture months",
+        ));
+
+    assert!(dest.join("README.md").exists());
+    assert!(dest.join(".a4/version").exists());
+    assert!(dest.join(&recent).exists());
+    assert!(!dest.join("capture/2001").exists());
+
+    // Pruned months are skip-worktree entries, not deletions.
+    assert_eq!(git_stdout(&dest, &["status", "--porcelain"]), "");
+    assert!(git_stdout(&dest, &["ls-files", "-v", "capture/2001"]).starts_with("S "));
+    assert_eq!(
+        git_stdout(&dest, &["config", "core.sparseCheckout"]).trim(),
+        "true"
+    );
+}
+
+#[test]
+fn test_clone_rejects_incompatible_version() {
+    let temp_dir = TempDir::new().unwrap();
+    let (remote, _) = remote_with_vault(temp_dir.path(), "2.0.0");
+    let dest = temp_dir.path().join("laptop");
+
+    Command::cargo_bin("a4")
+        .unwrap()
+        .args(["clone", remote.to_str().unwrap()])
+        .arg(&dest)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Vault targets A4 protocol 2.0.0, but this a4 supports 1.0.0",
+        ));
+    assert!(!dest.exists());
+
+    std::fs::create_dir(&dest).unwrap();
+    std::fs::write(dest.join("keep.md"), "mine\n").unwrap();
+    Command::cargo_bin("a4")
+        .unwrap()
+        .args(["clone", remote.to_str().unwrap()])
+        .arg(&dest)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("directory is not empty"));
+}
@@ -0,0 +1,46 @@
+use assert_cmd::cargo::cargo_bin;
+use std::process::Command;
+use tempfile::TempDir;
+
+const WRITERS: usize = 24;
+
+#[test]
+fn test_parallel_append_processes_lose_no_blocks() {
+    let vault = TempDir::new().unwrap();
+    let note = vault.path().join("capture/2025/2025-09/2025-09-14.md");
+
+    let children: Vec<_> = (0..WRITERS)
+        .map(|i| {
+            Command::new(cargo_bin("a4"))
+                .env("A4_VAULT_DIR", vault.path())
+                .args(["append", "--heading", "Journal", "--anchor"])
+                .arg(format!("jrnl-0930__p{i}"))
+                .arg("--file")
+                .arg(&note)
+                .arg("--text")
+                .arg(format!("entry {i}"))
+                .spawn()
+                .unwrap()
+        })
+        .collect();
+    for mut child in children {
+        assert!(child.wait().unwrap().success());
+    }
+
+    let content = std::fs::read_to_string(&note).unwrap();
+    let lost: Vec<_> = (0..WRITERS)
+        .filter(|i| !content.contains(&format!("^jrnl-0930__p{i}\n\nentry {i}\n")))
+        .collect();
+    assert!(lost.is_empty(), "lost appends {lost:?} in:\n{content}");
+    assert_eq!(content.matches("## Journal").count(), 1);
+
+    let leftovers: Vec<_> = std::fs::read_dir(note.parent().unwrap())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .filter(|name| name != "2025-09-14.md")
+        .collect();
+    assert!(leftovers.is_empty(), "temporary files left: {leftovers:?}");
+
+    let ignore = std::fs::read_to_string(vault.path().join(".a4/.gitignore")).unwrap();
+    assert!(ignore.lines().any(|line| line == "cache"));
+}
@@ -0,0 +1,174 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+const JOURNAL: &str = "capture/2025/2025-09/2025-09-14.md";
+const PLAN: &str = "capture/2025/2025-09/2025-09-15.md";
+
+fn git(dir: &Path, args: &[&str]) -> Vec<u8> {
+    let output = std::process::Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "git {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output.stdout
+}
+
+fn configure(dir: &Path) {
+    git(dir, &["config", "user.email", "test@example.com"]);
+    git(dir, &["config", "user.name", "Test User"]);
+}
+
+fn a4(vault: &Path) -> Command {
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    cmd.env("A4_VAULT_DIR", vault);
+    cmd
+}
+
+/// A bare remote and a vault clone whose config encrypts capture notes
+/// tagged `private`.
+fn setup() -> (TempDir, PathBuf) {
+    let temp_dir = TempDir::new().unwrap();
+    let remote = temp_dir.path().join("remote.git");
+    let vault = temp_dir.path().join("vault");
+
+    std::fs::create_dir(&remote).unwrap();
+    git(&remote, &["init", "-q", "--bare", "-b", "main"]);
+
+    std::fs::create_dir_all(vault.join(".a4")).unwrap();
+    git(&vault, &["init", "-q", "-b", "main"]);
+    configure(&vault);
+    git(&vault, &["remote", "add", "origin", "../remote.git"]);
+    std::fs::write(
+        vault.join(".a4/config.toml"),
+        "[crypt]\npaths = [\"capture/**\"]\ntags = [\"private\"]\n",
+    )
+    .unwrap();
+    git(&vault, &["add", "-A"]);
+    git(&vault, &["commit", "-q", "-m", "initial"]);
+    git(&vault, &["push", "-q", "-u", "origin", "main"]);
+
+    (temp_dir, vault)
+}
+
+fn remote_blob(temp_dir: &TempDir, path: &str) -> Vec<u8> {
+    git(
+        &temp_dir.path().join("remote.git"),
+        &["cat-file", "blob", &format!("main:{path}")],
+    )
+}
+
+fn porcelain(dir: &Path) -> String {
+    String::from_utf8(git(dir, &["status", "--porcelain"])).unwrap()
+}
+
+#[test]
+fn test_private_notes_are_encrypted_in_git_only() {
+    let (temp_dir, vault) = setup();
+
+    a4(&vault)
+        .args(["crypt", "init"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Public key: age1"));
+    assert!(std::fs::read_to_string(vault.join(".a4/.gitignore"))
+        .unwrap()
+        .contains("age.key"));
+
+    std::fs::create_dir_all(vault.join("capture/2025/2025-09")).unwrap();
+    std::fs::write(
+        vault.join(JOURNAL),
+        "---\ntags: [private]\n---\n## Evening\n",
+    )
+    .unwrap();
+    std::fs::write(vault.join(PLAN), "## Focus\n").unwrap();
+    a4(&vault)
+        .args(["append", "--heading", "Evening", "--anchor", "jrnl-2200"])
+        .args(["--text", "a very private thought", "--file"])
+        .arg(vault.join(JOURNAL))
+        .assert()
+        .success();
+
+    a4(&vault)
+        .arg("sync")
+        .assert()
+        .success()
+        .stdout("Sync completed successfully\n");
+
+    let journal = remote_blob(&temp_dir, JOURNAL);
+    assert!(journal.starts_with(b"age-encryption.org/v1\n"));
+    assert!(!String::from_utf8_lossy(&journal).contains("private thought"));
+    assert_eq!(remote_blob(&temp_dir, PLAN), b"## Focus\n");
+
+    // The working copy stays decrypted and clean
+    assert!(std::fs::read_to_string(vault.join(JOURNAL))
+        .unwrap()
+        .contains("a very private thought"));
+    assert_eq!(porcelain(&vault), "");
+
+    // The generated message still sees the anchor through the diff driver
+    let log = String::from_utf8(git(&vault, &["log", "-1", "--format=%B"])).unwrap();
+    assert!(log.contains("^jrnl-2200"), "{log}");
+}
+
+#[test]
+fn test_other_device_needs_key() {
+    let (temp_dir, vault) = setup();
+    a4(&vault).args(["crypt", "init"]).assert().success();
+    std::fs::create_dir_all(vault.join("capture/2025/2025-09")).unwrap();
+    std::fs::write(
+        vault.join(JOURNAL),
+        "---\ntags: private\n---\n## Evening\n\n^jrnl-2200\n\nfirst\n",
+    )
+    .unwrap();
+    a4(&vault).arg("sync").assert().success();
+
+    git(temp_dir.path(), &["clone", "-q", "remote.git", "laptop"]);
+    let laptop = temp_dir.path().join("laptop");
+    configure(&laptop);
+    assert!(std::fs::read(laptop.join(JOURNAL))
+        .unwrap()
+        .starts_with(b"age-encryption.org/v1\n"));
+
+    a4(&laptop)
+        .arg("sync")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("run 'a4 crypt init'"));
+    a4(&laptop)
+        .args(["append", "--heading", "Evening", "--anchor", "jrnl-2300"])
+        .args(["--text", "second", "--file"])
+        .arg(laptop.join(JOURNAL))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("this device has no key"));
+
+    std::fs::copy(vault.join(".a4/age.key"), laptop.join(".a4/age.key")).unwrap();
+    a4(&laptop)
+        .args(["crypt", "init"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!("Decrypted {JOURNAL}")));
+    // The failed append above already listed its lock directory in
+    // .a4/.gitignore; the decrypted note itself is unchanged.
+    assert_eq!(porcelain(&laptop), " M .a4/.gitignore\n");
+
+    a4(&laptop)
+        .args(["append", "--heading", "Evening", "--anchor", "jrnl-2300"])
+        .args(["--text", "second", "--file"])
+        .arg(laptop.join(JOURNAL))
+        .assert()
+        .success();
+    a4(&laptop).arg("sync").assert().success();
+
+    a4(&vault).arg("sync").assert().success();
+    let journal = std::fs::read_to_string(vault.join(JOURNAL)).unwrap();
+    assert!(journal.contains("first") && journal.contains("second"));
+    assert_eq!(porcelain(&vault), "");
+}
@@ -0,0 +1,63 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn a4(vault: &Path) -> Command {
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    cmd.env("A4_VAULT_DIR", vault);
+    cmd
+}
+
+fn write(root: &Path, relative: &str, content: &str) {
+    let path = root.join(relative);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, content).unwrap();
+}
+
+#[test]
+fn test_export_html_site() {
+    let temp_dir = TempDir::new().unwrap();
+    let vault = temp_dir.path().join("vault");
+    write(
+        &vault,
+        "capture/2025/2025-09/2025-09-14.md",
+        "## Focus\n\n^focus-0930\n\nParser work with [[projects/parser/index|the parser]]\n",
+    );
+    write(
+        &vault,
+        "capture/2025/2025-10/2025-10-02.md",
+        "## Notes\n\n^idea-1000\n\nUse a trie\n",
+    );
+    write(
+        &vault,
+        "projects/parser/index.md",
+        "# Parser\n\n![[capture/2025-09-14#^focus-0930]]\n",
+    );
+    let out = temp_dir.path().join("site");
+
+    a4(&vault)
+        .args(["export", "html", "--out"])
+        .arg(&out)
+        .assert()
+        .success()
+        .stdout(predicate::str::ends_with("index.html\n"))
+        .stderr(predicate::str::contains("Exported 3 notes and 0 assets"));
+
+    let index = std::fs::read_to_string(out.join("index.html")).unwrap();
+    let october = index.find("October 2025").unwrap();
+    let september = index.find("September 2025").unwrap();
+    assert!(october < september, "newest month first:\n{index}");
+    assert!(index.contains("href=\"projects/parser/index.html\">Parser</a>"));
+
+    let project = std::fs::read_to_string(out.join("projects/parser/index.html")).unwrap();
+    assert!(project.contains(
+        "<p>Parser work with <a href=\"../../projects/parser/index.html\">the parser</a></p>"
+    ));
+    assert!(project.contains("href=\"../../capture/2025/2025-09/2025-09-14.html\""));
+
+    let day = std::fs::read_to_string(out.join("capture/2025/2025-09/2025-09-14.html")).unwrap();
+    assert!(day.contains("<div class=\"anchor\" id=\"^focus-0930\">"));
+    assert!(day.contains("<h2 id=\"focus\">Focus</h2>"));
+    assert!(out.join("style.css").exists());
+}
@@ -0,0 +1,77 @@
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+fn a4(vault: &std::path::Path) -> Command {
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    cmd.env("A4_VAULT_DIR", vault);
+    cmd
+}
+
+#[test]
+fn test_inbox_drop_and_triage() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let output = a4(temp_dir.path())
+        .args([
+            "inbox",
+            "--anchor",
+            "inbox-0930",
+            "--text",
+            "- call dentist",
+        ])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "Command failed with output: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let (paste_zone, marker) = stdout.trim().rsplit_once('#').unwrap();
+    assert!(paste_zone.ends_with("--paste-zone.md"));
+    assert_eq!(marker, "^inbox-0930");
+
+    // Listing shows the untriaged block
+    let output = a4(temp_dir.path())
+        .args(["inbox", "triage"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let listing = String::from_utf8_lossy(&output.stdout);
+    assert!(listing.contains("^inbox-0930  - call dentist"));
+
+    // Move it into a project log
+    let output = a4(temp_dir.path())
+        .args([
+            "inbox",
+            "triage",
+            "--anchor",
+            "inbox-0930",
+            "--file",
+            "projects/health/log.md",
+            "--heading",
+            "Errands",
+        ])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "Command failed with output: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let log = std::fs::read_to_string(temp_dir.path().join("projects/health/log.md")).unwrap();
+    assert!(log.contains("## Errands\n\n^inbox-0930\n\n- call dentist\n"));
+
+    let inbox = std::fs::read_to_string(paste_zone).unwrap();
+    assert!(inbox.contains("moved ^inbox-0930 → [[projects/health/log#^inbox-0930]]"));
+
+    // Nothing left to triage
+    let output = a4(temp_dir.path())
+        .args(["inbox", "triage"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+}
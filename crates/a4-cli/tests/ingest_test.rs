@@ -0,0 +1,96 @@
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+fn a4(vault: &std::path::Path) -> Command {
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    cmd.env("A4_VAULT_DIR", vault);
+    cmd
+}
+
+#[test]
+fn test_ingest_article_with_daily_link() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let output = a4(temp_dir.path())
+        .args([
+            "ingest",
+            "article",
+            "--title",
+            "Karpathy: State of GPT",
+            "--url",
+            "https://example.com/gpt",
+            "--text",
+            "- Models are trained in stages",
+            "--daily",
+        ])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "Command failed with output: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let source_path = temp_dir
+        .path()
+        .join("sources/articles/karpathy-state-of-gpt.md");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout
+        .trim()
+        .ends_with("sources/articles/karpathy-state-of-gpt.md"));
+
+    let source = std::fs::read_to_string(&source_path).unwrap();
+    assert!(source.starts_with("---\nkind: source.article\n"));
+    assert!(source.contains("origin: { url: \"https://example.com/gpt\", via: \"a4 ingest\" }"));
+    assert!(source.contains("## Highlights\n\n- Models are trained in stages\n"));
+
+    let link = regex::Regex::new(
+        r"- Referenced in \[\[capture/\d{4}/\d{4}-\d{2}/\d{4}-\d{2}-\d{2}#\^read-\d{4}\]\]",
+    )
+    .unwrap();
+    assert!(link.is_match(&source), "Missing daily reference: {source}");
+
+    let today = a4(temp_dir.path()).arg("today").output().unwrap();
+    let daily_path = String::from_utf8_lossy(&today.stdout).trim().to_string();
+    let daily = std::fs::read_to_string(daily_path).unwrap();
+    let block = regex::Regex::new(
+        r"## Reading\n\n\^read-\d{4}\n\n- \[\[sources/articles/karpathy-state-of-gpt\]\] — Karpathy: State of GPT\n",
+    )
+    .unwrap();
+    assert!(block.is_match(&daily), "Missing read block: {daily}");
+
+    // Ingesting the same slug again must not overwrite the note
+    let output = a4(temp_dir.path())
+        .args(["ingest", "article", "--title", "Karpathy: State of GPT"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("already exists"));
+}
+
+#[test]
+fn test_ingest_article_from_local_html() {
+    let temp_dir = TempDir::new().unwrap();
+    let html_path = temp_dir.path().join("saved.html");
+    std::fs::write(
+        &html_path,
+        "<html><head><title>Saved Page</title></head>\
+         <body><h2>Intro</h2><p>Hello <em>world</em>.</p></body></html>",
+    )
+    .unwrap();
+
+    let output = a4(temp_dir.path())
+        .args(["ingest", "article", "--html", html_path.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "Command failed with output: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let source =
+        std::fs::read_to_string(temp_dir.path().join("sources/articles/saved-page.md")).unwrap();
+    assert!(source.contains("title: \"Saved Page\""));
+    assert!(source.contains("## Content\n\n## Intro\n\nHello *world*.\n"));
+}
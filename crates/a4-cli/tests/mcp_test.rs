@@ -0,0 +1,167 @@
+use assert_cmd::Command;
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+/// Send newline-delimited JSON-RPC messages and collect the responses.
+fn mcp(vault: &std::path::Path, extra_args: &[&str], messages: &[Value]) -> Vec<Value> {
+    let input: String = messages.iter().map(|m| format!("{m}\n")).collect();
+
+    let output = Command::cargo_bin("a4")
+        .unwrap()
+        .env("A4_VAULT_DIR", vault)
+        .arg("mcp")
+        .args(extra_args)
+        .write_stdin(input)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "Command failed with output: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+fn call(id: u64, name: &str, arguments: Value) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": "tools/call",
+        "params": { "name": name, "arguments": arguments },
+    })
+}
+
+fn text(response: &Value) -> &str {
+    response["result"]["content"][0]["text"].as_str().unwrap()
+}
+
+#[test]
+fn test_mcp_handshake_and_tools() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let responses = mcp(
+        temp_dir.path(),
+        &[],
+        &[
+            json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": { "protocolVersion": "2024-11-05" } }),
+            json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }),
+            json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/list" }),
+            json!({ "jsonrpc": "2.0", "id": 3, "method": "bogus" }),
+            json!({ "jsonrpc": "2.0", "id": 4, "method": "initialize", "params": { "protocolVersion": "2099-01-01" } }),
+        ],
+    );
+
+    assert_eq!(responses.len(), 4);
+    assert_eq!(responses[0]["result"]["serverInfo"]["name"], "a4");
+    assert_eq!(responses[0]["result"]["protocolVersion"], "2024-11-05");
+    let tools: Vec<_> = responses[1]["result"]["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|tool| tool["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(tools, vec!["today", "append", "search", "read", "list"]);
+    assert_eq!(responses[2]["error"]["code"], -32601);
+    // An unsupported version is answered with the one the server speaks
+    assert_eq!(responses[3]["result"]["protocolVersion"], "2024-11-05");
+}
+
+#[test]
+fn test_mcp_append_read_search_list() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let responses = mcp(
+        temp_dir.path(),
+        &[],
+        &[
+            call(
+                1,
+                "append",
+                json!({ "file": "notes/ideas.md", "heading": "Ideas", "anchor": "idea-0930", "content": "Block-level search" }),
+            ),
+            call(
+                2,
+                "append",
+                json!({ "file": "notes/ideas.md", "heading": "Ideas", "anchor": "idea", "content": "no time" }),
+            ),
+            call(
+                3,
+                "read",
+                json!({ "path": "notes/ideas", "block": "^idea-0930" }),
+            ),
+            call(
+                4,
+                "read",
+                json!({ "path": "notes/ideas", "heading": "Ideas" }),
+            ),
+            call(5, "search", json!({ "query": "block-level" })),
+            call(6, "list", json!({ "dir": "notes" })),
+            call(
+                7,
+                "append",
+                json!({ "file": "../escape.md", "heading": "X", "anchor": "x-0900", "content": "no" }),
+            ),
+            call(
+                8,
+                "append",
+                json!({ "file": ".git/config", "heading": "X", "anchor": "x-0900", "content": "no" }),
+            ),
+            call(
+                9,
+                "append",
+                json!({ "file": ".a4/config.toml", "heading": "X", "anchor": "x-0900", "content": "no" }),
+            ),
+        ],
+    );
+
+    assert_eq!(responses[0]["result"]["isError"], false);
+    assert!(text(&responses[0]).ends_with("notes/ideas.md#^idea-0930"));
+    assert_eq!(responses[1]["result"]["isError"], true);
+    assert!(text(&responses[1]).contains("Invalid anchor token"));
+    assert_eq!(text(&responses[2]), "Block-level search");
+    assert_eq!(text(&responses[3]), "^idea-0930\n\nBlock-level search");
+    assert_eq!(
+        text(&responses[4]),
+        "notes/ideas.md#^idea-0930:7  Block-level search"
+    );
+    assert_eq!(text(&responses[5]), "notes/ideas");
+    assert_eq!(responses[6]["result"]["isError"], true);
+    assert!(!temp_dir.path().parent().unwrap().join("escape.md").exists());
+    for response in &responses[7..] {
+        assert_eq!(response["result"]["isError"], true);
+        assert!(text(response).contains("Not a note"));
+    }
+    assert!(!temp_dir.path().join(".git/config").exists());
+    assert!(!temp_dir.path().join(".a4/config.toml").exists());
+}
+
+#[test]
+fn test_mcp_read_only() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let responses = mcp(
+        temp_dir.path(),
+        &["--read-only"],
+        &[
+            json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" }),
+            call(
+                2,
+                "append",
+                json!({ "heading": "Focus", "anchor": "focus-0930", "content": "nope" }),
+            ),
+            call(3, "today", json!({})),
+        ],
+    );
+
+    let tools = responses[0]["result"]["tools"].as_array().unwrap();
+    assert!(tools.iter().all(|tool| tool["name"] != "append"));
+    assert_eq!(responses[1]["result"]["isError"], true);
+    assert!(text(&responses[1]).contains("read-only"));
+    assert!(!std::path::Path::new(text(&responses[2])).exists());
+    assert!(!temp_dir.path().join("capture").exists());
+}
@@ -0,0 +1,146 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use tempfile::TempDir;
+
+fn write(root: &std::path::Path, path: &str, content: &str) {
+    let path = root.join(path);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+}
+
+fn a4(vault: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    cmd.env("A4_VAULT_DIR", vault.path());
+    cmd
+}
+
+fn today() -> String {
+    let now = time::OffsetDateTime::now_utc();
+    format!(
+        "{:04}-{:02}-{:02}",
+        now.year(),
+        now.month() as u8,
+        now.day()
+    )
+}
+
+#[test]
+fn test_today_honours_obsidian_daily_notes() {
+    let vault = TempDir::new().unwrap();
+    write(
+        vault.path(),
+        ".a4/config.toml",
+        "[obsidian]\ndaily_notes = true\n",
+    );
+    write(
+        vault.path(),
+        ".obsidian/daily-notes.json",
+        r#"{"folder":"Daily","format":"YYYY-MM-DD","template":"Templates/Daily"}"#,
+    );
+    write(
+        vault.path(),
+        "Templates/Daily.md",
+        "# {{title}}\n\nStarted {{date:YYYY-MM-DD}} at <% tp.date.now(\"HHmm\") %>\n<% tp.file.cursor() %>\n",
+    );
+
+    let output = a4(&vault).arg("today").output().unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("No A4 equivalent for <% tp.file.cursor() %>"));
+
+    let path = vault.path().join(format!("Daily/{}.md", today()));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).trim(),
+        path.display().to_string()
+    );
+
+    let content = fs::read_to_string(&path).unwrap();
+    assert!(content.starts_with(&format!("# {}\n\nStarted {} at ", today(), today())));
+    assert!(!content.contains("tp.date"));
+    assert!(content.contains("<% tp.file.cursor() %>"));
+
+    a4(&vault)
+        .args([
+            "append",
+            "--today",
+            "--heading",
+            "Journal",
+            "--anchor",
+            "note-0930",
+            "--text",
+            "Hello",
+        ])
+        .assert()
+        .success();
+    assert!(fs::read_to_string(&path).unwrap().contains("^note-0930"));
+}
+
+#[test]
+fn test_today_ignores_obsidian_settings_by_default() {
+    let vault = TempDir::new().unwrap();
+    write(
+        vault.path(),
+        ".obsidian/daily-notes.json",
+        r#"{"folder":"Daily"}"#,
+    );
+
+    a4(&vault)
+        .arg("today")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("capture"));
+    assert!(!vault.path().join("Daily").exists());
+}
+
+#[test]
+fn test_obsidian_check_reports_invalid_block_ids() {
+    let vault = TempDir::new().unwrap();
+    write(
+        vault.path(),
+        "capture/2025/2025-09/2025-09-14.md",
+        "^jrnl-0812\nOne\n\n^jrnl-0812__iphone\nTwo\n",
+    );
+
+    a4(&vault)
+        .args(["obsidian", "check"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "2025-09-14.md:4: ^jrnl-0812__iphone is not an Obsidian block ID",
+        ))
+        .stderr(predicate::str::contains("1 anchor(s) cannot be linked"));
+
+    write(
+        vault.path(),
+        "capture/2025/2025-09/2025-09-14.md",
+        "^jrnl-0812\nOne\n",
+    );
+    a4(&vault)
+        .args(["obsidian", "check"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Daily notes: no .obsidian/daily-notes.json",
+        ))
+        .stdout(predicate::str::contains(
+            "Anchors: all are valid Obsidian block IDs",
+        ));
+}
+
+#[test]
+fn test_convert_template() {
+    let vault = TempDir::new().unwrap();
+    write(
+        vault.path(),
+        "Templates/Daily.md",
+        "# {{date}}\n<% tp.file.title %> <% tp.config.target_file %>\n",
+    );
+
+    a4(&vault)
+        .args(["obsidian", "convert-template", "Templates/Daily.md"])
+        .assert()
+        .success()
+        .stdout("# {{date}}\n{{date}} <% tp.config.target_file %>\n")
+        .stderr(predicate::str::contains("<% tp.config.target_file %>"));
+}
@@ -0,0 +1,75 @@
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+fn a4(vault: &std::path::Path) -> Command {
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    cmd.env("A4_VAULT_DIR", vault);
+    cmd
+}
+
+#[test]
+fn test_project_new_log_and_list() {
+    let temp_dir = TempDir::new().unwrap();
+    let template_dir = temp_dir.path().join("routines").join("templates");
+    std::fs::create_dir_all(&template_dir).unwrap();
+    std::fs::write(
+        template_dir.join("project-index.md"),
+        "# Project {{slug}}\n\nStarted {{YYYY-MM-DD}}\n",
+    )
+    .unwrap();
+
+    let output = a4(temp_dir.path())
+        .args(["project", "new", "gb-ppu"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "Command failed with output: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let project_dir = temp_dir.path().join("projects").join("gb-ppu");
+    let index = std::fs::read_to_string(project_dir.join("index.md")).unwrap();
+    assert!(index.starts_with("# Project gb-ppu\n\nStarted "));
+    assert!(!index.contains("{{"));
+
+    // No log template: the built-in default is used
+    let log = std::fs::read_to_string(project_dir.join("log.md")).unwrap();
+    assert_eq!(log, "# gb-ppu log\n");
+
+    let output = a4(temp_dir.path())
+        .args(["project", "log", "gb-ppu", "--text", "- sprite fetch works"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let log = std::fs::read_to_string(project_dir.join("log.md")).unwrap();
+    let entry =
+        regex::Regex::new(r"## \d{4}-\d{2}-\d{2}\n\n\^log-\d{4}\n\n- sprite fetch works\n$")
+            .unwrap();
+    assert!(entry.is_match(&log), "Unexpected log: {log}");
+
+    let output = a4(temp_dir.path())
+        .args(["project", "list"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let listing = String::from_utf8_lossy(&output.stdout);
+    let row = regex::Regex::new(r"^\d{4}-\d{2}-\d{2}\s+gb-ppu$").unwrap();
+    assert!(
+        row.is_match(listing.trim()),
+        "Unexpected listing: {listing}"
+    );
+}
+
+#[test]
+fn test_project_new_rejects_invalid_slug() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let output = a4(temp_dir.path())
+        .args(["project", "new", "Not A Slug"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Invalid slug"));
+}
@@ -0,0 +1,91 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn a4(vault: &Path) -> Command {
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    cmd.env("A4_VAULT_DIR", vault);
+    cmd
+}
+
+fn write(root: &Path, relative: &str, content: &str) {
+    let path = root.join(relative);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, content).unwrap();
+}
+
+fn setup() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    let vault = temp_dir.path();
+    write(
+        vault,
+        ".a4/config.toml",
+        "[publish]\nmedia_url = \"/images\"\nfront_matter = { layout = \"post\", draft = false }\n",
+    );
+    write(
+        vault,
+        "capture/2025/2025-09/2025-09-14.md",
+        "## End of Day\n\n^eod-2215\n\nShipped the parser\n\n![diagram](assets/img/2025/2025-09/diagram.png)\n\n^jrnl-2230\n\nWorried about the launch\n\n^idea-2240\n\nAsk Sam about the offsite #private\n",
+    );
+    write(vault, "assets/img/2025/2025-09/diagram.png", "png");
+    write(
+        vault,
+        "collections/memos/parser.md",
+        "---\ntitle: Shipping the Parser\nkind: collection.memo\n---\n# Shipping the Parser\n\n![[capture/2025-09-14#End of Day]]\n",
+    );
+    temp_dir
+}
+
+#[test]
+fn test_publish_redacts_and_copies_media() {
+    let temp_dir = setup();
+    let vault = temp_dir.path();
+    let out = vault.join("site");
+
+    a4(vault)
+        .args([
+            "publish",
+            "collections/memos/parser",
+            "--date",
+            "2025-09-20",
+            "--out",
+        ])
+        .arg(&out)
+        .assert()
+        .success()
+        .stdout(format!(
+            "{}\n",
+            out.join("shipping-the-parser.md").display()
+        ));
+
+    assert_eq!(
+        std::fs::read_to_string(out.join("shipping-the-parser.md")).unwrap(),
+        "---\ntitle: Shipping the Parser\ndate: 2025-09-20\nslug: shipping-the-parser\ndraft: false\nlayout: post\n---\n# Shipping the Parser\n\nShipped the parser\n\n![diagram](/images/img/2025/2025-09/diagram.png)\n"
+    );
+    assert_eq!(
+        std::fs::read_to_string(out.join("media/img/2025/2025-09/diagram.png")).unwrap(),
+        "png"
+    );
+}
+
+#[test]
+fn test_publish_refuses_capture_links() {
+    let temp_dir = setup();
+    let vault = temp_dir.path();
+    write(
+        vault,
+        "collections/memos/leaky.md",
+        "# Leaky\n\nDetails in [[capture/2025-09-14]].\n",
+    );
+
+    a4(vault)
+        .args(["publish", "collections/memos/leaky", "--out"])
+        .arg(vault.join("site"))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Refusing to publish: links into capture/ remain:\n  line 3: [[capture/2025-09-14]]",
+        ));
+    assert!(!vault.join("site").exists());
+}
@@ -0,0 +1,61 @@
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+fn write_day(vault: &std::path::Path, day: &str, content: &str) {
+    let path = vault
+        .join("capture")
+        .join(&day[..4])
+        .join(&day[..7])
+        .join(format!("{day}.md"));
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, content).unwrap();
+}
+
+#[test]
+fn test_rollup_month_uses_configured_prefixes() {
+    let temp_dir = TempDir::new().unwrap();
+    write_day(
+        temp_dir.path(),
+        "2025-09-14",
+        "## Journal\n\n^jrnl-0812\n\nSlept well\n\n## End of Day\n\n^eod-2215\n\nShipped\n",
+    );
+    std::fs::create_dir_all(temp_dir.path().join(".a4")).unwrap();
+    std::fs::write(
+        temp_dir.path().join(".a4/config.toml"),
+        "[rollup]\nprefixes = [\"eod\"]\n",
+    )
+    .unwrap();
+
+    let output = Command::cargo_bin("a4")
+        .unwrap()
+        .env("A4_VAULT_DIR", temp_dir.path())
+        .args(["rollup", "month", "2025-09"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "Command failed with output: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let rollup =
+        std::fs::read_to_string(temp_dir.path().join("collections/journals/2025/2025-09.md"))
+            .unwrap();
+    assert!(rollup.contains("![[capture/2025/2025-09/2025-09-14#^eod-2215]]"));
+    assert!(!rollup.contains("jrnl-0812"));
+
+    // --prefix overrides the config; --inline copies content
+    let output = Command::cargo_bin("a4")
+        .unwrap()
+        .env("A4_VAULT_DIR", temp_dir.path())
+        .args(["rollup", "month", "2025-09", "--prefix", "jrnl", "--inline"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let rollup =
+        std::fs::read_to_string(temp_dir.path().join("collections/journals/2025/2025-09.md"))
+            .unwrap();
+    assert!(rollup.contains("Slept well\n\n— [[capture/2025/2025-09/2025-09-14#^jrnl-0812]]"));
+    assert!(!rollup.contains("eod-2215"));
+}
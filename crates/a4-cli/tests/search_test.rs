@@ -0,0 +1,57 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn a4(vault: &std::path::Path) -> Command {
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    cmd.env("A4_VAULT_DIR", vault);
+    cmd
+}
+
+fn setup() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    let day = temp_dir.path().join("capture/2025/2025-09");
+    std::fs::create_dir_all(&day).unwrap();
+    std::fs::write(
+        day.join("2025-09-14.md"),
+        "## End of Day\n\n^eod-2215\n\nShipped the search command\n",
+    )
+    .unwrap();
+    std::fs::create_dir_all(temp_dir.path().join("collections")).unwrap();
+    std::fs::write(
+        temp_dir.path().join("collections/w37.md"),
+        "# W37\n\n![[capture/2025-09-14#^eod-2215]]\n",
+    )
+    .unwrap();
+    temp_dir
+}
+
+#[test]
+fn test_search_prints_locations() {
+    let temp_dir = setup();
+
+    a4(temp_dir.path())
+        .args(["search", "SEARCH command"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "capture/2025/2025-09/2025-09-14.md#^eod-2215:5  Shipped the search command",
+        ));
+}
+
+#[test]
+fn test_stitch_renders_embeds() {
+    let temp_dir = setup();
+
+    a4(temp_dir.path())
+        .args(["stitch", "collections/w37"])
+        .assert()
+        .success()
+        .stdout("# W37\n\nShipped the search command\n");
+
+    a4(temp_dir.path())
+        .args(["stitch", "collections/missing"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Note not found"));
+}
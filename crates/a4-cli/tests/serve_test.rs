@@ -0,0 +1,232 @@
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::process::{Child, Command, Stdio};
+use tempfile::TempDir;
+
+/// Kills the server when the test ends, even on panic.
+struct Server {
+    child: Child,
+    addr: String,
+    token: String,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+impl Server {
+    fn start(vault: &std::path::Path) -> Server {
+        let mut child = Command::new(assert_cmd::cargo::cargo_bin("a4"))
+            .env("A4_VAULT_DIR", vault)
+            .args(["serve", "--listen", "127.0.0.1:0"])
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        let mut line = String::new();
+        BufReader::new(child.stdout.take().unwrap())
+            .read_line(&mut line)
+            .unwrap();
+        let addr = line
+            .trim()
+            .strip_prefix("Listening on http://")
+            .unwrap_or_else(|| panic!("unexpected output: {line:?}"))
+            .to_string();
+        let token = std::fs::read_to_string(vault.join(".a4/serve-token"))
+            .unwrap()
+            .trim()
+            .to_string();
+
+        Server { child, addr, token }
+    }
+
+    fn request(&self, method: &str, path: &str, body: Option<Value>) -> (u16, Value) {
+        raw_request(&self.addr, method, path, Some(&self.token), body)
+    }
+}
+
+fn raw_request(
+    addr: &str,
+    method: &str,
+    path: &str,
+    token: Option<&str>,
+    body: Option<Value>,
+) -> (u16, Value) {
+    let body = body.map(|b| b.to_string()).unwrap_or_default();
+    let auth = token
+        .map(|t| format!("Authorization: Bearer {t}\r\n"))
+        .unwrap_or_default();
+
+    let mut stream = TcpStream::connect(addr).unwrap();
+    write!(
+        stream,
+        "{method} {path} HTTP/1.1\r\nHost: {addr}\r\n{auth}Content-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+    .unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+    (status, serde_json::from_str(body).unwrap())
+}
+
+#[test]
+fn test_serve_requires_token() {
+    let temp_dir = TempDir::new().unwrap();
+    let server = Server::start(temp_dir.path());
+
+    let (status, body) = raw_request(&server.addr, "GET", "/today", None, None);
+    assert_eq!(status, 401);
+    assert!(body["error"].as_str().unwrap().contains("token"));
+
+    let (status, _) = raw_request(&server.addr, "GET", "/today", Some("wrong"), None);
+    assert_eq!(status, 401);
+
+    let ignore = std::fs::read_to_string(temp_dir.path().join(".a4/.gitignore")).unwrap();
+    assert!(ignore.lines().any(|line| line == "serve-token"));
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let metadata = std::fs::metadata(temp_dir.path().join(".a4/serve-token")).unwrap();
+        assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+    }
+}
+
+#[test]
+fn test_serve_today_append_search_stitch() {
+    let temp_dir = TempDir::new().unwrap();
+    let server = Server::start(temp_dir.path());
+
+    let (status, body) = server.request("GET", "/today", None);
+    assert_eq!(status, 200);
+    let today = body["path"].as_str().unwrap().to_string();
+    assert!(std::path::Path::new(&today).exists());
+
+    let (status, body) = server.request(
+        "POST",
+        "/append",
+        Some(json!({ "heading": "Focus", "anchor": "focus-0930", "content": "Ship the HTTP API" })),
+    );
+    assert_eq!(status, 200, "{body}");
+    assert_eq!(body["anchor"], "^focus-0930");
+    assert_eq!(body["path"], today);
+
+    let (status, body) = server.request(
+        "POST",
+        "/append",
+        Some(json!({
+            "heading": "Draft",
+            "anchor": "memo-1000",
+            "content": "![[notes/ideas#^idea-0800]]",
+            "file": "notes/memo.md"
+        })),
+    );
+    assert_eq!(status, 200, "{body}");
+    std::fs::write(
+        temp_dir.path().join("notes/ideas.md"),
+        "^idea-0800\n\nA stitched idea\n",
+    )
+    .unwrap();
+
+    let (status, body) = server.request("GET", "/search?q=http%20api", None);
+    assert_eq!(status, 200);
+    let hits = body["hits"].as_array().unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0]["anchor"], "^focus-0930");
+
+    // A trailing escape decodes too, and `+` is a space.
+    let (status, body) = server.request("GET", "/search?q=the+HTTP%20AP%49", None);
+    assert_eq!(status, 200);
+    assert_eq!(body["hits"].as_array().unwrap().len(), 1);
+
+    let (status, body) = server.request("GET", "/stitch?path=notes/memo", None);
+    assert_eq!(status, 200, "{body}");
+    assert!(body["markdown"]
+        .as_str()
+        .unwrap()
+        .contains("^memo-1000\n\nA stitched idea\n"));
+
+    let (status, _) = server.request("GET", "/stitch?path=notes/missing", None);
+    assert_eq!(status, 404);
+
+    let (status, _) = server.request(
+        "POST",
+        "/append",
+        Some(
+            json!({ "heading": "X", "anchor": "x-0900", "content": "no", "file": "../escape.md" }),
+        ),
+    );
+    assert_eq!(status, 400);
+
+    for file in [".git/config", ".a4/config.toml"] {
+        let (status, body) = server.request(
+            "POST",
+            "/append",
+            Some(json!({ "heading": "X", "anchor": "x-0900", "content": "no", "file": file })),
+        );
+        assert_eq!(status, 400, "{file}");
+        assert!(body["error"].as_str().unwrap().contains("Not a note"));
+        assert!(!temp_dir.path().join(file).exists());
+    }
+}
+
+#[test]
+fn test_serve_serializes_concurrent_appends() {
+    let temp_dir = TempDir::new().unwrap();
+    let server = Server::start(temp_dir.path());
+
+    std::thread::scope(|scope| {
+        for i in 0..8 {
+            let server = &server;
+            scope.spawn(move || {
+                let (status, body) = server.request(
+                    "POST",
+                    "/append",
+                    Some(json!({
+                        "heading": "Log",
+                        "anchor": format!("log-0900__w{i}"),
+                        "content": format!("entry {i}"),
+                        "file": "log.md"
+                    })),
+                );
+                assert_eq!(status, 200, "{body}");
+            });
+        }
+    });
+
+    let content = std::fs::read_to_string(temp_dir.path().join("log.md")).unwrap();
+    assert_eq!(content.matches("## Log").count(), 1);
+    for i in 0..8 {
+        assert!(content.contains(&format!("^log-0900__w{i}\n\nentry {i}\n")));
+    }
+}
+
+#[test]
+fn test_serve_rejects_oversized_body() {
+    let temp_dir = TempDir::new().unwrap();
+    let server = Server::start(temp_dir.path());
+
+    let (status, body) = server.request(
+        "POST",
+        "/append",
+        Some(json!({
+            "heading": "Log",
+            "anchor": "log-0900",
+            "content": "x".repeat(1024 * 1024),
+            "file": "log.md"
+        })),
+    );
+    assert_eq!(status, 413, "{body}");
+    assert!(!temp_dir.path().join("log.md").exists());
+
+    // The server keeps serving
+    let (status, _) = server.request("GET", "/today", None);
+    assert_eq!(status, 200);
+}
@@ -0,0 +1,178 @@
+use a4_core::LocalClock;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+const OLD_DAY: &str = "capture/2001/2001-01/2001-01-15.md";
+
+fn git(dir: &Path, args: &[&str]) {
+    let output = std::process::Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "git {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+/// A committed vault with two old capture months and the current one.
+fn setup() -> (TempDir, PathBuf, String) {
+    let temp_dir = TempDir::new().unwrap();
+    let vault = temp_dir.path().to_path_buf();
+
+    let today = LocalClock::today_utc();
+    let recent = format!(
+        "capture/{:04}/{}/{}",
+        today.year,
+        today.month(),
+        today.filename()
+    );
+    for (path, content) in [
+        ("README.md", "# Vault\n"),
+        (OLD_DAY, "## Old\n\nparser notes\n"),
+        ("capture/2001/2001-02/2001-02-01.md", "## Old\n"),
+        (recent.as_str(), "## Focus\n"),
+    ] {
+        let path = vault.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+    git(&vault, &["init", "-q", "-b", "main"]);
+    git(&vault, &["config", "user.email", "test@example.com"]);
+    git(&vault, &["config", "user.name", "Test User"]);
+    git(&vault, &["add", "-A"]);
+    git(&vault, &["commit", "-q", "-m", "initial"]);
+
+    (temp_dir, vault, recent)
+}
+
+fn a4(vault: &Path) -> Command {
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    cmd.env("A4_VAULT_DIR", vault);
+    cmd
+}
+
+#[test]
+fn test_sparse_keep_show_and_add() {
+    let (_temp_dir, vault, recent) = setup();
+
+    a4(&vault)
+        .args(["sparse", "show"])
+        .assert()
+        .success()
+        .stdout("Sparse checkout is off; every month is checked out\n");
+
+    a4(&vault)
+        .args(["sparse", "keep", "--months", "1"])
+        .assert()
+        .success();
+    assert!(vault.join(&recent).exists());
+    assert!(vault.join("README.md").exists());
+    assert!(!vault.join(OLD_DAY).exists());
+
+    a4(&vault)
+        .args(["sparse", "show"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Not checked out:\n  capture/2001/2001-01\n  capture/2001/2001-02\n",
+        ));
+
+    a4(&vault)
+        .args(["sparse", "add", "capture/2001/2001-01"])
+        .assert()
+        .success()
+        .stdout("Checked out capture/2001/2001-01\n");
+    assert!(vault.join(OLD_DAY).exists());
+    assert!(!vault.join("capture/2001/2001-02").exists());
+
+    a4(&vault)
+        .args(["sparse", "add", "sources/2001"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid sparse target"));
+}
+
+#[test]
+fn test_writes_to_trimmed_months_fail() {
+    let (_temp_dir, vault, _) = setup();
+    a4(&vault)
+        .args(["sparse", "keep", "--months", "1"])
+        .assert()
+        .success();
+
+    a4(&vault)
+        .args(["append", "--heading", "Old", "--anchor", "old-0900"])
+        .args(["--text", "late entry", "--file"])
+        .arg(vault.join(OLD_DAY))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "is not checked out (sparse checkout); run 'a4 sparse add capture/2001/2001-01' first",
+        ));
+    assert!(!vault.join(OLD_DAY).exists());
+
+    a4(&vault).arg("today").assert().success();
+
+    a4(&vault)
+        .args(["search", "parser"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "Note: 2 capture months are not checked out and were not searched",
+        ));
+}
+
+#[test]
+fn test_mcp_search_notes_unchecked_out_months() {
+    let (_temp_dir, vault, _) = setup();
+    a4(&vault)
+        .args(["sparse", "keep", "--months", "1"])
+        .assert()
+        .success();
+
+    let request = r#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"search","arguments":{"query":"parser"}}}"#;
+    a4(&vault)
+        .arg("mcp")
+        .write_stdin(format!("{request}\n"))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Note: 2 capture months are not checked out and were not searched",
+        ));
+}
+
+#[test]
+fn test_sparse_with_separate_git_dir() {
+    let (_temp_dir, vault, recent) = setup();
+    let git_home = TempDir::new().unwrap();
+    let git_dir = git_home.path().join("vault.git");
+    git(
+        &vault,
+        &[
+            "init",
+            "-q",
+            "--separate-git-dir",
+            git_dir.to_str().unwrap(),
+        ],
+    );
+    assert!(vault.join(".git").is_file());
+
+    a4(&vault)
+        .args(["sparse", "keep", "--months", "1"])
+        .assert()
+        .success();
+    assert!(vault.join(&recent).exists());
+    assert!(!vault.join(OLD_DAY).exists());
+    assert!(git_dir.join("info/sparse-checkout").exists());
+
+    a4(&vault)
+        .args(["sparse", "show"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("capture/2001/2001-01"));
+}
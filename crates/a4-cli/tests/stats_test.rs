@@ -0,0 +1,81 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn write_day(vault: &std::path::Path, day: &str, content: &str) {
+    let path = vault
+        .join("capture")
+        .join(&day[..4])
+        .join(&day[..7])
+        .join(format!("{day}.md"));
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, content).unwrap();
+}
+
+fn setup() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    write_day(
+        temp_dir.path(),
+        "2025-09-13",
+        "## Focus\n\n^focus-0930__mac\n\nDeep work on the parser\n",
+    );
+    write_day(
+        temp_dir.path(),
+        "2025-09-14",
+        "## Journal\n\n^jrnl-2130\n\nQuiet day\n",
+    );
+    temp_dir
+}
+
+#[test]
+fn test_stats_json() {
+    let temp_dir = setup();
+
+    let output = Command::cargo_bin("a4")
+        .unwrap()
+        .env("A4_VAULT_DIR", temp_dir.path())
+        .args(["stats", "--json"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "Command failed with output: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stats: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(stats["notes"], 2);
+    assert_eq!(stats["notes_per_directory"]["capture"], 2);
+    assert_eq!(stats["daily"]["created"], 2);
+    assert_eq!(stats["daily"]["longest_streak"], 2);
+    assert_eq!(stats["anchors"]["by_prefix"]["focus"], 1);
+    assert_eq!(stats["anchors"]["by_suffix"]["mac"], 1);
+    assert_eq!(stats["busiest_hours"], serde_json::json!(["09", "21"]));
+}
+
+#[test]
+fn test_stats_table_since() {
+    let temp_dir = setup();
+
+    Command::cargo_bin("a4")
+        .unwrap()
+        .env("A4_VAULT_DIR", temp_dir.path())
+        .args(["stats", "--since", "2025-09-14"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Anchors by prefix"))
+        .stdout(predicate::str::contains("jrnl"))
+        .stdout(predicate::str::contains("focus").not());
+}
+
+#[test]
+fn test_stats_rejects_bad_since() {
+    let temp_dir = setup();
+
+    Command::cargo_bin("a4")
+        .unwrap()
+        .env("A4_VAULT_DIR", temp_dir.path())
+        .args(["stats", "--since", "yesterday"])
+        .assert()
+        .failure();
+}
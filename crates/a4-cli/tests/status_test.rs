@@ -0,0 +1,140 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+fn git(dir: &Path, args: &[&str]) -> String {
+    let output = std::process::Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "git {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn configure(dir: &Path) {
+    git(dir, &["config", "user.email", "test@example.com"]);
+    git(dir, &["config", "user.name", "Test User"]);
+}
+
+/// A bare remote, a vault clone with a few tracked notes, and a second clone
+/// that has pushed one more commit the vault has fetched but not merged.
+fn setup() -> (TempDir, PathBuf) {
+    let temp_dir = TempDir::new().unwrap();
+    let remote = temp_dir.path().join("remote.git");
+    let vault = temp_dir.path().join("vault");
+    let other = temp_dir.path().join("other");
+
+    std::fs::create_dir(&remote).unwrap();
+    git(&remote, &["init", "-q", "--bare", "-b", "main"]);
+
+    std::fs::create_dir(&vault).unwrap();
+    git(&vault, &["init", "-q", "-b", "main"]);
+    configure(&vault);
+    git(
+        &vault,
+        &["remote", "add", "origin", remote.to_str().unwrap()],
+    );
+    std::fs::write(vault.join("log.md"), "# Log\n").unwrap();
+    std::fs::write(vault.join("plan.md"), "# Plan\nold\n").unwrap();
+    std::fs::write(vault.join("old.md"), "bye\n").unwrap();
+    git(&vault, &["add", "-A"]);
+    git(&vault, &["commit", "-q", "-m", "initial"]);
+    git(&vault, &["push", "-q", "-u", "origin", "main"]);
+
+    git(
+        temp_dir.path(),
+        &["clone", "-q", remote.to_str().unwrap(), "other"],
+    );
+    configure(&other);
+    std::fs::write(other.join("remote.md"), "from elsewhere\n").unwrap();
+    git(&other, &["add", "-A"]);
+    git(&other, &["commit", "-q", "-m", "remote edit"]);
+    git(&other, &["push", "-q", "origin", "main"]);
+    git(&vault, &["fetch", "-q", "origin"]);
+
+    (temp_dir, vault)
+}
+
+fn a4(vault: &Path) -> Command {
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    cmd.env("A4_VAULT_DIR", vault);
+    cmd
+}
+
+#[test]
+fn test_status_groups_changes_and_plans_rebase() {
+    let (_temp_dir, vault) = setup();
+    std::fs::write(vault.join("log.md"), "# Log\n\n^log-0930\n\nappended\n").unwrap();
+    std::fs::write(vault.join("plan.md"), "# Plan\nnew\n").unwrap();
+    std::fs::remove_file(vault.join("old.md")).unwrap();
+    std::fs::create_dir_all(vault.join("capture/2025/2025-09")).unwrap();
+    std::fs::write(vault.join("capture/2025/2025-09/2025-09-14.md"), "day\n").unwrap();
+    std::fs::write(vault.join("idea.md"), "new\n").unwrap();
+    // A write's temporary file left behind by a crash
+    std::fs::write(vault.join(".idea.md.4242-0123456789abcdef.tmp"), "new\n").unwrap();
+
+    let output = a4(&vault).args(["status", "--json"]).output().unwrap();
+    assert!(
+        output.status.success(),
+        "Command failed with output: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let report: Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    let changes = &report["changes"];
+    assert_eq!(
+        changes["new_daily_notes"],
+        serde_json::json!(["capture/2025/2025-09/2025-09-14.md"])
+    );
+    assert_eq!(changes["new_files"], serde_json::json!(["idea.md"]));
+    assert_eq!(changes["appended"], serde_json::json!(["log.md"]));
+    assert_eq!(changes["modified"], serde_json::json!(["plan.md"]));
+    assert_eq!(changes["deleted"], serde_json::json!(["old.md"]));
+    assert_eq!(report["remote_ref"], "refs/remotes/origin/main");
+    assert_eq!(report["ahead"], 0);
+    assert_eq!(report["behind"], 1);
+    assert_eq!(report["action"], "rebase");
+}
+
+#[test]
+fn test_status_fast_forward_when_clean() {
+    let (_temp_dir, vault) = setup();
+
+    a4(&vault)
+        .arg("status")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Nothing to commit"))
+        .stdout(predicate::str::contains(
+            "refs/remotes/origin/main: ahead 0, behind 1",
+        ))
+        .stdout(predicate::str::contains(
+            "Sync would: fast-forward to refs/remotes/origin/main",
+        ));
+}
+
+#[test]
+fn test_sync_dry_run_changes_nothing() {
+    let (_temp_dir, vault) = setup();
+    std::fs::write(vault.join("log.md"), "# Log\nmore\n").unwrap();
+    let head = git(&vault, &["rev-parse", "HEAD"]);
+    let porcelain = git(&vault, &["status", "--porcelain"]);
+
+    a4(&vault)
+        .args(["sync", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("appended:\n    log.md"))
+        .stdout(predicate::str::contains("Sync would: rebase"))
+        .stdout(predicate::str::contains("Commit message:\n  a4: sync\n"));
+
+    assert_eq!(git(&vault, &["rev-parse", "HEAD"]), head);
+    assert_eq!(git(&vault, &["status", "--porcelain"]), porcelain);
+}
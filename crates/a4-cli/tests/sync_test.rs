@@ -223,3 +223,36 @@ fn test_no_divergence_when_ahead() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+#[test]
+fn test_pending_diff_leaves_index_untouched() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let repo_path = temp_dir.path();
+
+    init_test_repo(repo_path)?;
+    create_commit(repo_path, "log.md", "# Log\n", "Initial commit")?;
+
+    fs::write(repo_path.join("log.md"), "# Log\n\n^log-0930\n\nmore\n")?;
+    fs::create_dir_all(repo_path.join("sources"))?;
+    fs::write(repo_path.join("sources/gpt.md"), "# GPT\n")?;
+
+    let backend = GixBackend::open(repo_path)?;
+    let diff = backend.pending_diff()?;
+    assert!(diff.contains("+++ b/log.md\n"));
+    assert!(diff.contains("+^log-0930\n"));
+    assert!(diff.contains("+++ b/sources/gpt.md\n"));
+
+    // Nothing was staged in the real index
+    let staged = std::process::Command::new("git")
+        .args(["diff", "--cached", "--name-only"])
+        .current_dir(repo_path)
+        .output()?;
+    assert!(staged.stdout.is_empty());
+    // and the scratch index is gone
+    for entry in fs::read_dir(repo_path.join(".git"))? {
+        let name = entry?.file_name();
+        assert!(!name.to_string_lossy().contains("a4-pending"), "{name:?}");
+    }
+
+    Ok(())
+}
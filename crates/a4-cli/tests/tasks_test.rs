@@ -0,0 +1,93 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn write_day(vault: &std::path::Path, day: &str, content: &str) {
+    let path = vault
+        .join("capture")
+        .join(&day[..4])
+        .join(&day[..7])
+        .join(format!("{day}.md"));
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, content).unwrap();
+}
+
+fn a4(vault: &std::path::Path) -> Command {
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    cmd.env("A4_VAULT_DIR", vault);
+    cmd
+}
+
+#[test]
+fn test_tasks_list_open() {
+    let temp_dir = TempDir::new().unwrap();
+    write_day(
+        temp_dir.path(),
+        "2020-01-02",
+        "## Tasks\n\n^tasks-0900\n\n- [ ] Renew passport\n- [x] Pay rent\n",
+    );
+
+    a4(temp_dir.path())
+        .arg("tasks")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "2020-01-02  ^tasks-0900  [ ]  Renew passport",
+        ))
+        .stdout(predicate::str::contains("[x]  Pay rent"));
+
+    a4(temp_dir.path())
+        .args(["tasks", "--open"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Renew passport"))
+        .stdout(predicate::str::contains("Pay rent").not());
+
+    a4(temp_dir.path())
+        .args(["tasks", "--since", "2020-01-03"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+}
+
+#[test]
+fn test_tasks_carry_uses_configured_heading() {
+    let temp_dir = TempDir::new().unwrap();
+    write_day(
+        temp_dir.path(),
+        "2020-01-02",
+        "## Tasks\n\n^tasks-0900\n\n- [ ] Renew passport\n",
+    );
+    std::fs::create_dir_all(temp_dir.path().join(".a4")).unwrap();
+    std::fs::write(
+        temp_dir.path().join(".a4/config.toml"),
+        "[tasks]\nheading = \"Carried\"\n",
+    )
+    .unwrap();
+
+    let output = a4(temp_dir.path())
+        .args(["tasks", "carry"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "Command failed with output: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let path = stdout.trim().split('#').next().unwrap();
+    let content = std::fs::read_to_string(path).unwrap();
+    assert!(content.contains("## Carried\n"));
+    assert!(content
+        .contains("- [ ] Renew passport (from [[capture/2020/2020-01/2020-01-02#^tasks-0900]])"));
+
+    // Already carried into today: a second run appends nothing, and its log
+    // line stays off stdout.
+    a4(temp_dir.path())
+        .args(["-v", "tasks", "carry"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains("No open tasks to carry"));
+}
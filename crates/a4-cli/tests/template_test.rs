@@ -138,3 +138,55 @@ Date again: {{YYYY-MM-DD}}
         "No template markers should remain in the file"
     );
 }
+
+#[test]
+fn test_today_command_renders_includes_and_conditionals() {
+    let temp_dir = TempDir::new().unwrap();
+    let templates = temp_dir.path().join("routines").join("templates");
+    std::fs::create_dir_all(&templates).unwrap();
+    std::fs::write(templates.join("habits.md"), "- [ ] Walk\n").unwrap();
+    std::fs::write(
+        templates.join("daily.md"),
+        "# {{weekday}} {{week}}\nBack: {{yesterday}}\n\
+         {{#if weekend}}Rest{{else}}Work{{/if}}\n{{include:habits}}",
+    )
+    .unwrap();
+
+    let output = Command::cargo_bin("a4")
+        .unwrap()
+        .env("A4_VAULT_DIR", temp_dir.path())
+        .arg("today")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let content = std::fs::read_to_string(String::from_utf8_lossy(&output.stdout).trim()).unwrap();
+    let expected = regex::Regex::new(
+        r"^# [A-Z][a-z]+day \d{4}-W\d{2}\nBack: \[\[capture/\d{4}/\d{4}-\d{2}/\d{4}-\d{2}-\d{2}\]\]\n(Rest|Work)\n- \[ \] Walk\n$",
+    )
+    .unwrap();
+    assert!(expected.is_match(&content), "{content}");
+}
+
+#[test]
+fn test_today_command_rejects_unknown_variables() {
+    let temp_dir = TempDir::new().unwrap();
+    let template_path = temp_dir
+        .path()
+        .join("routines")
+        .join("templates")
+        .join("daily.md");
+    std::fs::create_dir_all(template_path.parent().unwrap()).unwrap();
+    std::fs::write(&template_path, "# {{YYYY-MM-DD}}\n{{mood}}\n").unwrap();
+
+    let output = Command::cargo_bin("a4")
+        .unwrap()
+        .env("A4_VAULT_DIR", temp_dir.path())
+        .arg("today")
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Unknown template variable {{mood}}"));
+    assert!(!temp_dir.path().join("capture").exists());
+}
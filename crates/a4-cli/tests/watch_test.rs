@@ -0,0 +1,149 @@
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+fn git(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "git {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn clone(remote: &Path, dir: &Path) {
+    git(
+        dir.parent().unwrap(),
+        &[
+            "clone",
+            "-q",
+            remote.to_str().unwrap(),
+            dir.to_str().unwrap(),
+        ],
+    );
+    git(dir, &["config", "user.email", "test@example.com"]);
+    git(dir, &["config", "user.name", "Test User"]);
+}
+
+/// A bare remote with one commit on main, plus a clone to use as the vault.
+fn setup() -> (TempDir, std::path::PathBuf, std::path::PathBuf) {
+    let temp_dir = TempDir::new().unwrap();
+    let remote = temp_dir.path().join("remote.git");
+    let vault = temp_dir.path().join("vault");
+    std::fs::create_dir(&remote).unwrap();
+    git(&remote, &["init", "-q", "--bare", "-b", "main"]);
+
+    std::fs::create_dir(&vault).unwrap();
+    git(&vault, &["init", "-q", "-b", "main"]);
+    git(&vault, &["config", "user.email", "test@example.com"]);
+    git(&vault, &["config", "user.name", "Test User"]);
+    git(
+        &vault,
+        &["remote", "add", "origin", remote.to_str().unwrap()],
+    );
+    std::fs::write(vault.join("note.md"), "shared\n").unwrap();
+    git(&vault, &["add", "-A"]);
+    git(&vault, &["commit", "-q", "-m", "initial"]);
+    git(&vault, &["push", "-q", "-u", "origin", "main"]);
+
+    (temp_dir, remote, vault)
+}
+
+/// Kills the watcher when the test ends, even on panic.
+struct Watch {
+    child: Child,
+    stderr: mpsc::Receiver<String>,
+}
+
+impl Drop for Watch {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn start_watch(vault: &Path) -> Watch {
+    let mut child = Command::new(assert_cmd::cargo::cargo_bin("a4"))
+        .env("A4_VAULT_DIR", vault)
+        .args(["watch", "--debounce-ms", "200"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut line = String::new();
+    BufReader::new(child.stdout.take().unwrap())
+        .read_line(&mut line)
+        .unwrap();
+    assert!(line.starts_with("Watching "), "unexpected output: {line:?}");
+
+    let (tx, rx) = mpsc::channel();
+    let stderr = child.stderr.take().unwrap();
+    std::thread::spawn(move || {
+        for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+            let _ = tx.send(line);
+        }
+    });
+
+    Watch { child, stderr: rx }
+}
+
+fn wait_for(what: &str, mut done: impl FnMut() -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(20);
+    while !done() {
+        assert!(Instant::now() < deadline, "timed out waiting for {what}");
+        std::thread::sleep(Duration::from_millis(100));
+    }
+}
+
+#[test]
+fn test_watch_syncs_after_changes() {
+    let (_temp_dir, remote, vault) = setup();
+    let _watch = start_watch(&vault);
+
+    std::fs::create_dir_all(vault.join("capture")).unwrap();
+    std::fs::write(vault.join("capture/today.md"), "^focus-0930\n\nwatched\n").unwrap();
+
+    wait_for("the change to reach the remote", || {
+        git(&remote, &["log", "--oneline", "main"]).lines().count() == 2
+    });
+    let files = git(&remote, &["ls-tree", "-r", "--name-only", "main"]);
+    assert!(files.contains("capture/today.md"));
+}
+
+#[test]
+fn test_watch_pauses_on_conflict() {
+    let (temp_dir, remote, vault) = setup();
+
+    let other = temp_dir.path().join("other");
+    clone(&remote, &other);
+    std::fs::write(other.join("note.md"), "theirs\n").unwrap();
+    git(&other, &["commit", "-q", "-am", "their edit"]);
+    git(&other, &["push", "-q", "origin", "main"]);
+
+    let watch = start_watch(&vault);
+    std::fs::write(vault.join("note.md"), "ours\n").unwrap();
+
+    let mut paused = false;
+    wait_for("auto-sync to pause", || {
+        while let Ok(line) = watch.stderr.try_recv() {
+            paused |= line.contains("Auto-sync paused");
+        }
+        paused
+    });
+
+    // Nothing was pushed over the remote's history.
+    assert!(git(&remote, &["log", "--oneline", "main"]).contains("their edit"));
+    assert_eq!(
+        std::fs::read_to_string(vault.join("note.md")).unwrap(),
+        "ours\n"
+    );
+}
@@ -1,7 +1,10 @@
 use crate::anchors::AnchorToken;
+use crate::blocks::next_free_anchor;
+use crate::crypt;
 use crate::error::A4Error;
 use crate::headings::ensure_h2_heading;
-use crate::notes::{join_front_matter, read_note, write_note};
+use crate::notes::{join_front_matter, lock_note, read_note, write_note};
+use crate::sparse;
 use crate::vault::Vault;
 use std::path::Path;
 
@@ -12,6 +15,80 @@ pub struct AppendOptions<'a> {
 }
 
 pub fn append_block(vault: &Vault, file: &Path, opts: AppendOptions) -> Result<(), A4Error> {
+    let interpreted_content = interpret_escapes(opts.content);
+
+    append_block_verbatim(
+        vault,
+        file,
+        AppendOptions {
+            content: &interpreted_content,
+            ..opts
+        },
+    )
+}
+
+/// Interpret `\n`, `\t`, `\r` and `\\` escape sequences in typed content.
+pub(crate) fn interpret_escapes(content: &str) -> String {
+    content
+        .replace("\\n", "\n")
+        .replace("\\t", "\t")
+        .replace("\\r", "\r")
+        .replace("\\\\", "\\")
+}
+
+/// Like [`append_block`], but writes `opts.content` exactly as given. Used when
+/// moving existing note content, where backslashes must survive untouched.
+pub fn append_block_verbatim(
+    vault: &Vault,
+    file: &Path,
+    opts: AppendOptions,
+) -> Result<(), A4Error> {
+    let AppendOptions {
+        heading,
+        anchor,
+        content,
+    } = opts;
+    append_block_with(vault, file, heading, |_| {
+        Ok(Some((anchor, content.to_string())))
+    })?;
+    Ok(())
+}
+
+/// Like [`append_block_verbatim`], but suffixes `opts.anchor` if the note
+/// already has it (see [`next_free_anchor`]). Returns the anchor written.
+pub fn append_block_suffixed(
+    vault: &Vault,
+    file: &Path,
+    opts: AppendOptions,
+) -> Result<AnchorToken, A4Error> {
+    let mut written = opts.anchor.clone();
+    append_block_with(vault, file, opts.heading, |body| {
+        written = next_free_anchor(body, opts.anchor);
+        Ok(Some((written.clone(), opts.content.to_string())))
+    })?;
+    Ok(written)
+}
+
+/// Like [`append_block_verbatim`], but `block` chooses the anchor and content
+/// from the note's current body, read under the note's lock and decrypted, so
+/// concurrent writers never pick the same free anchor or both miss a marker.
+/// `block` returns `None` to leave the note untouched. Returns the anchor
+/// written, if any.
+pub fn append_block_with<F>(
+    vault: &Vault,
+    file: &Path,
+    heading: &str,
+    block: F,
+) -> Result<Option<AnchorToken>, A4Error>
+where
+    F: FnOnce(&str) -> Result<Option<(AnchorToken, String)>, A4Error>,
+{
+    // Held until the new content is in place. Taken first, since decrypting
+    // rewrites the note as well.
+    let _lock = lock_note(vault, file)?;
+
+    sparse::ensure_checked_out(vault, file)?;
+    crypt::ensure_decrypted(vault, file)?;
     vault.ensure_parents(file)?;
 
     let (front_matter, mut body) = if file.exists() {
@@ -21,7 +98,11 @@ pub fn append_block(vault: &Vault, file: &Path, opts: AppendOptions) -> Result<(
         (None, String::new())
     };
 
-    let (updated_body, _) = ensure_h2_heading(&body, opts.heading);
+    let Some((anchor, content)) = block(&body)? else {
+        return Ok(None);
+    };
+
+    let (updated_body, _) = ensure_h2_heading(&body, heading);
     body = updated_body;
 
     // Ensure the body ends with exactly one newline
@@ -32,25 +113,13 @@ pub fn append_block(vault: &Vault, file: &Path, opts: AppendOptions) -> Result<(
     // Always add double newline before anchor
     body.push('\n');
 
-    // Interpret escape sequences in content
-    let interpreted_content = opts
-        .content
-        .replace("\\n", "\n")
-        .replace("\\t", "\t")
-        .replace("\\r", "\r")
-        .replace("\\\\", "\\");
-
     // Add anchor with double newline after it, then content
-    body.push_str(&format!(
-        "{}\n\n{}\n",
-        opts.anchor.to_marker(),
-        interpreted_content
-    ));
+    body.push_str(&format!("{}\n\n{}\n", anchor.to_marker(), content));
 
     let final_content = join_front_matter(front_matter.as_deref(), &body);
     write_note(file, &final_content)?;
 
-    Ok(())
+    Ok(Some(anchor))
 }
 
 #[cfg(test)]
@@ -59,6 +128,120 @@ mod tests {
     use crate::vault::VaultOpts;
     use tempfile::TempDir;
 
+    #[test]
+    fn test_parallel_appends_lose_nothing() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().to_path_buf();
+        let file = root.join("capture/2025/2025-09/2025-09-14.md");
+
+        let handles: Vec<_> = (0..32)
+            .map(|i| {
+                let (root, file) = (root.clone(), file.clone());
+                std::thread::spawn(move || {
+                    let vault = Vault::open(&root, VaultOpts::default()).unwrap();
+                    let content = format!("entry {i}");
+                    append_block(
+                        &vault,
+                        &file,
+                        AppendOptions {
+                            heading: "Journal",
+                            anchor: AnchorToken::parse(&format!("jrnl-0930__t{i}")).unwrap(),
+                            content: &content,
+                        },
+                    )
+                    .unwrap();
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let content = std::fs::read_to_string(&file).unwrap();
+        for i in 0..32 {
+            assert!(content.contains(&format!("^jrnl-0930__t{i}\n\nentry {i}\n")));
+        }
+        assert_eq!(content.matches("## Journal").count(), 1);
+        assert_eq!(
+            std::fs::read_dir(file.parent().unwrap()).unwrap().count(),
+            1,
+            "no temporary files left behind"
+        );
+    }
+
+    #[test]
+    fn test_parallel_suffixed_appends_pick_distinct_anchors() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().to_path_buf();
+        let file = root.join("inbox/2025/2025-09/2025-09-14--paste-zone.md");
+
+        let handles: Vec<_> = (0..32)
+            .map(|i| {
+                let (root, file) = (root.clone(), file.clone());
+                std::thread::spawn(move || {
+                    let vault = Vault::open(&root, VaultOpts::default()).unwrap();
+                    let content = format!("entry {i}");
+                    append_block_suffixed(
+                        &vault,
+                        &file,
+                        AppendOptions {
+                            heading: "Inbox",
+                            anchor: AnchorToken::parse("inbox-0930").unwrap(),
+                            content: &content,
+                        },
+                    )
+                    .unwrap()
+                    .to_marker()
+                })
+            })
+            .collect();
+        let mut anchors: Vec<String> = handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect();
+        anchors.sort();
+        anchors.dedup();
+        assert_eq!(anchors.len(), 32, "duplicate anchors: {anchors:?}");
+
+        let content = std::fs::read_to_string(&file).unwrap();
+        for anchor in &anchors {
+            assert_eq!(
+                content.matches(&format!("{anchor}\n")).count(),
+                1,
+                "{anchor}"
+            );
+        }
+        for i in 0..32 {
+            assert!(content.contains(&format!("\n\nentry {i}\n")));
+        }
+    }
+
+    #[test]
+    fn test_append_block_with_sees_locked_body() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+        let file = temp_dir.path().join("test.md");
+        let add_once = |body: &str| {
+            Ok((!body.contains("<!-- once -->")).then(|| {
+                (
+                    AnchorToken::parse("cal-0900").unwrap(),
+                    "Standup\n<!-- once -->".to_string(),
+                )
+            }))
+        };
+
+        assert!(append_block_with(&vault, &file, "Schedule", add_once)
+            .unwrap()
+            .is_some());
+        assert!(append_block_with(&vault, &file, "Schedule", add_once)
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            std::fs::read_to_string(&file).unwrap(),
+            "\n\n## Schedule\n\n^cal-0900\n\nStandup\n<!-- once -->\n"
+        );
+    }
+
     #[test]
     fn test_append_to_empty_file() {
         let temp_dir = TempDir::new().unwrap();
@@ -210,4 +393,23 @@ mod tests {
             "\n\n## Notes\n\n^note-1100\n\nPath: C:\\Users\\Documents\nTab here:\tvalue\n"
         );
     }
+
+    #[test]
+    fn test_append_verbatim_keeps_backslashes() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+        let file = temp_dir.path().join("test.md");
+
+        let anchor = AnchorToken::parse("note-1100").unwrap();
+        let opts = AppendOptions {
+            heading: "Notes",
+            anchor,
+            content: "Literal \\n stays",
+        };
+
+        append_block_verbatim(&vault, &file, opts).unwrap();
+
+        let content = std::fs::read_to_string(&file).unwrap();
+        assert_eq!(content, "\n\n## Notes\n\n^note-1100\n\nLiteral \\n stays\n");
+    }
 }
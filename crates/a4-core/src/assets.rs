@@ -0,0 +1,224 @@
+use crate::date::UtcDay;
+use crate::error::A4Error;
+use crate::git_backend::{GitBackend, GixBackend};
+use crate::slug::slugify;
+use crate::sync::{self, SyncOptions, SyncReport};
+use crate::vault::Vault;
+use fs_err as fs;
+use std::path::{Path, PathBuf};
+
+/// Vault-relative mount point of the `a4-assets` companion repository.
+pub const ASSETS_DIR: &str = "assets";
+
+/// Commit message for the assets repository when none is given.
+pub const DEFAULT_MESSAGE: &str = "a4: assets";
+
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "svg", "webp", "avif"];
+
+/// State of the `assets/` submodule in a vault checkout.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Submodule {
+    /// No submodule is registered at `assets/` in `.gitmodules`.
+    Absent,
+    /// Registered, but not initialised on this device.
+    NotCheckedOut,
+    /// Checked out at this path.
+    CheckedOut(PathBuf),
+}
+
+/// Find the assets submodule from `.gitmodules` and the working tree.
+pub fn detect(vault: &Vault) -> Result<Submodule, A4Error> {
+    let gitmodules = vault.root().join(".gitmodules");
+    if !gitmodules.exists() || !registers_assets(&fs::read_to_string(&gitmodules)?) {
+        return Ok(Submodule::Absent);
+    }
+
+    let dir = vault.assets_dir();
+    if dir.join(".git").exists() {
+        Ok(Submodule::CheckedOut(dir))
+    } else {
+        Ok(Submodule::NotCheckedOut)
+    }
+}
+
+/// Whether a `.gitmodules` file has a submodule with `path = assets`.
+fn registers_assets(gitmodules: &str) -> bool {
+    gitmodules.lines().any(|line| {
+        line.split_once('=').is_some_and(|(key, value)| {
+            key.trim() == "path" && value.trim().trim_matches('/') == ASSETS_DIR
+        })
+    })
+}
+
+/// Commit, fetch, fast-forward and push the assets submodule, so the core
+/// repository can commit its updated pointer afterwards. Returns `None` when
+/// no submodule is checked out.
+pub fn sync(vault: &Vault, opts: &SyncOptions) -> Result<Option<SyncReport>, A4Error> {
+    let Submodule::CheckedOut(dir) = detect(vault)? else {
+        return Ok(None);
+    };
+
+    let mut backend = GixBackend::open(&dir)?;
+    if backend.head_branch()?.is_empty() {
+        return Err(A4Error::AssetsDetached);
+    }
+
+    // The branch override names a core-repo branch; the submodule keeps its own.
+    let opts = SyncOptions {
+        message: Some(opts.message.unwrap_or(DEFAULT_MESSAGE)),
+        remote: opts.remote,
+        branch: None,
+    };
+    sync::sync(&mut backend, &opts).map(Some)
+}
+
+/// A file copied into the assets repository.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Asset {
+    pub path: PathBuf,
+    /// Markdown to embed it, e.g. `![diagram](assets/img/2025/2025-09/diagram.png)`.
+    pub link: String,
+}
+
+/// Copy `source` to `assets/img/YYYY/YYYY-MM/<slug>.<ext>` for `day`. A file
+/// with identical content already there is reused; a different one gets a
+/// numbered name.
+pub fn add(vault: &Vault, source: &Path, day: UtcDay) -> Result<Asset, A4Error> {
+    let assets = vault.assets_dir();
+    match detect(vault)? {
+        Submodule::NotCheckedOut => return Err(A4Error::AssetsNotCheckedOut),
+        Submodule::Absent if !assets.is_dir() => {
+            return Err(A4Error::AssetsMissing {
+                root: vault.root().to_path_buf(),
+            })
+        }
+        _ => {}
+    }
+
+    let stem = source
+        .file_stem()
+        .map(|stem| stem.to_string_lossy())
+        .unwrap_or_default();
+    let stem = slugify(&stem).unwrap_or_else(|_| "asset".to_string());
+    let extension = source
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase());
+
+    let relative_dir = format!("img/{:04}/{}", day.year, day.month());
+    let dir = assets.join(&relative_dir);
+    fs::create_dir_all(&dir)?;
+
+    let content = fs::read(source)?;
+    let mut n = 1;
+    let name = loop {
+        let name = match (&extension, n) {
+            (Some(ext), 1) => format!("{stem}.{ext}"),
+            (Some(ext), n) => format!("{stem}-{n}.{ext}"),
+            (None, 1) => stem.clone(),
+            (None, n) => format!("{stem}-{n}"),
+        };
+        let path = dir.join(&name);
+        if !path.exists() {
+            fs::write(&path, &content)?;
+            break name;
+        }
+        if fs::read(&path)? == content {
+            break name;
+        }
+        n += 1;
+    };
+
+    let target = format!("{ASSETS_DIR}/{relative_dir}/{name}");
+    let is_image = extension
+        .as_deref()
+        .is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext));
+    let link = if is_image {
+        format!("![{stem}]({target})")
+    } else {
+        format!("[{name}]({target})")
+    };
+
+    Ok(Asset {
+        path: dir.join(name),
+        link,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault::VaultOpts;
+    use tempfile::TempDir;
+
+    fn vault_with_assets() -> (TempDir, Vault) {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir_all(temp_dir.path().join(ASSETS_DIR)).unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+        (temp_dir, vault)
+    }
+
+    #[test]
+    fn test_registers_assets() {
+        assert!(registers_assets(
+            "[submodule \"assets\"]\n\tpath = assets\n\turl = ../a4-assets.git\n"
+        ));
+        assert!(!registers_assets(
+            "[submodule \"theme\"]\n\tpath = themes/assets\n"
+        ));
+    }
+
+    #[test]
+    fn test_add_names_and_links() {
+        let (temp_dir, vault) = vault_with_assets();
+        let day = UtcDay::parse("2025-09-14").unwrap();
+        let source = temp_dir.path().join("Whiteboard Diagram.PNG");
+        fs::write(&source, "png").unwrap();
+
+        let asset = add(&vault, &source, day).unwrap();
+        assert_eq!(
+            asset.link,
+            "![whiteboard-diagram](assets/img/2025/2025-09/whiteboard-diagram.png)"
+        );
+        assert!(asset.path.exists());
+
+        // Same content is reused, different content gets a new name
+        assert_eq!(add(&vault, &source, day).unwrap(), asset);
+        fs::write(&source, "other").unwrap();
+        assert!(add(&vault, &source, day)
+            .unwrap()
+            .link
+            .ends_with("whiteboard-diagram-2.png)"));
+
+        let pdf = temp_dir.path().join("paper.pdf");
+        fs::write(&pdf, "pdf").unwrap();
+        assert_eq!(
+            add(&vault, &pdf, day).unwrap().link,
+            "[paper.pdf](assets/img/2025/2025-09/paper.pdf)"
+        );
+    }
+
+    #[test]
+    fn test_add_without_assets() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+        let source = temp_dir.path().join("a.png");
+        fs::write(&source, "png").unwrap();
+        let day = UtcDay::parse("2025-09-14").unwrap();
+
+        assert!(matches!(
+            add(&vault, &source, day),
+            Err(A4Error::AssetsMissing { .. })
+        ));
+
+        fs::write(
+            temp_dir.path().join(".gitmodules"),
+            "[submodule \"assets\"]\n\tpath = assets\n",
+        )
+        .unwrap();
+        assert_eq!(detect(&vault).unwrap(), Submodule::NotCheckedOut);
+        assert!(matches!(
+            add(&vault, &source, day),
+            Err(A4Error::AssetsNotCheckedOut)
+        ));
+    }
+}
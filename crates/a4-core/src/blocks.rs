@@ -0,0 +1,186 @@
+use crate::anchors::AnchorToken;
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// An anchored block: the `^token` marker line and everything after it up to
+/// the next anchor marker or heading.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Block {
+    pub anchor: AnchorToken,
+    /// Nearest heading above the marker, without the leading `#`s.
+    pub heading: Option<String>,
+    /// Block content with surrounding blank lines trimmed.
+    pub content: String,
+    /// 1-based line number of the marker line.
+    pub line: usize,
+}
+
+static MARKER_REGEX: OnceLock<Regex> = OnceLock::new();
+static HEADING_REGEX: OnceLock<Regex> = OnceLock::new();
+
+/// Parse a standalone marker line such as `^focus-0930` or `^jrnl-0812__iphone`.
+pub fn parse_marker_line(line: &str) -> Option<AnchorToken> {
+    let re = MARKER_REGEX.get_or_init(|| Regex::new(r"^\^(\S+)\s*$").unwrap());
+    let caps = re.captures(line.trim_end())?;
+    AnchorToken::parse(&caps[1]).ok()
+}
+
+fn parse_heading_line(line: &str) -> Option<String> {
+    let re = HEADING_REGEX.get_or_init(|| Regex::new(r"^#{1,6} (.+)$").unwrap());
+    re.captures(line.trim_end())
+        .map(|caps| caps[1].trim().to_string())
+}
+
+/// Split a note body into its anchored blocks, in file order.
+pub fn parse_blocks(body: &str) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut heading: Option<String> = None;
+    let mut current: Option<(AnchorToken, Option<String>, usize, Vec<&str>)> = None;
+
+    let mut finish = |current: &mut Option<(AnchorToken, Option<String>, usize, Vec<&str>)>| {
+        if let Some((anchor, heading, line, lines)) = current.take() {
+            blocks.push(Block {
+                anchor,
+                heading,
+                content: lines.join("\n").trim_matches('\n').to_string(),
+                line,
+            });
+        }
+    };
+
+    let mut in_fence = false;
+    for (index, line) in body.lines().enumerate() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        }
+
+        if !in_fence {
+            if let Some(token) = parse_marker_line(line) {
+                finish(&mut current);
+                current = Some((token, heading.clone(), index + 1, Vec::new()));
+                continue;
+            }
+
+            if let Some(text) = parse_heading_line(line) {
+                finish(&mut current);
+                heading = Some(text);
+                continue;
+            }
+        }
+
+        if let Some((_, _, _, lines)) = current.as_mut() {
+            lines.push(line);
+        }
+    }
+    finish(&mut current);
+
+    blocks
+}
+
+/// Find the block whose marker matches `token` (with or without the caret).
+pub fn find_block(body: &str, token: &str) -> Option<Block> {
+    let wanted = token.strip_prefix('^').unwrap_or(token);
+    parse_blocks(body)
+        .into_iter()
+        .find(|block| block.anchor.to_marker()[1..] == *wanted)
+}
+
+/// Lines under `heading` up to the next heading of the same or higher level.
+pub fn heading_section(body: &str, heading: &str) -> Option<String> {
+    let mut lines = body.lines();
+    let level = lines.by_ref().find_map(|line| {
+        let hashes = line.len() - line.trim_start_matches('#').len();
+        let text = line[hashes..].strip_prefix(' ')?;
+        (hashes > 0 && text.trim() == heading.trim()).then_some(hashes)
+    })?;
+
+    let section: Vec<_> = lines
+        .take_while(|line| {
+            let hashes = line.len() - line.trim_start_matches('#').len();
+            !(hashes > 0 && hashes <= level && line[hashes..].starts_with(' '))
+        })
+        .collect();
+    Some(section.join("\n").trim_matches('\n').to_string())
+}
+
+/// Return `anchor` unchanged when its marker is unused in `body`; otherwise add
+/// the first free numeric suffix (`__2`, `__3`, ...).
+pub fn next_free_anchor(body: &str, anchor: AnchorToken) -> AnchorToken {
+    let taken: Vec<String> = body
+        .lines()
+        .filter_map(parse_marker_line)
+        .map(|token| token.to_marker())
+        .collect();
+
+    if !taken.contains(&anchor.to_marker()) {
+        return anchor;
+    }
+
+    let mut n = 2;
+    loop {
+        let candidate = AnchorToken {
+            suffix: Some(match &anchor.suffix {
+                Some(s) => format!("{s}-{n}"),
+                None => n.to_string(),
+            }),
+            ..anchor.clone()
+        };
+        if !taken.contains(&candidate.to_marker()) {
+            return candidate;
+        }
+        n += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_blocks_after_appends() {
+        let body = "\n\n## Focus\n\n^focus-0930\n\nFirst block\n\n^focus-1030\n\nSecond\nblock\n";
+        let blocks = parse_blocks(body);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].anchor.to_marker(), "^focus-0930");
+        assert_eq!(blocks[0].heading.as_deref(), Some("Focus"));
+        assert_eq!(blocks[0].content, "First block");
+        assert_eq!(blocks[0].line, 5);
+        assert_eq!(blocks[1].content, "Second\nblock");
+    }
+
+    #[test]
+    fn test_block_ends_at_heading() {
+        let body = "## Intention\n^intent-0800\n\n## End of Day\n^eod-2215\nDone\n";
+        let blocks = parse_blocks(body);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].content, "");
+        assert_eq!(blocks[1].heading.as_deref(), Some("End of Day"));
+        assert_eq!(blocks[1].content, "Done");
+    }
+
+    #[test]
+    fn test_ignores_markers_in_code_fences() {
+        let body = "^note-1100\n\n```\n^focus-0930\n# not a heading\n```\n";
+        let blocks = parse_blocks(body);
+        assert_eq!(blocks.len(), 1);
+        assert!(blocks[0].content.contains("^focus-0930"));
+    }
+
+    #[test]
+    fn test_find_block() {
+        let body = "^jrnl-0812__iphone\n\nEntry\n";
+        let block = find_block(body, "^jrnl-0812__iphone").unwrap();
+        assert_eq!(block.content, "Entry");
+        assert!(find_block(body, "jrnl-0812").is_none());
+    }
+
+    #[test]
+    fn test_next_free_anchor() {
+        let body = "^inbox-0930\n\nOne\n\n^inbox-0930__2\n\nTwo\n";
+        let anchor = AnchorToken::parse("inbox-0930").unwrap();
+        assert_eq!(next_free_anchor(body, anchor).to_marker(), "^inbox-0930__3");
+
+        let anchor = AnchorToken::parse("inbox-1000").unwrap();
+        assert_eq!(next_free_anchor(body, anchor).to_marker(), "^inbox-1000");
+    }
+}
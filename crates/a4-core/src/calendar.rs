@@ -0,0 +1,1366 @@
+//! iCalendar (RFC 5545) export of anchored blocks, and import of events as
+//! anchored blocks.
+
+use crate::anchors::AnchorToken;
+use crate::append::append_block_with;
+use crate::blocks::{next_free_anchor, parse_blocks, parse_marker_line};
+use crate::config::CalendarConfig;
+use crate::date::UtcDay;
+use crate::error::A4Error;
+use crate::notes::read_note;
+use crate::vault::Vault;
+use regex::Regex;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use time::{Date, Duration, Month, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset, Weekday};
+use time_tz::{timezones, OffsetDateTimeExt, PrimitiveDateTimeExt, Tz};
+
+static RANGE_REGEX: OnceLock<Regex> = OnceLock::new();
+
+/// An anchored block as a calendar event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CalendarEvent {
+    /// Stable across exports: `<day>-<token>@a4`.
+    pub uid: String,
+    pub start: OffsetDateTime,
+    /// Only known when the block has a `14:10–14:45` time range.
+    pub duration: Option<Duration>,
+    pub summary: String,
+    pub description: String,
+}
+
+/// The `[calendar] timezone`, or the system time zone (UTC if unknown).
+pub fn timezone(config: &CalendarConfig) -> Result<&'static Tz, A4Error> {
+    match &config.timezone {
+        Some(name) => timezones::get_by_name(name)
+            .ok_or_else(|| A4Error::UnknownTimezone { name: name.clone() }),
+        None => Ok(time_tz::system::get_timezone().unwrap_or_else(|e| {
+            tracing::warn!("Cannot determine the system time zone, using UTC: {e}");
+            timezones::db::UTC
+        })),
+    }
+}
+
+/// Anchored blocks in daily notes as events, oldest first. Only blocks whose
+/// prefix is in `prefixes` are included, unless it is empty; with `since`,
+/// earlier days are skipped.
+pub fn anchored_events(
+    vault: &Vault,
+    tz: &Tz,
+    prefixes: &[String],
+    since: Option<UtcDay>,
+) -> Result<Vec<CalendarEvent>, A4Error> {
+    let mut events = Vec::new();
+
+    for (day, path) in vault.daily_notes()? {
+        if since.is_some_and(|since| day < since) {
+            continue;
+        }
+
+        let note = read_note(&path)?;
+        for block in parse_blocks(&note.body) {
+            let anchor = &block.anchor;
+            if !prefixes.is_empty() && !prefixes.contains(&anchor.prefix) {
+                continue;
+            }
+            let Some(start) = block_start(day, &anchor.hhmm, tz) else {
+                continue;
+            };
+            let marker = anchor.to_marker();
+
+            events.push(CalendarEvent {
+                uid: format!("{day}-{}@a4", &marker[1..]),
+                start,
+                duration: range_duration(&block.content),
+                summary: summary(&block.content).unwrap_or_else(|| marker.clone()),
+                description: block.content,
+            });
+        }
+    }
+
+    Ok(events)
+}
+
+/// The instant of local `hhmm` on the UTC `day` of a daily note. The local
+/// date is the one on which that time falls within the UTC day, so `0030`
+/// in UTC+2 belongs to the next local date.
+fn block_start(day: UtcDay, hhmm: &str, tz: &Tz) -> Option<OffsetDateTime> {
+    let time = Time::from_hms(hhmm[..2].parse().ok()?, hhmm[2..].parse().ok()?, 0).ok()?;
+    let date = day.to_date();
+    let at = |date| local_datetime(PrimitiveDateTime::new(date, time), tz);
+
+    let same = at(date)?;
+    let utc_date = same.to_offset(UtcOffset::UTC).date();
+    if utc_date == date {
+        return Some(same);
+    }
+    let shifted = if utc_date > date {
+        date.previous_day()
+    } else {
+        date.next_day()
+    };
+    Some(
+        shifted
+            .and_then(at)
+            .filter(|start| start.to_offset(UtcOffset::UTC).date() == date)
+            .unwrap_or(same),
+    )
+}
+
+/// A local wall-clock time in `tz`. Ambiguous times take the earlier
+/// instant; times skipped by a DST change move forward an hour.
+pub fn local_datetime(local: PrimitiveDateTime, tz: &Tz) -> Option<OffsetDateTime> {
+    local
+        .assume_timezone(tz)
+        .take_first()
+        .or_else(|| (local + Duration::HOUR).assume_timezone(tz).take_first())
+}
+
+/// Length of the first `HH:MM–HH:MM` range in `content`.
+fn range_duration(content: &str) -> Option<Duration> {
+    let re = RANGE_REGEX.get_or_init(|| {
+        Regex::new(r"\b([01]?\d|2[0-3]):([0-5]\d)\s*(?:–|—|-|to)\s*([01]?\d|2[0-3]):([0-5]\d)\b")
+            .unwrap()
+    });
+    let caps = re.captures(content)?;
+    let minutes = |h: usize, m: usize| -> Option<i64> {
+        Some(caps[h].parse::<i64>().ok()? * 60 + caps[m].parse::<i64>().ok()?)
+    };
+    let length = (minutes(3, 4)? - minutes(1, 2)?).rem_euclid(24 * 60);
+    (length > 0).then(|| Duration::minutes(length))
+}
+
+/// First non-empty line without list, checkbox, heading or quote markers.
+fn summary(content: &str) -> Option<String> {
+    let line = content
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())?;
+    let mut line = line.trim_start_matches(['#', '>']).trim_start();
+    for marker in ["- ", "* ", "+ "] {
+        line = line.strip_prefix(marker).unwrap_or(line);
+    }
+    for checkbox in ["[ ] ", "[x] ", "[X] "] {
+        line = line.strip_prefix(checkbox).unwrap_or(line);
+    }
+    Some(line.trim().to_string()).filter(|line| !line.is_empty())
+}
+
+/// Serialise `events` as a VCALENDAR with CRLF line endings. `stamp` is the
+/// DTSTAMP of every event.
+pub fn to_ics(events: &[CalendarEvent], stamp: OffsetDateTime) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//A4//a4 export ics//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+    for event in events {
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}", escape_text(&event.uid)));
+        lines.push(format!("DTSTAMP:{}", utc_stamp(stamp)));
+        lines.push(format!("DTSTART:{}", utc_stamp(event.start)));
+        if let Some(duration) = event.duration {
+            lines.push(format!("DTEND:{}", utc_stamp(event.start + duration)));
+        }
+        lines.push(format!("SUMMARY:{}", escape_text(&event.summary)));
+        lines.push(format!("DESCRIPTION:{}", escape_text(&event.description)));
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|line| fold(line) + "\r\n").collect()
+}
+
+/// `20250914T073000Z`
+fn utc_stamp(at: OffsetDateTime) -> String {
+    let at = at.to_offset(UtcOffset::UTC);
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        at.year(),
+        at.month() as u8,
+        at.day(),
+        at.hour(),
+        at.minute(),
+        at.second()
+    )
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Fold a content line at 75 octets, never splitting a character.
+fn fold(line: &str) -> String {
+    let mut out = String::new();
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out
+}
+
+/// Today's date in `tz`.
+pub fn local_today(tz: &Tz) -> Date {
+    OffsetDateTime::now_utc().to_timezone(tz).date()
+}
+
+/// When an `.ics` event starts or ends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventTime {
+    /// An all-day `VALUE=DATE`.
+    Date(Date),
+    DateTime(OffsetDateTime),
+}
+
+/// A VEVENT from an `.ics` file.
+#[derive(Debug, Clone)]
+pub struct IcsEvent {
+    pub uid: String,
+    pub summary: String,
+    pub location: Option<String>,
+    pub start: EventTime,
+    pub end: Option<EventTime>,
+    /// Zone the start was given in; recurrences repeat in it.
+    pub zone: &'static Tz,
+    pub rrule: Option<String>,
+    pub exdates: Vec<EventTime>,
+    /// Set on an edited instance of a recurring event.
+    pub recurrence_id: Option<EventTime>,
+    pub cancelled: bool,
+}
+
+struct Property {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl Property {
+    fn param(&self, key: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+fn invalid(reason: impl Into<String>) -> A4Error {
+    A4Error::InvalidCalendar {
+        reason: reason.into(),
+    }
+}
+
+/// Parse the VEVENTs of an iCalendar file. Floating times, and `TZID`s that
+/// are not IANA names, are taken to be in `tz`.
+pub fn parse_ics(text: &str, tz: &'static Tz) -> Result<Vec<IcsEvent>, A4Error> {
+    let mut events = Vec::new();
+    let mut components: Vec<String> = Vec::new();
+    let mut properties = Vec::new();
+    let mut seen_calendar = false;
+
+    for line in unfold(text) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let property =
+            parse_line(&line).ok_or_else(|| invalid(format!("malformed line '{line}'")))?;
+        match property.name.as_str() {
+            "BEGIN" => {
+                let component = property.value.trim().to_ascii_uppercase();
+                seen_calendar |= component == "VCALENDAR";
+                if component == "VEVENT" {
+                    properties.clear();
+                }
+                components.push(component);
+            }
+            "END" => {
+                let component = property.value.trim().to_ascii_uppercase();
+                if components.pop().as_deref() != Some(component.as_str()) {
+                    return Err(invalid(format!(
+                        "END:{component} without BEGIN:{component}"
+                    )));
+                }
+                if component == "VEVENT" {
+                    events.push(event_from(&properties, tz)?);
+                }
+            }
+            _ if components.last().is_some_and(|c| c == "VEVENT") => properties.push(property),
+            _ => {}
+        }
+    }
+
+    if !seen_calendar {
+        return Err(invalid("no BEGIN:VCALENDAR"));
+    }
+    if let Some(component) = components.last() {
+        return Err(invalid(format!("missing END:{component}")));
+    }
+    Ok(events)
+}
+
+/// Join folded continuation lines.
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// `NAME;PARAM=VALUE:value`, splitting at the first colon outside quotes.
+fn parse_line(line: &str) -> Option<Property> {
+    let mut in_quotes = false;
+    let (colon, _) = line.char_indices().find(|&(_, c)| {
+        if c == '"' {
+            in_quotes = !in_quotes;
+        }
+        c == ':' && !in_quotes
+    })?;
+
+    let mut head = line[..colon].split(';');
+    let name = head.next()?.trim().to_ascii_uppercase();
+    if name.is_empty() {
+        return None;
+    }
+    let params = head
+        .filter_map(|param| param.split_once('='))
+        .map(|(key, value)| {
+            (
+                key.trim().to_ascii_uppercase(),
+                value.trim().trim_matches('"').to_string(),
+            )
+        })
+        .collect();
+
+    Some(Property {
+        name,
+        params,
+        value: line[colon + 1..].to_string(),
+    })
+}
+
+fn event_from(properties: &[Property], tz: &'static Tz) -> Result<IcsEvent, A4Error> {
+    let get = |name: &str| properties.iter().find(|p| p.name == name);
+    let time_of = |p: &Property| parse_time(&p.value, p.param("VALUE"), p.param("TZID"), tz);
+
+    let (start, zone) = time_of(get("DTSTART").ok_or_else(|| invalid("VEVENT without DTSTART"))?)?;
+    let end = match (get("DTEND"), get("DURATION")) {
+        (Some(end), _) => Some(time_of(end)?.0),
+        (None, Some(duration)) => {
+            let duration = parse_duration(&duration.value)
+                .ok_or_else(|| invalid(format!("bad DURATION '{}'", duration.value)))?;
+            Some(add_duration(start, duration))
+        }
+        (None, None) => None,
+    };
+
+    let summary = get("SUMMARY")
+        .map(|p| unescape(&p.value))
+        .unwrap_or_default();
+    let uid = match get("UID") {
+        Some(uid) => uid.value.trim().to_string(),
+        None => format!("{start:?}-{summary}"),
+    };
+
+    let mut exdates = Vec::new();
+    for exdate in properties.iter().filter(|p| p.name == "EXDATE") {
+        for value in exdate.value.split(',') {
+            exdates.push(parse_time(value, exdate.param("VALUE"), exdate.param("TZID"), tz)?.0);
+        }
+    }
+
+    Ok(IcsEvent {
+        uid,
+        summary,
+        location: get("LOCATION")
+            .map(|p| unescape(&p.value))
+            .filter(|location| !location.trim().is_empty()),
+        start,
+        end,
+        zone,
+        rrule: get("RRULE").map(|p| p.value.trim().to_string()),
+        exdates,
+        recurrence_id: get("RECURRENCE-ID")
+            .map(time_of)
+            .transpose()?
+            .map(|(at, _)| at),
+        cancelled: get("STATUS").is_some_and(|p| p.value.trim().eq_ignore_ascii_case("CANCELLED")),
+    })
+}
+
+/// A `DATE` or `DATE-TIME` value, with the zone it is in.
+fn parse_time(
+    value: &str,
+    kind: Option<&str>,
+    tzid: Option<&str>,
+    tz: &'static Tz,
+) -> Result<(EventTime, &'static Tz), A4Error> {
+    let value = value.trim();
+    let bad = || invalid(format!("bad date '{value}'"));
+    let number = |from: usize, to: usize| -> Result<u32, A4Error> {
+        value
+            .get(from..to)
+            .filter(|digits| digits.bytes().all(|b| b.is_ascii_digit()))
+            .and_then(|digits| digits.parse().ok())
+            .ok_or_else(bad)
+    };
+
+    let month = Month::try_from(number(4, 6)? as u8).map_err(|_| bad())?;
+    let date = Date::from_calendar_date(number(0, 4)? as i32, month, number(6, 8)? as u8)
+        .map_err(|_| bad())?;
+    if value.len() == 8 || kind.is_some_and(|kind| kind.eq_ignore_ascii_case("DATE")) {
+        return Ok((EventTime::Date(date), tz));
+    }
+
+    if value.get(8..9) != Some("T") {
+        return Err(bad());
+    }
+    let time = Time::from_hms(
+        number(9, 11)? as u8,
+        number(11, 13)? as u8,
+        number(13, 15)? as u8,
+    )
+    .map_err(|_| bad())?;
+    let local = PrimitiveDateTime::new(date, time);
+
+    if value.ends_with('Z') {
+        return Ok((EventTime::DateTime(local.assume_utc()), timezones::db::UTC));
+    }
+    let zone = match tzid {
+        Some(id) => timezones::get_by_name(id.trim_start_matches('/')).unwrap_or_else(|| {
+            tracing::warn!("Unknown TZID {id}, assuming the [calendar] time zone");
+            tz
+        }),
+        None => tz,
+    };
+    let at = local_datetime(local, zone).ok_or_else(bad)?;
+    Ok((EventTime::DateTime(at), zone))
+}
+
+/// An RFC 5545 duration such as `PT45M` or `P1DT2H`.
+fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let (negative, rest) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.trim_start_matches('+')),
+    };
+
+    let mut total = Duration::ZERO;
+    let mut number = String::new();
+    for c in rest.strip_prefix('P')?.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            unit => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                total += match unit {
+                    'W' => Duration::weeks(n),
+                    'D' => Duration::days(n),
+                    'H' => Duration::hours(n),
+                    'M' => Duration::minutes(n),
+                    'S' => Duration::seconds(n),
+                    _ => return None,
+                };
+            }
+        }
+    }
+    Some(if negative { -total } else { total })
+}
+
+fn add_duration(at: EventTime, duration: Duration) -> EventTime {
+    match at {
+        EventTime::Date(date) => EventTime::Date(date + Duration::days(duration.whole_days())),
+        EventTime::DateTime(at) => EventTime::DateTime(at + duration),
+    }
+}
+
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match (c, c == '\\') {
+            (_, true) => match chars.next() {
+                Some('n' | 'N') => out.push('\n'),
+                Some(escaped) => out.push(escaped),
+                None => {}
+            },
+            (c, false) => out.push(c),
+        }
+    }
+    out
+}
+
+/// An event instance on a given day, ready to import.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Occurrence {
+    /// Identifies the instance across imports: the event UID and the date.
+    pub key: String,
+    pub start: EventTime,
+    pub end: Option<EventTime>,
+    pub summary: String,
+    pub location: Option<String>,
+}
+
+/// Instances of `events` that start on `date` in `tz`, or all-day events that
+/// cover it, all-day first and then by start. Cancelled events are left out.
+/// Recurring events are expanded for `FREQ=DAILY|WEEKLY|MONTHLY|YEARLY` with
+/// `INTERVAL`, `COUNT`, `UNTIL`, `BYDAY` (with ordinals for monthly and
+/// yearly rules), `BYMONTH` (yearly rules) and `EXDATE`.
+pub fn occurrences_on(events: &[IcsEvent], date: Date, tz: &Tz) -> Vec<Occurrence> {
+    let overridden: HashSet<(&str, Date)> = events
+        .iter()
+        .filter_map(|event| {
+            Some((
+                event.uid.as_str(),
+                local_date(event.recurrence_id?, event.zone),
+            ))
+        })
+        .collect();
+
+    let mut occurrences = Vec::new();
+    for event in events.iter().filter(|event| !event.cancelled) {
+        let starts = match &event.rrule {
+            Some(rule) if event.recurrence_id.is_none() => expand(event, rule, date)
+                .into_iter()
+                .filter(|start| {
+                    !overridden.contains(&(event.uid.as_str(), local_date(*start, event.zone)))
+                })
+                .collect(),
+            _ => vec![event.start],
+        };
+
+        for start in starts {
+            let end = event
+                .end
+                .map(|end| add_duration(start, span(event.start, end)));
+            let on_date = match start {
+                EventTime::Date(first) => {
+                    let last = match end {
+                        Some(EventTime::Date(end)) if end > first => {
+                            end.previous_day().unwrap_or(first)
+                        }
+                        _ => first,
+                    };
+                    (first..=last).contains(&date)
+                }
+                EventTime::DateTime(at) => at.to_timezone(tz).date() == date,
+            };
+            if on_date {
+                occurrences.push(Occurrence {
+                    key: format!("{}/{date}", event.uid),
+                    start,
+                    end,
+                    summary: event.summary.clone(),
+                    location: event.location.clone(),
+                });
+            }
+        }
+    }
+
+    occurrences.sort_by_key(|occurrence| match occurrence.start {
+        EventTime::Date(_) => (0, 0),
+        EventTime::DateTime(at) => (1, at.unix_timestamp()),
+    });
+    occurrences
+}
+
+fn local_date(at: EventTime, zone: &Tz) -> Date {
+    match at {
+        EventTime::Date(date) => date,
+        EventTime::DateTime(at) => at.to_timezone(zone).date(),
+    }
+}
+
+fn span(start: EventTime, end: EventTime) -> Duration {
+    match (start, end) {
+        (EventTime::Date(start), EventTime::Date(end)) => end - start,
+        (EventTime::DateTime(start), EventTime::DateTime(end)) => end - start,
+        _ => Duration::ZERO,
+    }
+}
+
+/// Starts of a recurring event on the local dates around `date`, in the
+/// event's zone. The caller keeps the ones that fall on `date`.
+fn expand(event: &IcsEvent, rule: &str, date: Date) -> Vec<EventTime> {
+    let first = local_date(event.start, event.zone);
+    let rule: HashMap<String, String> = rule
+        .split(';')
+        .filter_map(|part| part.split_once('='))
+        .map(|(key, value)| (key.trim().to_ascii_uppercase(), value.trim().to_string()))
+        .collect();
+    let recurrence = Recurrence::parse(&rule);
+    let excluded: HashSet<Date> = event
+        .exdates
+        .iter()
+        .map(|exdate| local_date(*exdate, event.zone))
+        .collect();
+    let until = rule
+        .get("UNTIL")
+        .and_then(|until| parse_time(until, None, None, event.zone).ok())
+        .map(|(until, _)| local_date(until, event.zone));
+    let count: Option<usize> = rule.get("COUNT").and_then(|count| count.parse().ok());
+
+    [date.previous_day(), Some(date), date.next_day()]
+        .into_iter()
+        .flatten()
+        .filter(|day| {
+            *day >= first
+                && until.is_none_or(|until| *day <= until)
+                && !excluded.contains(day)
+                && (*day == first
+                    || recurrence
+                        .as_ref()
+                        .is_some_and(|recurrence| recurrence.matches(first, *day)))
+                && count.is_none_or(|count| {
+                    recurrence
+                        .as_ref()
+                        .map_or(1, |recurrence| recurrence.count(first, *day))
+                        <= count
+                })
+        })
+        .filter_map(|day| match event.start {
+            EventTime::Date(_) => Some(EventTime::Date(day)),
+            EventTime::DateTime(at) => {
+                let time = at.to_timezone(event.zone).time();
+                local_datetime(PrimitiveDateTime::new(day, time), event.zone)
+                    .map(EventTime::DateTime)
+            }
+        })
+        .collect()
+}
+
+const RULE_PARTS: &[&str] = &[
+    "FREQ", "INTERVAL", "COUNT", "UNTIL", "BYDAY", "BYMONTH", "WKST",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// The recurrence pattern of an `RRULE`, without `COUNT` and `UNTIL`.
+#[derive(Debug)]
+struct Recurrence {
+    frequency: Frequency,
+    interval: i64,
+    /// `BYDAY` entries: an optional ordinal within the month or year (`2TU`,
+    /// `-1FR`) and the weekday.
+    by_day: Option<Vec<(Option<i64>, Weekday)>>,
+    /// `BYMONTH`, only supported with `FREQ=YEARLY`.
+    by_month: Option<Vec<Month>>,
+}
+
+impl Recurrence {
+    /// `None` for rules with parts or values this does not support; such
+    /// events only occur on their first day.
+    fn parse(rule: &HashMap<String, String>) -> Option<Self> {
+        if rule.keys().any(|key| !RULE_PARTS.contains(&key.as_str())) {
+            return None;
+        }
+        let frequency = match rule.get("FREQ")?.as_str() {
+            "DAILY" => Frequency::Daily,
+            "WEEKLY" => Frequency::Weekly,
+            "MONTHLY" => Frequency::Monthly,
+            "YEARLY" => Frequency::Yearly,
+            _ => return None,
+        };
+        let interval = rule
+            .get("INTERVAL")
+            .and_then(|interval| interval.parse().ok())
+            .filter(|interval| *interval > 0)
+            .unwrap_or(1);
+
+        let by_day = match rule.get("BYDAY") {
+            Some(days) => Some(
+                days.split(',')
+                    .map(|day| parse_by_day(day.trim()))
+                    .collect::<Option<Vec<_>>>()?,
+            ),
+            None => None,
+        };
+        // Ordinals only mean something within a month or a year
+        let ordinals = by_day
+            .iter()
+            .flatten()
+            .any(|(ordinal, _)| ordinal.is_some());
+        if ordinals && matches!(frequency, Frequency::Daily | Frequency::Weekly) {
+            return None;
+        }
+
+        let by_month = match rule.get("BYMONTH") {
+            Some(_) if frequency != Frequency::Yearly => return None,
+            Some(months) => Some(
+                months
+                    .split(',')
+                    .map(|month| Month::try_from(month.trim().parse::<u8>().ok()?).ok())
+                    .collect::<Option<Vec<_>>>()?,
+            ),
+            None => None,
+        };
+
+        Some(Recurrence {
+            frequency,
+            interval,
+            by_day,
+            by_month,
+        })
+    }
+
+    /// Whether `day`, on or after `first`, matches the pattern starting on
+    /// `first`. The first day itself always does.
+    fn matches(&self, first: Date, day: Date) -> bool {
+        let period = self.period(day);
+        day == first
+            || ((period - self.period(first)) % self.interval == 0
+                && self.dates_in(period, first).contains(&day))
+    }
+
+    /// 1-based position of `day` among the occurrences from `first`. Counts
+    /// whole periods rather than walking every day in between.
+    fn count(&self, first: Date, day: Date) -> usize {
+        let after_first = |date: &&Date| first < **date && **date <= day;
+        let count_in = |period: i64| {
+            self.dates_in(period, first)
+                .iter()
+                .filter(after_first)
+                .count()
+        };
+
+        let (from, to) = (self.period(first), self.period(day));
+        let later = match self.frequency {
+            Frequency::Daily => {
+                let steps = (day - first).whole_days() / self.interval;
+                let hits = |step: &i64| count_in(from + step * self.interval) == 1;
+                // The weekday repeats every 7 steps
+                let per_cycle = (1..=7).filter(hits).count();
+                let cycles = steps / 7;
+                cycles as usize * per_cycle + (cycles * 7 + 1..=steps).filter(hits).count()
+            }
+            Frequency::Weekly if to - from > self.interval => {
+                // Every week strictly in between has the same days
+                let last = from + (to - from) / self.interval * self.interval;
+                let between = ((last - from) / self.interval - 1) as usize;
+                count_in(from)
+                    + between * self.dates_in(from + self.interval, first).len()
+                    + count_in(last)
+            }
+            _ => (from..=to)
+                .step_by(self.interval as usize)
+                .map(count_in)
+                .sum(),
+        };
+        1 + later
+    }
+
+    /// Index of the day, week, month or year `date` falls in.
+    fn period(&self, date: Date) -> i64 {
+        match self.frequency {
+            Frequency::Daily => date.to_julian_day().into(),
+            // Julian day 0 is a Monday
+            Frequency::Weekly => i64::from(monday(date).to_julian_day()).div_euclid(7),
+            Frequency::Monthly => {
+                i64::from(date.year()) * 12 + i64::from(u8::from(date.month())) - 1
+            }
+            Frequency::Yearly => date.year().into(),
+        }
+    }
+
+    /// The dates in `period` the pattern starting on `first` selects, sorted.
+    fn dates_in(&self, period: i64, first: Date) -> Vec<Date> {
+        let weekdays = |days: &[(Option<i64>, Weekday)]| -> Vec<Weekday> {
+            days.iter().map(|(_, weekday)| *weekday).collect()
+        };
+        let mut dates = match self.frequency {
+            Frequency::Daily => i32::try_from(period)
+                .ok()
+                .and_then(|day| Date::from_julian_day(day).ok())
+                .filter(|day| {
+                    self.by_day
+                        .as_deref()
+                        .is_none_or(|days| weekdays(days).contains(&day.weekday()))
+                })
+                .into_iter()
+                .collect(),
+            Frequency::Weekly => {
+                let days = match self.by_day.as_deref() {
+                    Some(days) => weekdays(days),
+                    None => vec![first.weekday()],
+                };
+                i32::try_from(period * 7)
+                    .ok()
+                    .and_then(|monday| Date::from_julian_day(monday).ok())
+                    .map(|monday| {
+                        days.iter()
+                            .map(|day| {
+                                monday + Duration::days(day.number_days_from_monday().into())
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default()
+            }
+            Frequency::Monthly => {
+                let year = period.div_euclid(12) as i32;
+                let month =
+                    Month::try_from(period.rem_euclid(12) as u8 + 1).unwrap_or(Month::January);
+                self.dates_in_month(year, month, first)
+            }
+            Frequency::Yearly => {
+                let year = period as i32;
+                match (&self.by_day, &self.by_month) {
+                    (Some(days), None) => match (
+                        Date::from_calendar_date(year, Month::January, 1),
+                        Date::from_calendar_date(year, Month::December, 31),
+                    ) {
+                        (Ok(start), Ok(end)) => by_day_between(days, start, end),
+                        _ => Vec::new(),
+                    },
+                    (_, months) => months
+                        .as_deref()
+                        .unwrap_or(&[first.month()])
+                        .iter()
+                        .flat_map(|month| self.dates_in_month(year, *month, first))
+                        .collect(),
+                }
+            }
+        };
+        dates.sort();
+        dates.dedup();
+        dates
+    }
+
+    /// `BYDAY` days of the month, or the day of the month `first` fell on.
+    fn dates_in_month(&self, year: i32, month: Month, first: Date) -> Vec<Date> {
+        let Some(days) = &self.by_day else {
+            return Date::from_calendar_date(year, month, first.day())
+                .into_iter()
+                .collect();
+        };
+        match Date::from_calendar_date(year, month, 1) {
+            Ok(start) => {
+                let end = start.replace_day(month.length(year)).unwrap_or(start);
+                by_day_between(days, start, end)
+            }
+            Err(_) => Vec::new(),
+        }
+    }
+}
+
+/// `2TU`, `-1FR` or `MO`.
+fn parse_by_day(entry: &str) -> Option<(Option<i64>, Weekday)> {
+    let split = entry.len().checked_sub(2)?;
+    let weekday = match entry.get(split..)? {
+        "MO" => Weekday::Monday,
+        "TU" => Weekday::Tuesday,
+        "WE" => Weekday::Wednesday,
+        "TH" => Weekday::Thursday,
+        "FR" => Weekday::Friday,
+        "SA" => Weekday::Saturday,
+        "SU" => Weekday::Sunday,
+        _ => return None,
+    };
+    let ordinal = match &entry[..split] {
+        "" => None,
+        ordinal => Some(
+            ordinal
+                .parse::<i64>()
+                .ok()
+                .filter(|n| (1..=53).contains(&n.abs()))?,
+        ),
+    };
+    Some((ordinal, weekday))
+}
+
+/// The dates from `start` to `end` that `days` select: every such weekday, or
+/// the nth from the start (or from the end, if negative).
+fn by_day_between(days: &[(Option<i64>, Weekday)], start: Date, end: Date) -> Vec<Date> {
+    let mut dates = Vec::new();
+    for (ordinal, weekday) in days {
+        let offset = (i64::from(weekday.number_days_from_monday())
+            - i64::from(start.weekday().number_days_from_monday()))
+        .rem_euclid(7);
+        let all: Vec<Date> = std::iter::successors(Some(start + Duration::days(offset)), |day| {
+            day.checked_add(Duration::weeks(1))
+        })
+        .take_while(|day| *day <= end)
+        .collect();
+        match ordinal {
+            None => dates.extend(all),
+            Some(n) => {
+                let index = if *n > 0 {
+                    usize::try_from(n - 1).ok()
+                } else {
+                    all.len().checked_sub(n.unsigned_abs() as usize)
+                };
+                dates.extend(index.and_then(|index| all.get(index)));
+            }
+        }
+    }
+    dates
+}
+
+fn monday(date: Date) -> Date {
+    date - Duration::days(date.weekday().number_days_from_monday().into())
+}
+
+/// A block [`import`] appended.
+#[derive(Debug)]
+pub struct Imported {
+    pub path: PathBuf,
+    pub anchor: AnchorToken,
+    pub summary: String,
+}
+
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    pub added: Vec<Imported>,
+    /// Occurrences already imported earlier.
+    pub skipped: usize,
+}
+
+/// Append one `^<prefix>-HHMM` block per occurrence under the `[calendar]`
+/// heading of the daily note for the UTC day it starts on (all-day events go
+/// to `date` with `0000`). Each block ends with an `<!-- ics:<hash> -->`
+/// comment of the occurrence key, so importing again skips it. An anchor that
+/// is already taken gets the `device` suffix, or a numbered one.
+pub fn import(
+    vault: &Vault,
+    occurrences: &[Occurrence],
+    date: Date,
+    config: &CalendarConfig,
+    tz: &Tz,
+    device: Option<&str>,
+) -> Result<ImportReport, A4Error> {
+    let mut report = ImportReport::default();
+
+    for occurrence in occurrences {
+        let (day, hhmm, line) = match occurrence.start {
+            EventTime::Date(_) => (
+                UtcDay::from_date(date),
+                "0000".to_string(),
+                format!("All day: {}", one_line(&occurrence.summary)),
+            ),
+            EventTime::DateTime(at) => {
+                let local = at.to_timezone(tz);
+                let mut time = format!("{:02}:{:02}", local.hour(), local.minute());
+                if let Some(EventTime::DateTime(end)) = occurrence.end {
+                    let end = end.to_timezone(tz);
+                    time.push_str(&format!("–{:02}:{:02}", end.hour(), end.minute()));
+                }
+                (
+                    UtcDay::from_date(at.to_offset(UtcOffset::UTC).date()),
+                    format!("{:02}{:02}", local.hour(), local.minute()),
+                    format!("{time} {}", one_line(&occurrence.summary)),
+                )
+            }
+        };
+
+        let path = vault.daily_note_path(day)?;
+        let marker = format!("<!-- ics:{} -->", key_hash(&occurrence.key));
+        let mut content = line.trim_end().to_string();
+        if let Some(location) = &occurrence.location {
+            content.push_str(&format!("\nLocation: {}", one_line(location)));
+        }
+        content.push('\n');
+        content.push_str(&marker);
+
+        let anchor = AnchorToken::parse(&format!("{}-{hhmm}", config.prefix))?;
+        let device_anchor = device
+            .map(|device| AnchorToken::parse(&format!("{}-{hhmm}__{device}", config.prefix)))
+            .transpose()?;
+
+        // The marker check and the anchor choice see the note as it is under
+        // the lock, so overlapping imports add each event once.
+        let written = append_block_with(vault, &path, &config.heading, |body| {
+            if body.contains(&marker) {
+                return Ok(None);
+            }
+            let taken = body
+                .lines()
+                .filter_map(parse_marker_line)
+                .any(|token| token == anchor);
+            let anchor = match device_anchor {
+                Some(device_anchor) if taken => next_free_anchor(body, device_anchor),
+                _ => next_free_anchor(body, anchor),
+            };
+            Ok(Some((anchor, content)))
+        })?;
+        match written {
+            Some(anchor) => report.added.push(Imported {
+                path,
+                anchor,
+                summary: occurrence.summary.clone(),
+            }),
+            None => report.skipped += 1,
+        }
+    }
+
+    Ok(report)
+}
+
+fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// First 16 hex digits of the SHA-256 of `key`.
+fn key_hash(key: &str) -> String {
+    Sha256::digest(key.as_bytes())
+        .iter()
+        .take(8)
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault::VaultOpts;
+    use tempfile::TempDir;
+
+    fn berlin() -> &'static Tz {
+        timezone(&CalendarConfig {
+            timezone: Some("Europe/Berlin".to_string()),
+            ..CalendarConfig::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_block_start_keeps_utc_day() {
+        let day = UtcDay::parse("2025-09-14").unwrap();
+        let tz = berlin();
+
+        let start = block_start(day, "0930", tz).unwrap();
+        assert_eq!(utc_stamp(start), "20250914T073000Z");
+
+        // 00:30 in UTC+2 is 22:30Z the day before, so it belongs to the next local date
+        let start = block_start(day, "0030", tz).unwrap();
+        assert_eq!(utc_stamp(start), "20250914T223000Z");
+    }
+
+    #[test]
+    fn test_range_duration_and_summary() {
+        assert_eq!(
+            range_duration("Deep work 14:10–14:45 on the parser"),
+            Some(Duration::minutes(35))
+        );
+        assert_eq!(range_duration("23:30-00:15"), Some(Duration::minutes(45)));
+        assert_eq!(range_duration("at 14:10 only"), None);
+
+        assert_eq!(
+            summary("\n- [ ] Review PR, then merge\n- second\n").as_deref(),
+            Some("Review PR, then merge")
+        );
+        assert_eq!(summary("\n\n"), None);
+    }
+
+    #[test]
+    fn test_export_ics() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("capture/2025/2025-09/2025-09-14.md");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(
+            &path,
+            "## Focus\n\n^focus-0930\n\n09:30–10:15 Parser, lexer\n\n^jrnl-2200\n\nTired\n",
+        )
+        .unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+
+        let events = anchored_events(&vault, berlin(), &["focus".to_string()], None).unwrap();
+        assert_eq!(events.len(), 1);
+        let stamp = OffsetDateTime::from_unix_timestamp(1_757_836_800).unwrap();
+
+        assert_eq!(
+            to_ics(&events, stamp),
+            "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//A4//a4 export ics//EN\r\n\
+             CALSCALE:GREGORIAN\r\nBEGIN:VEVENT\r\nUID:2025-09-14-focus-0930@a4\r\n\
+             DTSTAMP:20250914T080000Z\r\nDTSTART:20250914T073000Z\r\nDTEND:20250914T081500Z\r\n\
+             SUMMARY:09:30–10:15 Parser\\, lexer\r\nDESCRIPTION:09:30–10:15 Parser\\, lexer\r\n\
+             END:VEVENT\r\nEND:VCALENDAR\r\n"
+        );
+
+        let all = anchored_events(&vault, berlin(), &[], None).unwrap();
+        assert_eq!(all.len(), 2);
+        let later = UtcDay::parse("2025-09-15").unwrap();
+        assert!(anchored_events(&vault, berlin(), &[], Some(later))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_fold() {
+        let line = format!("DESCRIPTION:{}", "é".repeat(40));
+        let folded = fold(&line);
+        assert!(folded
+            .split("\r\n")
+            .all(|part| part.len() <= 75 && part.is_char_boundary(part.len())));
+        assert_eq!(folded.replace("\r\n ", ""), line);
+    }
+
+    const ICS: &str = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\n\
+        BEGIN:VEVENT\r\nUID:standup@example.com\r\n\
+        DTSTART;TZID=Europe/Berlin:20250901T093000\r\nDTEND;TZID=Europe/Berlin:20250901T094500\r\n\
+        RRULE:FREQ=WEEKLY;BYDAY=MO,WE,FR\r\nEXDATE;TZID=Europe/Berlin:20250912T093000\r\n\
+        SUMMARY:Standup\r\nEND:VEVENT\r\n\
+        BEGIN:VEVENT\r\nUID:standup@example.com\r\nRECURRENCE-ID;TZID=Europe/Berlin:20250915T093000\r\n\
+        DTSTART;TZID=Europe/Berlin:20250915T110000\r\nDURATION:PT15M\r\nSUMMARY:Standup (moved)\r\nEND:VEVENT\r\n\
+        BEGIN:VEVENT\r\nUID:review\r\nDTSTART:20250915T120000Z\r\nDTEND:20250915T130000Z\r\n\
+        SUMMARY:Design review\\, parser\r\nLOCATION:Room 4\r\n\
+        BEGIN:VALARM\r\nTRIGGER:-PT10M\r\nEND:VALARM\r\nEND:VEVENT\r\n\
+        BEGIN:VEVENT\r\nUID:offsite\r\nDTSTART;VALUE=DATE:20250914\r\nDTEND;VALUE=DATE:20250916\r\n\
+        SUMMARY:Team offsite with a very long title that the calendar app folded onto a\r\n  second line\r\n\
+        END:VEVENT\r\n\
+        BEGIN:VEVENT\r\nUID:cancelled\r\nDTSTART:20250915T150000Z\r\nSTATUS:CANCELLED\r\nSUMMARY:Gone\r\nEND:VEVENT\r\n\
+        END:VCALENDAR\r\n";
+
+    fn date(s: &str) -> Date {
+        UtcDay::parse(s).unwrap().to_date()
+    }
+
+    #[test]
+    fn test_occurrences_on() {
+        let tz = berlin();
+        let events = parse_ics(ICS, tz).unwrap();
+        assert_eq!(events.len(), 5);
+        assert_eq!(events[2].summary, "Design review, parser");
+        assert!(events[3].summary.ends_with("folded onto a second line"));
+
+        let summaries = |day: &str| -> Vec<String> {
+            occurrences_on(&events, date(day), tz)
+                .into_iter()
+                .map(|o| o.summary)
+                .collect()
+        };
+        // Monday: the standup instance was moved, the offsite covers it
+        assert_eq!(
+            summaries("2025-09-15"),
+            vec![
+                events[3].summary.clone(),
+                "Standup (moved)".to_string(),
+                "Design review, parser".to_string()
+            ]
+        );
+        assert_eq!(summaries("2025-09-10"), vec!["Standup"]);
+        // Excluded, not a BYDAY, before the first
+        assert!(summaries("2025-09-12").is_empty());
+        assert!(summaries("2025-09-09").is_empty());
+        assert!(summaries("2025-08-29").is_empty());
+
+        let standup = &occurrences_on(&events, date("2025-09-10"), tz)[0];
+        assert_eq!(standup.key, "standup@example.com/2025-09-10");
+        let EventTime::DateTime(start) = standup.start else {
+            panic!("timed event expected");
+        };
+        assert_eq!(utc_stamp(start), "20250910T073000Z");
+    }
+
+    fn recurrence(text: &str) -> Option<Recurrence> {
+        let rule: HashMap<String, String> = text
+            .split(';')
+            .filter_map(|part| part.split_once('='))
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        Recurrence::parse(&rule)
+    }
+
+    #[test]
+    fn test_rule_parts() {
+        let matches = |text: &str, first: &str, day: &str| {
+            recurrence(text).is_some_and(|r| r.matches(date(first), date(day)))
+        };
+        assert!(matches("FREQ=DAILY;INTERVAL=2", "2025-01-31", "2025-02-02"));
+        assert!(!matches(
+            "FREQ=DAILY;INTERVAL=2",
+            "2025-01-31",
+            "2025-02-01"
+        ));
+        assert!(matches("FREQ=MONTHLY", "2025-01-31", "2025-03-31"));
+        assert!(!matches("FREQ=MONTHLY", "2025-01-31", "2025-04-30"));
+        assert!(recurrence("FREQ=MONTHLY;BYMONTHDAY=1").is_none());
+        assert!(recurrence("FREQ=WEEKLY;BYDAY=1MO").is_none());
+        assert!(recurrence("FREQ=MONTHLY;BYMONTH=5").is_none());
+        assert!(recurrence("FREQ=MONTHLY;BYDAY=0MO").is_none());
+
+        // First and third Monday, and the last Friday, of every other month
+        let rule = "FREQ=MONTHLY;INTERVAL=2;BYDAY=1MO,3MO,-1FR";
+        for day in [
+            "2025-09-01",
+            "2025-09-15",
+            "2025-09-26",
+            "2025-11-03",
+            "2025-11-28",
+        ] {
+            assert!(matches(rule, "2025-09-01", day), "{day}");
+        }
+        for day in ["2025-09-08", "2025-09-19", "2025-10-06", "2025-10-31"] {
+            assert!(!matches(rule, "2025-09-01", day), "{day}");
+        }
+        // Every Tuesday and Thursday of the month
+        assert!(matches(
+            "FREQ=MONTHLY;BYDAY=TU,TH",
+            "2025-09-02",
+            "2025-10-30"
+        ));
+        assert!(!matches(
+            "FREQ=MONTHLY;BYDAY=TU,TH",
+            "2025-09-02",
+            "2025-10-29"
+        ));
+
+        // Second Sunday of May; the 20th Monday of the year
+        assert!(matches(
+            "FREQ=YEARLY;BYMONTH=5;BYDAY=2SU",
+            "2025-05-11",
+            "2026-05-10"
+        ));
+        assert!(!matches(
+            "FREQ=YEARLY;BYMONTH=5;BYDAY=2SU",
+            "2025-05-11",
+            "2026-05-11"
+        ));
+        assert!(matches(
+            "FREQ=YEARLY;BYDAY=20MO",
+            "2025-05-19",
+            "2026-05-18"
+        ));
+        assert!(matches(
+            "FREQ=YEARLY;BYMONTH=1,7",
+            "2025-01-15",
+            "2025-07-15"
+        ));
+    }
+
+    #[test]
+    fn test_count_matches_walking_every_day() {
+        let first = date("2023-03-14");
+        for text in [
+            "FREQ=DAILY",
+            "FREQ=DAILY;INTERVAL=3;BYDAY=MO,TU",
+            "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE,FR",
+            "FREQ=WEEKLY;BYDAY=SU",
+            "FREQ=MONTHLY;BYDAY=1MO,3MO,-1FR",
+            "FREQ=MONTHLY;INTERVAL=5",
+            "FREQ=YEARLY;BYMONTH=3,9;BYDAY=-1TU,2TU",
+            "FREQ=YEARLY;BYDAY=MO",
+        ] {
+            let recurrence = recurrence(text).unwrap();
+            let mut walked = 0;
+            let mut day = first;
+            while day < date("2026-01-10") {
+                if recurrence.matches(first, day) {
+                    walked += 1;
+                    assert_eq!(recurrence.count(first, day), walked, "{text} on {day}");
+                }
+                day = day.next_day().unwrap();
+            }
+            assert!(walked > 1, "{text}");
+        }
+    }
+
+    #[test]
+    fn test_monthly_byday_occurrences() {
+        let ics = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:sync\r\n\
+            DTSTART;TZID=Europe/Berlin:20250901T100000\r\n\
+            RRULE:FREQ=MONTHLY;BYDAY=1MO,3MO;COUNT=3\r\nSUMMARY:Sync\r\n\
+            END:VEVENT\r\nEND:VCALENDAR\r\n";
+        let tz = berlin();
+        let events = parse_ics(ics, tz).unwrap();
+        let on = |day: &str| occurrences_on(&events, date(day), tz).len();
+
+        assert_eq!(on("2025-09-01"), 1);
+        assert_eq!(on("2025-09-15"), 1);
+        assert_eq!(on("2025-10-06"), 1);
+        assert_eq!(on("2025-09-08"), 0);
+        // COUNT=3 ends the series before the third Monday of October
+        assert_eq!(on("2025-10-20"), 0);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for (text, reason) in [
+            (
+                "BEGIN:VEVENT\nDTSTART:20250901\nEND:VEVENT\n",
+                "no BEGIN:VCALENDAR",
+            ),
+            (
+                "BEGIN:VCALENDAR\nBEGIN:VEVENT\nUID:a\nEND:VEVENT\nEND:VCALENDAR\n",
+                "VEVENT without DTSTART",
+            ),
+            (
+                "BEGIN:VCALENDAR\nBEGIN:VEVENT\nDTSTART:2025091\nEND:VEVENT\nEND:VCALENDAR\n",
+                "bad date '2025091'",
+            ),
+            ("BEGIN:VCALENDAR\nBEGIN:VEVENT\n", "missing END:VEVENT"),
+        ] {
+            match parse_ics(text, berlin()) {
+                Err(A4Error::InvalidCalendar { reason: r }) => assert_eq!(r, reason),
+                other => panic!("unexpected result: {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_import_skips_known_and_suffixes_collisions() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+        let path = vault.capture_day_path(UtcDay::parse("2025-09-15").unwrap());
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "## Schedule\n\n^meet-1400\n\nLunch with Sam\n").unwrap();
+
+        let tz = berlin();
+        let config = CalendarConfig::default();
+        let occurrences = occurrences_on(&parse_ics(ICS, tz).unwrap(), date("2025-09-15"), tz);
+
+        let report = import(
+            &vault,
+            &occurrences,
+            date("2025-09-15"),
+            &config,
+            tz,
+            Some("mac"),
+        )
+        .unwrap();
+        let anchors: Vec<String> = report.added.iter().map(|i| i.anchor.to_marker()).collect();
+        assert_eq!(anchors, vec!["^meet-0000", "^meet-1100", "^meet-1400__mac"]);
+
+        let body = std::fs::read_to_string(&path).unwrap();
+        let hash = key_hash("review/2025-09-15");
+        assert!(
+            body.ends_with(&format!(
+                "^meet-1400__mac\n\n14:00–15:00 Design review, parser\nLocation: Room 4\n<!-- ics:{hash} -->\n"
+            )),
+            "{body}"
+        );
+        assert!(body.contains("\n\n11:00–11:15 Standup (moved)\n"), "{body}");
+
+        let again = import(
+            &vault,
+            &occurrences,
+            date("2025-09-15"),
+            &config,
+            tz,
+            Some("mac"),
+        )
+        .unwrap();
+        assert!(again.added.is_empty());
+        assert_eq!(again.skipped, 3);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), body);
+    }
+}
@@ -0,0 +1,235 @@
+use crate::date::UtcDay;
+use crate::error::A4Error;
+use crate::sparse::{self, CaptureScope};
+use fs_err as fs;
+use gix::clone::PrepareCheckout;
+use gix::index::entry::Flags;
+use gix::progress::Discard;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+
+/// Protocol version this implementation targets (protocol §15).
+pub const PROTOCOL_VERSION: &str = "1.0.0";
+
+/// Vault-relative file recording the protocol version a vault targets.
+pub const VERSION_FILE: &str = ".a4/version";
+
+#[derive(Default)]
+pub struct CloneOptions {
+    /// Sparse-checkout only the most recent `n` capture months.
+    pub keep_months: Option<u32>,
+    /// Reference day for `keep_months`.
+    pub today: Option<UtcDay>,
+}
+
+#[derive(Debug)]
+pub struct CloneReport {
+    pub root: PathBuf,
+    /// Contents of `.a4/version`, if the vault records one.
+    pub version: Option<String>,
+    /// Sparse-checkout patterns applied, empty for a full checkout.
+    pub sparse_patterns: Vec<String>,
+}
+
+/// Clone a vault into `dest`, refusing vaults whose `.a4/version` has a
+/// different major version than [`PROTOCOL_VERSION`]. The incompatible clone
+/// is removed again before anything is checked out.
+pub fn clone(url: &str, dest: &Path, opts: &CloneOptions) -> Result<CloneReport, A4Error> {
+    if dest.exists() && fs::read_dir(dest)?.next().is_some() {
+        return Err(A4Error::CloneTargetNotEmpty {
+            path: dest.to_path_buf(),
+        });
+    }
+    let created = !dest.exists();
+
+    let git_error = |e: &dyn std::fmt::Display| A4Error::Git(format!("Failed to clone {url}: {e}"));
+    let interrupt = AtomicBool::new(false);
+    let mut prepare = gix::prepare_clone(url, dest).map_err(|e| git_error(&e))?;
+    let checkout = match prepare.fetch_then_checkout(Discard, &interrupt) {
+        Ok((checkout, _)) => checkout,
+        Err(e) => {
+            // Dropping the half-cloned repository removes `dest` entirely.
+            drop(prepare);
+            discard(dest, created);
+            return Err(git_error(&e));
+        }
+    };
+
+    // On error the checkout is dropped, which removes `dest` as well.
+    populate(checkout, opts, &interrupt).inspect_err(|_| discard(dest, created))
+}
+
+/// Check the fetched vault's version, then check it out, sparsely if asked.
+fn populate(
+    mut checkout: PrepareCheckout,
+    opts: &CloneOptions,
+    interrupt: &AtomicBool,
+) -> Result<CloneReport, A4Error> {
+    let repo = checkout.repo();
+    let version = read_version(repo)?;
+    let root = repo
+        .work_dir()
+        .ok_or_else(|| A4Error::Git("Cloned repository has no working directory".to_string()))?
+        .to_path_buf();
+
+    let Some(months) = opts.keep_months else {
+        checkout
+            .main_worktree(Discard, interrupt)
+            .map_err(|e| A4Error::Git(format!("Failed to check out: {e}")))?;
+        return Ok(CloneReport {
+            root,
+            version,
+            sparse_patterns: Vec::new(),
+        });
+    };
+
+    let today = opts.today.unwrap_or_else(crate::LocalClock::today_utc);
+    let scope = CaptureScope::recent(today, months);
+    let patterns = scope.patterns();
+    sparse::write_patterns(repo.git_dir(), &patterns)?;
+    if repo.head_id().is_ok() {
+        sparse_checkout(repo, &scope, interrupt)?;
+    }
+    checkout.persist();
+
+    Ok(CloneReport {
+        root,
+        version,
+        sparse_patterns: patterns,
+    })
+}
+
+/// Populate the working tree and index from HEAD like
+/// [`PrepareCheckout::main_worktree`], but mark files outside `scope`
+/// skip-worktree instead of writing them, as git's sparse checkout does.
+fn sparse_checkout(
+    repo: &gix::Repository,
+    scope: &CaptureScope,
+    interrupt: &AtomicBool,
+) -> Result<(), A4Error> {
+    let checkout_error =
+        |e: &dyn std::fmt::Display| A4Error::Git(format!("Failed to check out: {e}"));
+    let workdir = repo
+        .work_dir()
+        .ok_or_else(|| A4Error::Git("Cloned repository has no working directory".to_string()))?;
+    let tree = repo
+        .head_commit()
+        .map_err(|e| checkout_error(&e))?
+        .tree_id()
+        .map_err(|e| checkout_error(&e))?;
+    let mut index = repo
+        .index_from_tree(&tree)
+        .map_err(|e| checkout_error(&e))?;
+
+    for (entry, path) in index.entries_mut_with_paths() {
+        if !scope.keeps(&path.to_string()) {
+            entry.flags |= Flags::EXTENDED | Flags::SKIP_WORKTREE;
+        }
+    }
+
+    let options = gix::worktree::state::checkout::Options {
+        fs: gix::fs::Capabilities::probe(repo.git_dir()),
+        destination_is_initially_empty: true,
+        ..Default::default()
+    };
+    let objects = repo
+        .objects
+        .clone()
+        .into_arc()
+        .map_err(|e| checkout_error(&e))?;
+    gix::worktree::state::checkout(
+        &mut index, workdir, objects, &Discard, &Discard, interrupt, options,
+    )
+    .map_err(|e| checkout_error(&e))?;
+    index
+        .write(Default::default())
+        .map_err(|e| checkout_error(&e))?;
+    Ok(())
+}
+
+/// Read `.a4/version` from the fetched HEAD tree and check it is compatible.
+fn read_version(repo: &gix::Repository) -> Result<Option<String>, A4Error> {
+    let Ok(commit) = repo.head_commit() else {
+        // Empty remote: nothing to check yet
+        return Ok(None);
+    };
+    let read_error =
+        |e: &dyn std::fmt::Display| A4Error::Git(format!("Failed to read {VERSION_FILE}: {e}"));
+    let tree = commit.tree().map_err(|e| read_error(&e))?;
+    let Some(entry) = tree
+        .lookup_entry_by_path(VERSION_FILE)
+        .map_err(|e| read_error(&e))?
+    else {
+        return Ok(None);
+    };
+    let blob = entry.object().map_err(|e| read_error(&e))?;
+
+    let version = String::from_utf8_lossy(&blob.data).trim().to_string();
+    check_version(&version)?;
+    Ok(Some(version))
+}
+
+/// Accept any version with the same major number as [`PROTOCOL_VERSION`].
+pub fn check_version(version: &str) -> Result<(), A4Error> {
+    let major = |v: &str| {
+        v.trim()
+            .trim_start_matches('v')
+            .split('.')
+            .next()
+            .and_then(|major| major.parse::<u64>().ok())
+    };
+
+    match (major(version), major(PROTOCOL_VERSION)) {
+        (Some(found), Some(supported)) if found == supported => Ok(()),
+        _ => Err(A4Error::UnsupportedVersion {
+            found: version.to_string(),
+            supported: PROTOCOL_VERSION.to_string(),
+        }),
+    }
+}
+
+/// Remove a failed clone. A directory the caller created beforehand is kept,
+/// empty, even if gix already removed it.
+fn discard(dest: &Path, created: bool) {
+    let result = if created {
+        if dest.exists() {
+            fs::remove_dir_all(dest)
+        } else {
+            Ok(())
+        }
+    } else if !dest.exists() {
+        fs::create_dir_all(dest)
+    } else {
+        // Keep the (previously empty) directory the caller chose.
+        fs::read_dir(dest).and_then(|entries| {
+            entries.flatten().try_for_each(|entry| {
+                let path = entry.path();
+                if path.is_dir() {
+                    fs::remove_dir_all(path)
+                } else {
+                    fs::remove_file(path)
+                }
+            })
+        })
+    };
+    if let Err(e) = result {
+        tracing::warn!("Failed to remove {}: {}", dest.display(), e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_version() {
+        assert!(check_version("1.0.0").is_ok());
+        assert!(check_version("1.4.2\n").is_ok());
+        assert!(check_version("v1.2").is_ok());
+        assert!(matches!(
+            check_version("2.0.0"),
+            Err(A4Error::UnsupportedVersion { .. })
+        ));
+        assert!(check_version("latest").is_err());
+    }
+}
@@ -0,0 +1,240 @@
+use crate::blocks::parse_marker_line;
+use crate::diff::{self, FileDiff};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Anchors listed in the subject before the rest are counted.
+const MAX_SUBJECT_ANCHORS: usize = 3;
+
+/// Vault areas named in the commit grammar `a4: capture|plan|source|log: <short>`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Area {
+    Capture,
+    Plan,
+    Source,
+    Log,
+}
+
+impl Area {
+    pub fn of(path: &str) -> Option<Area> {
+        let path = Path::new(path);
+        if path.starts_with("capture") {
+            Some(Area::Capture)
+        } else if path.starts_with("collections/weekly-plans") {
+            Some(Area::Plan)
+        } else if path.starts_with("sources") {
+            Some(Area::Source)
+        } else if path.starts_with("projects")
+            && path.components().count() == 3
+            && path.ends_with("log.md")
+        {
+            Some(Area::Log)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Area::Capture => "capture",
+            Area::Plan => "plan",
+            Area::Source => "source",
+            Area::Log => "log",
+        }
+    }
+}
+
+/// Anchor markers among a file's added lines.
+fn added_anchors(file: &FileDiff) -> Vec<String> {
+    file.added
+        .iter()
+        .filter_map(|added| parse_marker_line(&added.text))
+        .map(|token| token.to_marker())
+        .collect()
+}
+
+/// The area with the most changed files; ties go to the area declared first.
+fn dominant_area(files: &[FileDiff]) -> Option<Area> {
+    let mut counts: BTreeMap<Area, usize> = BTreeMap::new();
+    for area in files.iter().filter_map(|f| Area::of(&f.path)) {
+        *counts.entry(area).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .max_by(|(a, a_count), (b, b_count)| a_count.cmp(b_count).then(b.cmp(a)))
+        .map(|(area, _)| area)
+}
+
+/// Build a commit message from a diff, following the protocol grammar:
+/// the dominant vault area, then a short summary of the anchors added, then
+/// one body line per file. Returns `None` for an empty diff or one that
+/// touches no vault area.
+pub fn generate(diff: &str) -> Option<String> {
+    let files = diff::parse(diff);
+    let area = dominant_area(&files)?;
+    let subject = format!("a4: {}: {}", area.as_str(), summary(&files));
+
+    let body: Vec<String> = files
+        .iter()
+        .map(|file| {
+            let anchors = added_anchors(file);
+            if file.deleted {
+                format!("- {} (deleted)", file.path)
+            } else if anchors.is_empty() {
+                format!("- {}", file.path)
+            } else {
+                format!("- {}: {}", file.path, anchors.join(", "))
+            }
+        })
+        .collect();
+
+    Some(format!("{subject}\n\n{}\n", body.join("\n")))
+}
+
+fn summary(files: &[FileDiff]) -> String {
+    let anchors: Vec<String> = files.iter().flat_map(added_anchors).collect();
+    if !anchors.is_empty() {
+        let mut short = format!(
+            "add {}",
+            anchors
+                .iter()
+                .take(MAX_SUBJECT_ANCHORS)
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        if anchors.len() > MAX_SUBJECT_ANCHORS {
+            short.push_str(&format!(
+                " and {} more",
+                anchors.len() - MAX_SUBJECT_ANCHORS
+            ));
+        }
+        return short;
+    }
+
+    let verb = if files.iter().all(|file| file.created) {
+        "add"
+    } else if files.iter().all(|file| file.deleted) {
+        "remove"
+    } else {
+        "update"
+    };
+    match files {
+        [file] => {
+            let stem = Path::new(&file.path)
+                .file_stem()
+                .map_or_else(|| file.path.clone(), |s| s.to_string_lossy().into_owned());
+            format!("{verb} {stem}")
+        }
+        _ => format!("{verb} {} files", files.len()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "\
+diff --git a/capture/2025/2025-09/2025-09-14.md b/capture/2025/2025-09/2025-09-14.md
+index 1111111..2222222 100644
+--- a/capture/2025/2025-09/2025-09-14.md
++++ b/capture/2025/2025-09/2025-09-14.md
+@@ -5,0 +6,8 @@
++
++## Focus
++
++^focus-0930
++
++Refactor the parser
++
++^eod-2215
+diff --git a/projects/a4/log.md b/projects/a4/log.md
+new file mode 100644
+index 0000000..3333333
+--- /dev/null
++++ b/projects/a4/log.md
+@@ -0,0 +1,3 @@
++^log-1000__parser
++
+++++ not a header
+diff --git a/inbox/old.md b/inbox/old.md
+deleted file mode 100644
+index 4444444..0000000
+--- a/inbox/old.md
++++ /dev/null
+@@ -1 +0,0 @@
+-^idea-0800
+";
+
+    #[test]
+    fn test_generate_message() {
+        assert_eq!(
+            generate(DIFF).unwrap(),
+            "a4: capture: add ^focus-0930, ^eod-2215, ^log-1000__parser\n\n\
+             - capture/2025/2025-09/2025-09-14.md: ^focus-0930, ^eod-2215\n\
+             - projects/a4/log.md: ^log-1000__parser\n\
+             - inbox/old.md (deleted)\n"
+        );
+        assert_eq!(generate(""), None);
+    }
+
+    #[test]
+    fn test_generate_without_anchors() {
+        let diff = "\
+diff --git a/sources/articles/gpt.md b/sources/articles/gpt.md
+new file mode 100644
+--- /dev/null
++++ b/sources/articles/gpt.md
+@@ -0,0 +1 @@
++# GPT
+";
+        assert!(generate(diff).unwrap().starts_with("a4: source: add gpt\n"));
+
+        let diff = diff.replace("sources/articles", "notes");
+        assert_eq!(generate(&diff), None);
+    }
+
+    #[test]
+    fn test_generate_names_dominant_area() {
+        let file = |path: &str| {
+            format!(
+                "diff --git a/{path} b/{path}\n--- a/{path}\n+++ b/{path}\n@@ -1,0 +2 @@\n+more\n"
+            )
+        };
+        let diff = [
+            file("capture/2025/2025-09/2025-09-14.md"),
+            file("projects/a4/log.md"),
+            file("projects/gb/log.md"),
+            file("notes/misc.md"),
+        ]
+        .concat();
+        assert!(generate(&diff)
+            .unwrap()
+            .starts_with("a4: log: update 4 files\n"));
+
+        let diff = [
+            file("sources/articles/gpt.md"),
+            file("collections/weekly-plans/2025/2025-W37.md"),
+        ]
+        .concat();
+        assert!(generate(&diff)
+            .unwrap()
+            .starts_with("a4: plan: update 2 files\n"));
+    }
+
+    #[test]
+    fn test_area_of() {
+        assert_eq!(
+            Area::of("capture/2025/2025-09/2025-09-14.md"),
+            Some(Area::Capture)
+        );
+        assert_eq!(
+            Area::of("collections/weekly-plans/2025/2025-W37.md"),
+            Some(Area::Plan)
+        );
+        assert_eq!(Area::of("collections/memo.md"), None);
+        assert_eq!(Area::of("sources/articles/gpt.md"), Some(Area::Source));
+        assert_eq!(Area::of("projects/a4/log.md"), Some(Area::Log));
+        assert_eq!(Area::of("projects/a4/notes/log.md"), None);
+    }
+}
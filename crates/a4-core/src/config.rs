@@ -0,0 +1,206 @@
+use crate::error::A4Error;
+use crate::vault::Vault;
+use fs_err as fs;
+use serde::Deserialize;
+
+/// Vault settings from `.a4/config.toml`. Every field has a default, so a
+/// missing file or section behaves like an empty one.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub rollup: RollupConfig,
+    pub tasks: TasksConfig,
+    pub sync: SyncConfig,
+    pub secrets: SecretsConfig,
+    pub crypt: CryptConfig,
+    pub publish: PublishConfig,
+    pub calendar: CalendarConfig,
+    pub obsidian: ObsidianConfig,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RollupConfig {
+    /// Anchor prefixes gathered into month roll-ups.
+    pub prefixes: Vec<String>,
+}
+
+impl Default for RollupConfig {
+    fn default() -> Self {
+        RollupConfig {
+            prefixes: vec!["jrnl".to_string()],
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TasksConfig {
+    /// Heading that `a4 tasks carry` appends under in today's note.
+    pub heading: String,
+}
+
+impl Default for TasksConfig {
+    fn default() -> Self {
+        TasksConfig {
+            heading: "Tasks".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SyncConfig {
+    /// What `a4 sync` does when capture notes were rewritten instead of appended to.
+    pub on_rewrite: RewritePolicy,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RewritePolicy {
+    #[default]
+    Refuse,
+    Warn,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SecretsConfig {
+    /// Extra regexes the pre-commit secret scan treats as credentials.
+    pub patterns: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CryptConfig {
+    /// gitattributes patterns of notes stored age-encrypted, e.g. `capture/**`.
+    pub paths: Vec<String>,
+    /// When set, only notes under `paths` whose front matter `tags` include
+    /// one of these are encrypted.
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PublishConfig {
+    /// Anchor prefixes of blocks `a4 publish` leaves out.
+    pub private_prefixes: Vec<String>,
+    /// Inline `#tags` that mark a block as private.
+    pub private_tags: Vec<String>,
+    /// Folder under `--out` that linked `assets/` files are copied into.
+    pub media_dir: String,
+    /// URL prefix for rewritten asset links; defaults to `media_dir`.
+    pub media_url: Option<String>,
+    /// Extra front matter for the static site generator, e.g. `layout = "post"`.
+    pub front_matter: toml::Table,
+}
+
+impl Default for PublishConfig {
+    fn default() -> Self {
+        PublishConfig {
+            private_prefixes: vec!["jrnl".to_string()],
+            private_tags: vec!["private".to_string()],
+            media_dir: "media".to_string(),
+            media_url: None,
+            front_matter: toml::Table::new(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CalendarConfig {
+    /// IANA time zone that anchor times are in, e.g. `Europe/Berlin`;
+    /// defaults to the system time zone.
+    pub timezone: Option<String>,
+    /// Anchor prefix of blocks `a4 import ics` appends.
+    pub prefix: String,
+    /// Heading that `a4 import ics` appends under.
+    pub heading: String,
+}
+
+impl Default for CalendarConfig {
+    fn default() -> Self {
+        CalendarConfig {
+            timezone: None,
+            prefix: "meet".to_string(),
+            heading: "Schedule".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ObsidianConfig {
+    /// Create `a4 today` notes where Obsidian's Daily Notes plugin would,
+    /// from its template (`.obsidian/daily-notes.json`).
+    pub daily_notes: bool,
+}
+
+impl Config {
+    pub fn load(vault: &Vault) -> Result<Self, A4Error> {
+        let path = vault.config_path();
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+
+        let raw = fs::read_to_string(&path)?;
+        toml::from_str(&raw).map_err(|e| A4Error::InvalidConfig {
+            path,
+            reason: e.message().to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault::VaultOpts;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_missing_config_uses_defaults() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+
+        let config = Config::load(&vault).unwrap();
+        assert_eq!(config.rollup.prefixes, vec!["jrnl"]);
+        assert_eq!(config.tasks.heading, "Tasks");
+        assert_eq!(config.sync.on_rewrite, RewritePolicy::Refuse);
+    }
+
+    #[test]
+    fn test_load_config() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::create_dir(temp_dir.path().join(".a4")).unwrap();
+        std::fs::write(
+            temp_dir.path().join(".a4/config.toml"),
+            "[rollup]\nprefixes = [\"jrnl\", \"eod\"]\n\n[tasks]\nheading = \"Carried\"\n\n[sync]\non_rewrite = \"warn\"\n\n[secrets]\npatterns = [\"acme_[0-9]{8}\"]\n",
+        )
+        .unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+
+        let config = Config::load(&vault).unwrap();
+        assert_eq!(config.rollup.prefixes, vec!["jrnl", "eod"]);
+        assert_eq!(config.tasks.heading, "Carried");
+        assert_eq!(config.sync.on_rewrite, RewritePolicy::Warn);
+        assert_eq!(config.secrets.patterns, vec!["acme_[0-9]{8}"]);
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::create_dir(temp_dir.path().join(".a4")).unwrap();
+        std::fs::write(
+            temp_dir.path().join(".a4/config.toml"),
+            "[rollup]\nprefix = 1\n",
+        )
+        .unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+
+        assert!(matches!(
+            Config::load(&vault),
+            Err(A4Error::InvalidConfig { .. })
+        ));
+    }
+}
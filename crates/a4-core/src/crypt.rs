@@ -0,0 +1,677 @@
+//! Encrypted notes in the [age](https://age-encryption.org/v1) format.
+//!
+//! Notes matching `[crypt] paths` are stored in git as age files (X25519
+//! recipients) and kept decrypted in the working copy by a git clean/smudge
+//! filter that `a4 crypt init` installs. Because age output is randomised, the
+//! clean filter reuses the staged ciphertext whenever it still decrypts to the
+//! working-copy text, so untouched notes never show up as modified.
+
+use crate::config::CryptConfig;
+use crate::error::A4Error;
+use crate::notes::split_front_matter;
+use crate::vault::Vault;
+use age::secrecy::ExposeSecret;
+use fs_err as fs;
+use std::io::{Read, Write};
+use std::path::Path;
+
+/// Name of the git filter and diff driver in `.gitattributes`.
+pub const FILTER: &str = "a4-crypt";
+
+/// Machine-local identity, under `.a4/` and never committed.
+pub const KEY_FILE: &str = "age.key";
+
+const VERSION_LINE: &str = "age-encryption.org/v1";
+
+const ATTRIBUTES_BEGIN: &str = "# a4 crypt (managed by 'a4 crypt init')";
+const ATTRIBUTES_END: &str = "# end a4 crypt";
+
+fn crypt_error(what: &str) -> A4Error {
+    A4Error::Crypt(what.to_string())
+}
+
+/// An X25519 secret key, `AGE-SECRET-KEY-1...`.
+pub struct Identity(age::x25519::Identity);
+
+/// An X25519 public key, `age1...`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Recipient(age::x25519::Recipient);
+
+impl Identity {
+    pub fn generate() -> Self {
+        Identity(age::x25519::Identity::generate())
+    }
+
+    /// Parse the first key line of an identity file, skipping `#` comments.
+    pub fn parse(text: &str) -> Result<Self, A4Error> {
+        let line = text
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with('#'))
+            .ok_or_else(|| crypt_error("identity file has no key"))?;
+        line.parse()
+            .map(Identity)
+            .map_err(|e| crypt_error(&format!("invalid key: {e}")))
+    }
+
+    pub fn recipient(&self) -> Recipient {
+        Recipient(self.0.to_public())
+    }
+
+    /// The key file contents, in the layout `age-keygen` writes.
+    pub fn to_file(&self) -> String {
+        format!("# public key: {}\n{}\n", self.recipient(), self)
+    }
+}
+
+impl std::fmt::Display for Identity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.0.to_string().expose_secret())
+    }
+}
+
+impl Recipient {
+    pub fn parse(text: &str) -> Result<Self, A4Error> {
+        text.trim()
+            .parse()
+            .map(Recipient)
+            .map_err(|e| crypt_error(&format!("invalid key: {e}")))
+    }
+}
+
+impl std::fmt::Display for Recipient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Whether `data` starts with an age header.
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(VERSION_LINE.as_bytes()) && data.get(VERSION_LINE.len()) == Some(&b'\n')
+}
+
+/// Encrypt `plaintext` to every recipient.
+pub fn encrypt(plaintext: &[u8], recipients: &[Recipient]) -> Result<Vec<u8>, A4Error> {
+    let encryptor = age::Encryptor::with_recipients(
+        recipients
+            .iter()
+            .map(|recipient| &recipient.0 as &dyn age::Recipient),
+    )
+    .map_err(|e| crypt_error(&e.to_string()))?;
+
+    let mut out = Vec::with_capacity(plaintext.len());
+    let mut writer = encryptor
+        .wrap_output(&mut out)
+        .map_err(|e| crypt_error(&format!("failed to encrypt: {e}")))?;
+    writer.write_all(plaintext)?;
+    writer.finish()?;
+    Ok(out)
+}
+
+/// Decrypt an age file with `identity`.
+pub fn decrypt(data: &[u8], identity: &Identity) -> Result<Vec<u8>, A4Error> {
+    let mut reader = age::Decryptor::new_buffered(data)
+        .and_then(|decryptor| decryptor.decrypt(std::iter::once(&identity.0 as &dyn age::Identity)))
+        .map_err(|e| crypt_error(&e.to_string()))?;
+
+    let mut plaintext = Vec::new();
+    reader
+        .read_to_end(&mut plaintext)
+        .map_err(|e| crypt_error(&format!("payload authentication failed: {e}")))?;
+    Ok(plaintext)
+}
+
+/// A vault's keys: the local identity, if this device has one, and the
+/// committed recipients every private note is encrypted to.
+pub struct Keyring {
+    pub identity: Option<Identity>,
+    pub recipients: Vec<Recipient>,
+}
+
+impl Keyring {
+    pub fn load(vault: &Vault) -> Result<Self, A4Error> {
+        let key_path = key_path(vault);
+        let identity = if key_path.exists() {
+            Some(Identity::parse(&fs::read_to_string(&key_path)?)?)
+        } else {
+            None
+        };
+
+        let recipients_path = vault.crypt_recipients_path();
+        let recipients = if recipients_path.exists() {
+            fs::read_to_string(&recipients_path)?
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(Recipient::parse)
+                .collect::<Result<_, _>>()?
+        } else {
+            Vec::new()
+        };
+
+        Ok(Keyring {
+            identity,
+            recipients,
+        })
+    }
+
+    /// Decrypt `data` if it is an age file; otherwise return it unchanged.
+    pub fn reveal(&self, path: &Path, data: Vec<u8>) -> Result<Vec<u8>, A4Error> {
+        if !is_encrypted(&data) {
+            return Ok(data);
+        }
+        let identity = self
+            .identity
+            .as_ref()
+            .ok_or_else(|| A4Error::CryptKeyMissing {
+                path: path.to_path_buf(),
+            })?;
+        decrypt(&data, identity)
+    }
+
+    /// Git clean filter: the blob to store for working-copy `plaintext`.
+    /// `staged` is the blob currently in the index, reused when it still
+    /// decrypts to the same text.
+    pub fn clean(
+        &self,
+        config: &CryptConfig,
+        plaintext: Vec<u8>,
+        staged: Option<Vec<u8>>,
+    ) -> Result<Vec<u8>, A4Error> {
+        if is_encrypted(&plaintext) || !is_private(config, &plaintext) {
+            return Ok(plaintext);
+        }
+        if let (Some(staged), Some(identity)) = (staged, &self.identity) {
+            if is_encrypted(&staged) && decrypt(&staged, identity).ok().as_ref() == Some(&plaintext)
+            {
+                return Ok(staged);
+            }
+        }
+        if self.recipients.is_empty() {
+            return Err(A4Error::CryptNotInitialized);
+        }
+        encrypt(&plaintext, &self.recipients)
+    }
+
+    /// Git smudge filter: decrypt when possible, else leave the ciphertext.
+    pub fn smudge(&self, path: &Path, data: Vec<u8>) -> Vec<u8> {
+        if !is_encrypted(&data) {
+            return data;
+        }
+        match &self.identity {
+            Some(identity) => decrypt(&data, identity).unwrap_or_else(|e| {
+                tracing::warn!("Leaving {} encrypted: {}", path.display(), e);
+                data
+            }),
+            None => data,
+        }
+    }
+}
+
+fn key_path(vault: &Vault) -> std::path::PathBuf {
+    vault.private_file_path(KEY_FILE)
+}
+
+/// Whether a note under `[crypt] paths` is encrypted: always, unless `tags`
+/// narrows it to notes whose front matter lists one of them.
+pub fn is_private(config: &CryptConfig, content: &[u8]) -> bool {
+    if config.tags.is_empty() {
+        return true;
+    }
+    let text = String::from_utf8_lossy(content);
+    let Some(front_matter) = split_front_matter(&text).0 else {
+        return false;
+    };
+    let yaml = front_matter
+        .trim_end()
+        .trim_start_matches("---")
+        .trim_end_matches("---");
+    let tags = match serde_yaml::from_str::<serde_yaml::Value>(yaml)
+        .ok()
+        .and_then(|value| value.get("tags").cloned())
+    {
+        Some(serde_yaml::Value::Sequence(tags)) => tags,
+        Some(tag @ serde_yaml::Value::String(_)) => vec![tag],
+        _ => return false,
+    };
+    tags.iter()
+        .filter_map(|tag| tag.as_str())
+        .any(|tag| config.tags.iter().any(|wanted| wanted == tag))
+}
+
+/// The `.gitattributes` block routing `[crypt] paths` through the filter.
+fn attributes_block(config: &CryptConfig) -> String {
+    let mut block = format!("{ATTRIBUTES_BEGIN}\n");
+    for pattern in &config.paths {
+        block.push_str(&format!("{pattern} filter={FILTER} diff={FILTER}\n"));
+    }
+    block.push_str(ATTRIBUTES_END);
+    block.push('\n');
+    block
+}
+
+/// Replace (or add) the managed block in `.gitattributes` text.
+fn with_attributes_block(existing: &str, block: &str) -> String {
+    let mut out = String::new();
+    let mut lines = existing.lines();
+    let mut replaced = false;
+    while let Some(line) = lines.next() {
+        if line == ATTRIBUTES_BEGIN {
+            for line in lines.by_ref() {
+                if line == ATTRIBUTES_END {
+                    break;
+                }
+            }
+            out.push_str(block);
+            replaced = true;
+        } else {
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    if !replaced {
+        if !out.is_empty() && !out.ends_with("\n\n") {
+            out.push('\n');
+        }
+        out.push_str(block);
+    }
+    out
+}
+
+fn git(root: &Path, args: &[&str]) -> Result<std::process::Output, A4Error> {
+    std::process::Command::new("git")
+        .args(args)
+        .current_dir(root)
+        .output()
+        .map_err(|e| A4Error::Git(format!("Failed to run git {}: {e}", args[0])))
+}
+
+/// The index blob for `path`, if it is tracked.
+pub fn staged_blob(root: &Path, path: &str) -> Result<Option<Vec<u8>>, A4Error> {
+    let output = git(root, &["cat-file", "blob", &format!(":{path}")])?;
+    Ok(output.status.success().then_some(output.stdout))
+}
+
+/// Whether this clone has the filter installed and `.gitattributes` matches
+/// `[crypt] paths`.
+pub fn is_installed(vault: &Vault, config: &CryptConfig) -> Result<bool, A4Error> {
+    let output = git(
+        vault.root(),
+        &["config", "--get", &format!("filter.{FILTER}.clean")],
+    )?;
+    if !output.status.success() {
+        return Ok(false);
+    }
+    let attributes = vault.root().join(".gitattributes");
+    let existing = if attributes.exists() {
+        fs::read_to_string(&attributes)?
+    } else {
+        String::new()
+    };
+    Ok(existing.contains(&attributes_block(config)))
+}
+
+/// Fail before a sync that would commit private notes in plain text.
+pub fn check(vault: &Vault, config: &CryptConfig) -> Result<(), A4Error> {
+    if config.paths.is_empty() || is_installed(vault, config)? {
+        Ok(())
+    } else {
+        Err(A4Error::CryptNotInitialized)
+    }
+}
+
+/// What `a4 crypt init` set up.
+#[derive(Debug)]
+pub struct InitReport {
+    pub recipient: String,
+    pub created_key: bool,
+    /// Working-copy files that were still encrypted and are now decrypted.
+    pub decrypted: Vec<String>,
+}
+
+/// Generate an identity into a new key file at `path`, owner-only from the
+/// start so the secret is never readable by others.
+fn create_key(path: &Path) -> Result<Identity, A4Error> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use fs_err::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+
+    let identity = Identity::generate();
+    if let Err(e) = file
+        .write_all(identity.to_file().as_bytes())
+        .and_then(|()| file.sync_all())
+    {
+        // Don't leave a truncated key behind for the next init to trust.
+        let _ = fs::remove_file(path);
+        return Err(e.into());
+    }
+    Ok(identity)
+}
+
+/// Set up encryption in this clone: create or load `.a4/age.key`, add its
+/// public key to `.a4/age-recipients`, write the `.gitattributes` block and
+/// register the filter, which runs `program` (the a4 executable). Notes
+/// checked out before the filter existed are checked out again, decrypted.
+pub fn init(vault: &Vault, config: &CryptConfig, program: &Path) -> Result<InitReport, A4Error> {
+    if config.paths.is_empty() {
+        return Err(A4Error::InvalidConfig {
+            path: vault.config_path(),
+            reason: "[crypt] paths is empty; list the notes to encrypt, e.g. \"capture/**\""
+                .to_string(),
+        });
+    }
+
+    vault.ignore_private(KEY_FILE)?;
+    let key_path = vault.private_file_path(KEY_FILE);
+    let (identity, created_key) = match create_key(&key_path) {
+        Ok(identity) => (identity, true),
+        Err(A4Error::Io(e)) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+            (Identity::parse(&fs::read_to_string(&key_path)?)?, false)
+        }
+        Err(e) => return Err(e),
+    };
+    let recipient = identity.recipient();
+
+    let keyring = Keyring::load(vault)?;
+    if !keyring.recipients.contains(&recipient) {
+        let path = vault.crypt_recipients_path();
+        let mut recipients = if path.exists() {
+            fs::read_to_string(&path)?
+        } else {
+            String::new()
+        };
+        if !recipients.is_empty() && !recipients.ends_with('\n') {
+            recipients.push('\n');
+        }
+        recipients.push_str(&format!("{recipient}\n"));
+        fs::write(&path, recipients)?;
+    }
+
+    let attributes = vault.root().join(".gitattributes");
+    let existing = if attributes.exists() {
+        fs::read_to_string(&attributes)?
+    } else {
+        String::new()
+    };
+    fs::write(
+        &attributes,
+        with_attributes_block(&existing, &attributes_block(config)),
+    )?;
+
+    let command = format!(
+        "'{}' --vault . crypt",
+        program.display().to_string().replace('\'', r"'\''")
+    );
+    for (key, value) in [
+        (
+            format!("filter.{FILTER}.clean"),
+            format!("{command} clean %f"),
+        ),
+        (
+            format!("filter.{FILTER}.smudge"),
+            format!("{command} smudge %f"),
+        ),
+        (format!("filter.{FILTER}.required"), "true".to_string()),
+        (
+            format!("diff.{FILTER}.textconv"),
+            format!("{command} textconv"),
+        ),
+    ] {
+        let output = git(vault.root(), &["config", &key, &value])?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(A4Error::Git(format!("git config {key} failed: {stderr}")));
+        }
+    }
+
+    // Re-check out notes cloned before the filter existed, so the smudge
+    // filter decrypts them and the index records their decrypted size.
+    let mut decrypted = Vec::new();
+    let output = git(vault.root(), &["ls-files", "-z"])?;
+    for path in output.stdout.split(|&b| b == 0).filter(|p| !p.is_empty()) {
+        let path = String::from_utf8_lossy(path).into_owned();
+        let Ok(data) = fs::read(vault.root().join(&path)) else {
+            continue;
+        };
+        if is_encrypted(&data)
+            && decrypt(&data, &identity).is_ok()
+            && staged_blob(vault.root(), &path)?.as_ref() == Some(&data)
+        {
+            decrypted.push(path);
+        }
+    }
+    if !decrypted.is_empty() {
+        let mut args = vec!["checkout", "--"];
+        args.extend(decrypted.iter().map(String::as_str));
+        let output = git(vault.root(), &args)?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(A4Error::Git(format!(
+                "Failed to decrypt checkout: {stderr}"
+            )));
+        }
+    }
+
+    Ok(InitReport {
+        recipient: recipient.to_string(),
+        created_key,
+        decrypted,
+    })
+}
+
+/// Decrypt `data` read from `path` if it is an age file, e.g. a HEAD blob
+/// compared against the decrypted working copy.
+pub fn reveal(vault: &Vault, path: &Path, data: Vec<u8>) -> Result<Vec<u8>, A4Error> {
+    if !is_encrypted(&data) {
+        return Ok(data);
+    }
+    Keyring::load(vault)?.reveal(path, data)
+}
+
+/// Decrypt `path` in place if it is still encrypted, so it can be edited.
+pub fn ensure_decrypted(vault: &Vault, path: &Path) -> Result<(), A4Error> {
+    if !path.exists() {
+        return Ok(());
+    }
+    let data = fs::read(path)?;
+    if is_encrypted(&data) {
+        fs::write(path, reveal(vault, path, data)?)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// age's STREAM chunk size.
+    const CHUNK_SIZE: usize = 64 * 1024;
+
+    fn private_config() -> CryptConfig {
+        CryptConfig {
+            paths: vec!["capture/**".to_string()],
+            tags: vec!["private".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let identity = Identity::generate();
+        let other = Identity::generate();
+        let recipients = [identity.recipient(), other.recipient()];
+
+        let big: Vec<u8> = (0..CHUNK_SIZE * 2 + 100).map(|i| i as u8).collect();
+        for plaintext in [b"".as_slice(), b"## Journal\n\nprivate\n", &big] {
+            let sealed = encrypt(plaintext, &recipients).unwrap();
+            assert!(is_encrypted(&sealed));
+            assert_eq!(decrypt(&sealed, &identity).unwrap(), plaintext);
+            assert_eq!(decrypt(&sealed, &other).unwrap(), plaintext);
+        }
+
+        // A full final chunk is not followed by an empty one
+        let exact = vec![7u8; CHUNK_SIZE];
+        let sealed = encrypt(&exact, &recipients).unwrap();
+        assert_eq!(decrypt(&sealed, &identity).unwrap(), exact);
+    }
+
+    /// The reference `age` CLI, or `rage`, if either is installed.
+    fn age_cli() -> Option<&'static str> {
+        ["age", "rage"].into_iter().find(|bin| {
+            std::process::Command::new(bin)
+                .arg("--version")
+                .output()
+                .is_ok_and(|output| output.status.success())
+        })
+    }
+
+    #[test]
+    fn test_interop_with_age_cli() {
+        let Some(bin) = age_cli() else {
+            eprintln!("skipping: neither age nor rage is installed");
+            return;
+        };
+        let dir = tempfile::TempDir::new().unwrap();
+        let identity = Identity::generate();
+        let key_file = dir.path().join("age.key");
+        fs::write(&key_file, identity.to_file()).unwrap();
+        let run = |args: &[&std::ffi::OsStr]| {
+            let output = std::process::Command::new(bin).args(args).output().unwrap();
+            assert!(
+                output.status.success(),
+                "{bin} failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+            output.stdout
+        };
+
+        // Ours, decrypted by the CLI with our key file
+        let plaintext = b"## Journal\n\nprivate\n";
+        let sealed = dir.path().join("ours.age");
+        fs::write(
+            &sealed,
+            encrypt(plaintext, &[identity.recipient()]).unwrap(),
+        )
+        .unwrap();
+        let opened = run(&[
+            "-d".as_ref(),
+            "-i".as_ref(),
+            key_file.as_os_str(),
+            sealed.as_os_str(),
+        ]);
+        assert_eq!(opened, plaintext);
+
+        // The CLI's, decrypted by us
+        let note = dir.path().join("note.md");
+        fs::write(&note, plaintext).unwrap();
+        let recipient = identity.recipient().to_string();
+        let theirs = run(&["-r".as_ref(), recipient.as_ref(), note.as_os_str()]);
+        assert!(is_encrypted(&theirs));
+        assert_eq!(decrypt(&theirs, &identity).unwrap(), plaintext);
+    }
+
+    #[test]
+    fn test_wrong_key_and_tampering() {
+        let identity = Identity::generate();
+        let sealed = encrypt(b"secret", &[identity.recipient()]).unwrap();
+
+        assert!(decrypt(&sealed, &Identity::generate()).is_err());
+
+        let mut tampered = sealed.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(decrypt(&tampered, &identity).is_err());
+
+        let header_end = sealed.windows(4).position(|w| w == b"\n---").unwrap();
+        let mut tampered = sealed.clone();
+        tampered[header_end - 2] ^= 1;
+        assert!(decrypt(&tampered, &identity).is_err());
+    }
+
+    #[test]
+    fn test_key_encoding() {
+        let identity = Identity::generate();
+        let file = identity.to_file();
+        assert!(file.starts_with("# public key: age1"));
+        assert!(identity.to_string().starts_with("AGE-SECRET-KEY-1"));
+
+        let parsed = Identity::parse(&file).unwrap();
+        assert_eq!(parsed.recipient(), identity.recipient());
+        assert_eq!(
+            Recipient::parse(&identity.recipient().to_string()).unwrap(),
+            identity.recipient()
+        );
+        assert!(Recipient::parse(&identity.to_string()).is_err());
+        assert!(Identity::parse("# nothing here\n").is_err());
+    }
+
+    #[test]
+    fn test_clean_keeps_staged_ciphertext() {
+        let identity = Identity::generate();
+        let keyring = Keyring {
+            recipients: vec![identity.recipient()],
+            identity: Some(identity),
+        };
+        let config = private_config();
+        let note = b"---\ntags: [journal, private]\n---\n## Evening\n".to_vec();
+
+        let first = keyring.clean(&config, note.clone(), None).unwrap();
+        assert!(is_encrypted(&first));
+        let again = keyring
+            .clean(&config, note.clone(), Some(first.clone()))
+            .unwrap();
+        assert_eq!(again, first);
+
+        let mut edited = note.clone();
+        edited.extend_from_slice(b"\nmore\n");
+        assert_ne!(
+            keyring.clean(&config, edited, Some(first.clone())).unwrap(),
+            first
+        );
+
+        // Untagged notes and existing ciphertext pass through
+        let public = b"## Focus\n".to_vec();
+        assert_eq!(
+            keyring.clean(&config, public.clone(), None).unwrap(),
+            public
+        );
+        assert_eq!(keyring.clean(&config, first.clone(), None).unwrap(), first);
+        assert_eq!(keyring.smudge(Path::new("n.md"), first), note);
+    }
+
+    #[test]
+    fn test_is_private() {
+        let config = private_config();
+        assert!(is_private(&config, b"---\ntags: private\n---\nbody\n"));
+        assert!(!is_private(&config, b"---\ntags: [work]\n---\nbody\n"));
+        assert!(!is_private(&config, b"body only\n"));
+        assert!(is_private(
+            &CryptConfig {
+                tags: Vec::new(),
+                ..config
+            },
+            b"body only\n"
+        ));
+    }
+
+    #[test]
+    fn test_attributes_block() {
+        let block = attributes_block(&private_config());
+        assert_eq!(
+            block,
+            "# a4 crypt (managed by 'a4 crypt init')\ncapture/** filter=a4-crypt diff=a4-crypt\n# end a4 crypt\n"
+        );
+        let added = with_attributes_block("*.png binary\n", &block);
+        assert_eq!(added, format!("*.png binary\n\n{block}"));
+
+        let other = attributes_block(&CryptConfig {
+            paths: vec!["journal/**".to_string()],
+            tags: Vec::new(),
+        });
+        let replaced = with_attributes_block(&added, &other);
+        assert_eq!(replaced, format!("*.png binary\n\n{other}"));
+    }
+}
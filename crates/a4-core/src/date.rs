@@ -1,12 +1,21 @@
-use time::{OffsetDateTime, UtcOffset};
+use crate::error::A4Error;
+use std::fmt;
+use time::{OffsetDateTime, PrimitiveDateTime, UtcOffset};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct UtcDay {
     pub year: i32,
     pub month: u8,
     pub day: u8,
 }
 
+/// A calendar month, as used by `capture/YYYY/YYYY-MM/` directories.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct UtcMonth {
+    pub year: i32,
+    pub month: u8,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct IsoWeek {
     pub year: i32,
@@ -15,6 +24,26 @@ pub struct IsoWeek {
 
 pub struct LocalClock;
 
+/// Source of the current time for rendering notes. `LocalClock` reads the
+/// system clock; a fixed `OffsetDateTime` gives reproducible output.
+pub trait Clock {
+    /// The current instant, in the local offset.
+    fn now(&self) -> OffsetDateTime;
+}
+
+impl Clock for LocalClock {
+    fn now(&self) -> OffsetDateTime {
+        let local_offset = UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC);
+        OffsetDateTime::now_utc().to_offset(local_offset)
+    }
+}
+
+impl Clock for OffsetDateTime {
+    fn now(&self) -> OffsetDateTime {
+        *self
+    }
+}
+
 impl LocalClock {
     pub fn now_local_hhmm() -> String {
         let now = OffsetDateTime::now_utc();
@@ -25,6 +54,14 @@ impl LocalClock {
         format!("{:02}{:02}", local_time.hour(), local_time.minute())
     }
 
+    /// Current UTC instant as RFC 3339 with whole seconds (`2025-09-14T07:58:12Z`).
+    pub fn now_utc_rfc3339() -> String {
+        let now = OffsetDateTime::now_utc();
+        let now = now.replace_nanosecond(0).unwrap_or(now);
+        now.format(&time::format_description::well_known::Rfc3339)
+            .unwrap_or_else(|_| now.to_string())
+    }
+
     pub fn today_utc() -> UtcDay {
         let now = OffsetDateTime::now_utc();
         UtcDay {
@@ -48,7 +85,360 @@ impl LocalClock {
 }
 
 impl UtcDay {
+    /// Parse an ISO `YYYY-MM-DD` date, rejecting days that do not exist.
+    pub fn parse(s: &str) -> Result<Self, A4Error> {
+        let invalid = || A4Error::InvalidDate {
+            input: s.to_string(),
+        };
+
+        let mut parts = s.trim().splitn(3, '-');
+        let (Some(year), Some(month), Some(day)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+
+        if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+            return Err(invalid());
+        }
+
+        let year: i32 = year.parse().map_err(|_| invalid())?;
+        let month: u8 = month.parse().map_err(|_| invalid())?;
+        let day: u8 = day.parse().map_err(|_| invalid())?;
+
+        let month = time::Month::try_from(month).map_err(|_| invalid())?;
+        let date = time::Date::from_calendar_date(year, month, day).map_err(|_| invalid())?;
+        Ok(Self::from_date(date))
+    }
+
     pub fn filename(&self) -> String {
-        format!("{:04}-{:02}-{:02}.md", self.year, self.month, self.day)
+        format!("{self}.md")
+    }
+
+    pub fn from_date(date: time::Date) -> Self {
+        UtcDay {
+            year: date.year(),
+            month: date.month() as u8,
+            day: date.day(),
+        }
+    }
+
+    pub fn to_date(&self) -> time::Date {
+        time::Date::from_calendar_date(
+            self.year,
+            time::Month::try_from(self.month).unwrap(),
+            self.day,
+        )
+        .unwrap()
+    }
+
+    pub fn month(&self) -> UtcMonth {
+        UtcMonth {
+            year: self.year,
+            month: self.month,
+        }
+    }
+}
+
+impl fmt::Display for UtcDay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl UtcMonth {
+    /// Parse `YYYY-MM`.
+    pub fn parse(s: &str) -> Result<Self, A4Error> {
+        let invalid = || A4Error::InvalidDate {
+            input: s.to_string(),
+        };
+
+        let (year, month) = s.trim().split_once('-').ok_or_else(invalid)?;
+        if year.len() != 4 || month.len() != 2 {
+            return Err(invalid());
+        }
+
+        let year: i32 = year.parse().map_err(|_| invalid())?;
+        let month: u8 = month.parse().map_err(|_| invalid())?;
+        if !(1..=12).contains(&month) {
+            return Err(invalid());
+        }
+
+        Ok(UtcMonth { year, month })
+    }
+
+    /// The calendar month before this one.
+    pub fn previous(self) -> UtcMonth {
+        if self.month == 1 {
+            UtcMonth {
+                year: self.year - 1,
+                month: 12,
+            }
+        } else {
+            UtcMonth {
+                year: self.year,
+                month: self.month - 1,
+            }
+        }
+    }
+}
+
+impl fmt::Display for UtcMonth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}", self.year, self.month)
+    }
+}
+
+const TOKENS: &[&str] = &[
+    "YYYY", "YY", "MMMM", "MMM", "MM", "M", "DDDD", "DDD", "Do", "DD", "D", "dddd", "ddd", "dd",
+    "d", "GGGG", "WW", "W", "HH", "H", "hh", "h", "mm", "m", "ss", "s", "A", "a",
+];
+
+/// Format `at` with a moment.js pattern, as Obsidian and templates use. `[text]` is
+/// literal; unsupported letters are copied through unchanged.
+pub fn format_moment(pattern: &str, at: PrimitiveDateTime) -> String {
+    let mut out = String::new();
+    let mut rest = pattern;
+    while let Some(c) = rest.chars().next() {
+        if c == '[' {
+            if let Some(end) = rest.find(']') {
+                out.push_str(&rest[1..end]);
+                rest = &rest[end + 1..];
+                continue;
+            }
+        }
+        match TOKENS.iter().find(|token| rest.starts_with(**token)) {
+            Some(token) => {
+                out.push_str(&format_token(token, at));
+                rest = &rest[token.len()..];
+            }
+            None => {
+                out.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    out
+}
+
+fn format_token(token: &str, at: PrimitiveDateTime) -> String {
+    let date = at.date();
+    let hour12 = match at.hour() % 12 {
+        0 => 12,
+        h => h,
+    };
+    match token {
+        "YYYY" => format!("{:04}", date.year()),
+        "YY" => format!("{:02}", date.year().rem_euclid(100)),
+        "MMMM" => date.month().to_string(),
+        "MMM" => date.month().to_string()[..3].to_string(),
+        "MM" => format!("{:02}", date.month() as u8),
+        "M" => (date.month() as u8).to_string(),
+        "DDDD" => format!("{:03}", date.ordinal()),
+        "DDD" => date.ordinal().to_string(),
+        "Do" => ordinal(date.day()),
+        "DD" => format!("{:02}", date.day()),
+        "D" => date.day().to_string(),
+        "dddd" => date.weekday().to_string(),
+        "ddd" => date.weekday().to_string()[..3].to_string(),
+        "dd" => date.weekday().to_string()[..2].to_string(),
+        "d" => date.weekday().number_days_from_sunday().to_string(),
+        "GGGG" => format!("{:04}", date.to_iso_week_date().0),
+        "WW" => format!("{:02}", date.iso_week()),
+        "W" => date.iso_week().to_string(),
+        "HH" => format!("{:02}", at.hour()),
+        "H" => at.hour().to_string(),
+        "hh" => format!("{hour12:02}"),
+        "h" => hour12.to_string(),
+        "mm" => format!("{:02}", at.minute()),
+        "m" => at.minute().to_string(),
+        "ss" => format!("{:02}", at.second()),
+        "s" => at.second().to_string(),
+        "A" => if at.hour() < 12 { "AM" } else { "PM" }.to_string(),
+        "a" => if at.hour() < 12 { "am" } else { "pm" }.to_string(),
+        _ => token.to_string(),
+    }
+}
+
+fn ordinal(n: u8) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{n}{suffix}")
+}
+
+/// The date a [`format_moment`] name was made from, or `None` if `pattern`
+/// could not have produced `text` at midnight of some day. Names are read
+/// back token by token, then checked by formatting the date again.
+pub fn parse_moment(pattern: &str, text: &str) -> Option<time::Date> {
+    let (mut year, mut month, mut day, mut ordinal) = (None, None, None, None);
+    let (mut rest, mut input) = (pattern, text);
+    while let Some(c) = rest.chars().next() {
+        if c == '[' {
+            if let Some(end) = rest.find(']') {
+                input = input.strip_prefix(&rest[1..end])?;
+                rest = &rest[end + 1..];
+                continue;
+            }
+        }
+        let Some(token) = TOKENS.iter().find(|token| rest.starts_with(**token)) else {
+            input = input.strip_prefix(c)?;
+            rest = &rest[c.len_utf8()..];
+            continue;
+        };
+        rest = &rest[token.len()..];
+        match *token {
+            "MMMM" | "MMM" => {
+                let (n, len) = (1..=12u8).find_map(|n| {
+                    let name = time::Month::try_from(n).ok()?.to_string();
+                    let name = if *token == "MMM" { &name[..3] } else { &name };
+                    input.starts_with(name).then_some((n, name.len()))
+                })?;
+                month = Some(n);
+                input = &input[len..];
+            }
+            "dddd" | "ddd" | "dd" | "A" | "a" => {
+                // Weekday names and AM/PM are all ASCII letters.
+                let len = input
+                    .find(|c: char| !c.is_ascii_alphabetic())
+                    .unwrap_or(input.len());
+                input = &input[len..];
+            }
+            _ => {
+                let (min, max) = match *token {
+                    "YYYY" | "GGGG" => (4, 4),
+                    "DDDD" => (3, 3),
+                    "DDD" => (1, 3),
+                    "d" => (1, 1),
+                    "Do" | "M" | "D" | "W" | "H" | "h" | "m" | "s" => (1, 2),
+                    _ => (2, 2),
+                };
+                let len = input
+                    .bytes()
+                    .take(max)
+                    .take_while(u8::is_ascii_digit)
+                    .count();
+                if len < min {
+                    return None;
+                }
+                let value: i32 = input[..len].parse().ok()?;
+                input = &input[len..];
+                if *token == "Do" {
+                    input = input.get(2..)?;
+                }
+                match *token {
+                    "YYYY" => year = Some(value),
+                    "YY" => year = Some(2000 + value),
+                    "MM" | "M" => month = Some(value as u8),
+                    "DD" | "D" | "Do" => day = Some(value as u8),
+                    "DDDD" | "DDD" => ordinal = Some(value as u16),
+                    _ => {}
+                }
+            }
+        }
+    }
+    if !input.is_empty() {
+        return None;
+    }
+
+    let date = match (year?, month, day, ordinal) {
+        (year, Some(month), Some(day), _) => {
+            time::Date::from_calendar_date(year, time::Month::try_from(month).ok()?, day).ok()?
+        }
+        (year, _, _, Some(ordinal)) => time::Date::from_ordinal_date(year, ordinal).ok()?,
+        _ => return None,
+    };
+    let midnight = PrimitiveDateTime::new(date, time::Time::MIDNIGHT);
+    (format_moment(pattern, midnight) == text).then_some(date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_day() {
+        let day = UtcDay::parse("2025-09-14").unwrap();
+        assert_eq!(
+            day,
+            UtcDay {
+                year: 2025,
+                month: 9,
+                day: 14
+            }
+        );
+        assert_eq!(day.to_string(), "2025-09-14");
+        assert_eq!(day.filename(), "2025-09-14.md");
+    }
+
+    #[test]
+    fn test_parse_month() {
+        let month = UtcMonth::parse("2025-09").unwrap();
+        assert_eq!(month.to_string(), "2025-09");
+        assert_eq!(UtcDay::parse("2025-09-14").unwrap().month(), month);
+        assert!(UtcMonth::parse("2025-13").is_err());
+        assert!(UtcMonth::parse("2025-9").is_err());
+        assert_eq!(month.previous().to_string(), "2025-08");
+        assert_eq!(
+            UtcMonth::parse("2025-01").unwrap().previous().to_string(),
+            "2024-12"
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_day() {
+        assert!(UtcDay::parse("2025-02-30").is_err());
+        assert!(UtcDay::parse("2025-9-14").is_err());
+        assert!(UtcDay::parse("20250914").is_err());
+        assert!(UtcDay::parse("2025-13-01").is_err());
+    }
+
+    #[test]
+    fn test_format_moment() {
+        let at = PrimitiveDateTime::new(
+            UtcDay::parse("2025-09-04").unwrap().to_date(),
+            time::Time::from_hms(14, 5, 9).unwrap(),
+        );
+        assert_eq!(format_moment("YYYY-MM-DD", at), "2025-09-04");
+        assert_eq!(
+            format_moment("YYYY/MM/YYYY-MM-DD", at),
+            "2025/09/2025-09-04"
+        );
+        assert_eq!(
+            format_moment("dddd, MMMM Do YY", at),
+            "Thursday, September 4th 25"
+        );
+        assert_eq!(format_moment("ddd D MMM", at), "Thu 4 Sep");
+        assert_eq!(format_moment("GGGG-[W]WW", at), "2025-W36");
+        assert_eq!(format_moment("HH:mm:ss h A", at), "14:05:09 2 PM");
+        assert_eq!(format_moment("[Day] DDD", at), "Day 247");
+    }
+
+    #[test]
+    fn test_parse_moment() {
+        let day = UtcDay::parse("2025-09-04").unwrap().to_date();
+        assert_eq!(parse_moment("YYYY-MM-DD", "2025-09-04"), Some(day));
+        assert_eq!(
+            parse_moment("YYYY/MM/YYYY-MM-DD", "2025/09/2025-09-04"),
+            Some(day)
+        );
+        assert_eq!(
+            parse_moment("dddd, MMMM Do YY", "Thursday, September 4th 25"),
+            Some(day)
+        );
+        assert_eq!(parse_moment("[Day] DDD YYYY", "Day 247 2025"), Some(day));
+
+        // Names that disagree with themselves or the pattern
+        assert_eq!(
+            parse_moment("YYYY/MM/YYYY-MM-DD", "2025/08/2025-09-04"),
+            None
+        );
+        assert_eq!(parse_moment("dddd YYYY-MM-DD", "Monday 2025-09-04"), None);
+        assert_eq!(parse_moment("YYYY-MM-DD", "2025-02-30"), None);
+        assert_eq!(parse_moment("YYYY-MM-DD", "2025-09-04 notes"), None);
+        assert_eq!(parse_moment("YYYY-MM-DD", "notes"), None);
     }
 }
@@ -0,0 +1,120 @@
+/// One file from a zero-context unified diff (`git diff --unified=0`).
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct FileDiff {
+    pub path: String,
+    pub created: bool,
+    pub deleted: bool,
+    pub added: Vec<AddedLine>,
+}
+
+/// A line added by the diff, with its 1-based line number in the new file.
+#[derive(Debug, PartialEq, Eq)]
+pub struct AddedLine {
+    pub line: usize,
+    pub text: String,
+}
+
+/// Parse `git diff` output into per-file changes.
+pub fn parse(diff: &str) -> Vec<FileDiff> {
+    let mut files: Vec<FileDiff> = Vec::new();
+    let mut next_line = None;
+
+    for line in diff.lines() {
+        if line.starts_with("diff --git ") {
+            files.push(FileDiff::default());
+            next_line = None;
+            continue;
+        }
+        let Some(file) = files.last_mut() else {
+            continue;
+        };
+
+        if let Some(header) = line.strip_prefix("@@ ") {
+            next_line = hunk_start(header);
+        } else if let Some(line_no) = next_line.as_mut() {
+            if let Some(text) = line.strip_prefix('+') {
+                file.added.push(AddedLine {
+                    line: *line_no,
+                    text: text.to_string(),
+                });
+                *line_no += 1;
+            } else if line.starts_with(' ') {
+                *line_no += 1;
+            }
+        } else if line.starts_with("new file mode") {
+            file.created = true;
+        } else if line.starts_with("deleted file mode") {
+            file.deleted = true;
+        } else if let Some(path) = line.strip_prefix("+++ b/") {
+            file.path = path.to_string();
+        } else if let Some(path) = line.strip_prefix("--- a/") {
+            file.path = path.to_string();
+        }
+    }
+
+    files.retain(|file| !file.path.is_empty());
+    files
+}
+
+/// First new-file line of a hunk header like `-5,0 +6,8 @@`.
+fn hunk_start(header: &str) -> Option<usize> {
+    let new_range = header
+        .split_whitespace()
+        .find(|part| part.starts_with('+'))?;
+    let start = new_range[1..].split(',').next()?;
+    start.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let diff = "\
+diff --git a/capture/2025/2025-09/2025-09-14.md b/capture/2025/2025-09/2025-09-14.md
+index 1111111..2222222 100644
+--- a/capture/2025/2025-09/2025-09-14.md
++++ b/capture/2025/2025-09/2025-09-14.md
+@@ -5,0 +6,2 @@ ## Focus
++
++^focus-0930
+@@ -9 +12 @@
+-old
++new
+diff --git a/projects/a4/log.md b/projects/a4/log.md
+new file mode 100644
+index 0000000..3333333
+--- /dev/null
++++ b/projects/a4/log.md
+@@ -0,0 +1,2 @@
++^log-1000
+++++ not a header
+diff --git a/inbox/old.md b/inbox/old.md
+deleted file mode 100644
+index 4444444..0000000
+--- a/inbox/old.md
++++ /dev/null
+@@ -1 +0,0 @@
+-^idea-0800
+";
+        let files = parse(diff);
+        assert_eq!(files.len(), 3);
+
+        assert_eq!(files[0].path, "capture/2025/2025-09/2025-09-14.md");
+        let added: Vec<(usize, &str)> = files[0]
+            .added
+            .iter()
+            .map(|a| (a.line, a.text.as_str()))
+            .collect();
+        assert_eq!(added, vec![(6, ""), (7, "^focus-0930"), (12, "new")]);
+
+        assert!(files[1].created);
+        assert_eq!(files[1].added[1].text, "+++ not a header");
+        assert_eq!(files[1].added[1].line, 2);
+
+        assert_eq!(files[2].path, "inbox/old.md");
+        assert!(files[2].deleted);
+        assert!(files[2].added.is_empty());
+    }
+}
@@ -21,6 +21,9 @@ pub enum A4Error {
     #[error("Path traversal attempt detected: {path}")]
     PathTraversal { path: PathBuf },
 
+    #[error("Not a note: {path} (only .md files outside dot-directories can be appended to)")]
+    NotANote { path: PathBuf },
+
     #[error("Invalid UTF-8 in file: {path}")]
     InvalidUtf8 { path: PathBuf },
 
@@ -39,6 +42,120 @@ pub enum A4Error {
     #[error("No remote configured for repository")]
     NoRemote,
 
+    #[error(
+        "Rebase conflict detected when syncing with {remote_ref}. \
+        Please resolve the conflicts manually:\n\
+        1. Run 'git rebase {remote_ref}' in the vault directory\n\
+        2. Resolve any conflicts\n\
+        3. Run 'git rebase --continue' after resolving\n\
+        4. Run 'a4 sync' again to push changes"
+    )]
+    RebaseConflict { remote_ref: String },
+
+    #[error("Invalid date: {input} (expected YYYY-MM-DD)")]
+    InvalidDate { input: String },
+
+    #[error("Block ^{anchor} not found in {path}")]
+    BlockNotFound { path: PathBuf, anchor: String },
+
+    #[error("Invalid slug '{slug}': must match [a-z0-9][a-z0-9-]{{1,63}}")]
+    InvalidSlug { slug: String },
+
+    #[error("Project already exists: {path}")]
+    ProjectExists { path: PathBuf },
+
+    #[error("Project not found: {slug} (create it with 'a4 project new {slug}')")]
+    ProjectNotFound { slug: String },
+
+    #[error("Source already exists: {path}")]
+    SourceExists { path: PathBuf },
+
+    #[error("Invalid config {path}: {reason}")]
+    InvalidConfig { path: PathBuf, reason: String },
+
+    #[error("Cannot stitch ![[{link}]]: {reason}")]
+    BrokenEmbed { link: String, reason: String },
+
+    #[error(
+        "Refusing to sync: capture notes were rewritten, not appended to:\n{report}\
+        Restore the original lines, or set on_rewrite = \"warn\" under [sync] in .a4/config.toml"
+    )]
+    CaptureRewritten { report: String },
+
+    #[error(
+        "Refusing to commit: possible secrets in pending changes:\n{report}\
+        Remove them, or list false positives in .a4/secrets-allowlist"
+    )]
+    SecretsFound { report: String },
+
+    #[error("Cannot clone into {path}: directory is not empty")]
+    CloneTargetNotEmpty { path: PathBuf },
+
+    #[error(
+        "Vault targets A4 protocol {found}, but this a4 supports {supported}; upgrade a4 to use it"
+    )]
+    UnsupportedVersion { found: String, supported: String },
+
+    #[error("Invalid sparse target '{target}': expected capture/YYYY or capture/YYYY/YYYY-MM")]
+    InvalidSparseTarget { target: String },
+
+    #[error("Sparse-checkout pattern not managed by a4: {pattern}")]
+    UnmanagedSparsePattern { pattern: String },
+
+    #[error("{path} is not checked out (sparse checkout); run 'a4 sparse add {dir}' first")]
+    NotCheckedOut { path: PathBuf, dir: String },
+
+    #[error(
+        "No assets/ directory in {root}; add the a4-assets repository as a submodule at assets/"
+    )]
+    AssetsMissing { root: PathBuf },
+
+    #[error("The assets/ submodule is not checked out; run 'git submodule update --init assets'")]
+    AssetsNotCheckedOut,
+
+    #[error(
+        "The assets/ submodule is on a detached HEAD; check out a branch \
+        (e.g. 'git -C assets switch main') before syncing"
+    )]
+    AssetsDetached,
+
+    #[error("Encryption error: {0}")]
+    Crypt(String),
+
+    #[error(
+        "Encrypted paths are configured under [crypt] but this clone is not set up; run 'a4 crypt init'"
+    )]
+    CryptNotInitialized,
+
+    #[error(
+        "{path} is encrypted and this device has no key; copy .a4/age.key from another device and run 'a4 crypt init'"
+    )]
+    CryptKeyMissing { path: PathBuf },
+
+    #[error(
+        "Refusing to publish: links into capture/ remain:\n{report}\
+        Embed the content with ![[...]] or remove the links"
+    )]
+    PublishLeaksCapture { report: String },
+
+    #[error("Cannot publish: {path} not found (is the assets/ submodule checked out?)")]
+    PublishAssetMissing { path: PathBuf },
+
+    #[error("Unknown time zone '{name}' in [calendar] timezone (expected an IANA name such as Europe/Berlin)")]
+    UnknownTimezone { name: String },
+
+    #[error("Invalid iCalendar data: {reason}")]
+    InvalidCalendar { reason: String },
+
+    #[error("Invalid Obsidian settings in {path}: {reason}")]
+    InvalidObsidianSettings { path: PathBuf, reason: String },
+
+    #[error("Unknown template variable {{{{{name}}}}}")]
+    UnknownTemplateVariable { name: String },
+
+    #[error("Invalid template: {reason}")]
+    InvalidTemplate { reason: String },
+
     #[error("Failed to parse front matter: {0}")]
     FrontMatterParse(String),
 }
@@ -1,4 +1,6 @@
 use crate::error::A4Error;
+use crate::notes::{temp_path, TEMP_PATH_PATTERN};
+use std::io::Write;
 use std::path::Path;
 
 pub trait GitBackend {
@@ -14,6 +16,29 @@ pub trait GitBackend {
     fn head_branch(&self) -> Result<String, A4Error>;
     fn diverged(&self, remote_ref: &str) -> Result<bool, A4Error>;
     fn has_uncommitted_changes(&self) -> Result<bool, A4Error>;
+    /// Working-tree changes (including untracked files) relative to HEAD.
+    fn status_entries(&self) -> Result<Vec<StatusEntry>, A4Error>;
+    /// Commits `(ahead, behind)` of `remote_ref`, or `None` if the ref is unknown.
+    fn ahead_behind(&self, remote_ref: &str) -> Result<Option<(usize, usize)>, A4Error>;
+    /// Contents of `path` at HEAD, or `None` if it is not tracked there.
+    fn head_file(&self, path: &str) -> Result<Option<Vec<u8>>, A4Error>;
+    /// Zero-context diff of everything `stage_all` would commit, relative to
+    /// HEAD. Leaves the real index untouched.
+    fn pending_diff(&self) -> Result<String, A4Error>;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileChange {
+    Added,
+    Modified,
+    Deleted,
+}
+
+/// One path from `git status`, relative to the repository root.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StatusEntry {
+    pub path: String,
+    pub change: FileChange,
 }
 
 #[derive(Debug, PartialEq)]
@@ -32,6 +57,7 @@ impl GitBackend for GixBackend {
         let repo = gix::discover(cwd).map_err(|_| A4Error::GitRepoNotFound {
             path: cwd.to_path_buf(),
         })?;
+        exclude_temp_paths(repo.git_dir())?;
 
         Ok(GixBackend { repo })
     }
@@ -255,4 +281,156 @@ impl GitBackend for GixBackend {
 
         Ok(!output.stdout.is_empty())
     }
+
+    fn status_entries(&self) -> Result<Vec<StatusEntry>, A4Error> {
+        let output = std::process::Command::new("git")
+            .arg("status")
+            .arg("--porcelain=v1")
+            .arg("-z")
+            .arg("--no-renames")
+            .arg("--untracked-files=all")
+            .current_dir(self.repo.work_dir().unwrap())
+            .output()
+            .map_err(|e| A4Error::Git(format!("Failed to check status: {e}")))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(A4Error::Git(format!("Status failed: {stderr}")));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .split('\0')
+            .filter(|record| record.len() > 3)
+            .map(|record| {
+                let (code, path) = record.split_at(3);
+                let change = if code.contains('D') {
+                    FileChange::Deleted
+                } else if code.contains('?') || code.contains('A') {
+                    FileChange::Added
+                } else {
+                    FileChange::Modified
+                };
+                StatusEntry {
+                    path: path.to_string(),
+                    change,
+                }
+            })
+            .collect())
+    }
+
+    fn ahead_behind(&self, remote_ref: &str) -> Result<Option<(usize, usize)>, A4Error> {
+        let output = std::process::Command::new("git")
+            .arg("rev-list")
+            .arg("--left-right")
+            .arg("--count")
+            .arg(format!("HEAD...{remote_ref}"))
+            .current_dir(self.repo.work_dir().unwrap())
+            .output()
+            .map_err(|e| A4Error::Git(format!("Failed to count commits: {e}")))?;
+
+        if !output.status.success() {
+            return Ok(None);
+        }
+
+        let counts = String::from_utf8_lossy(&output.stdout);
+        let mut counts = counts.split_whitespace().map(|n| n.parse().unwrap_or(0));
+        Ok(Some((
+            counts.next().unwrap_or(0),
+            counts.next().unwrap_or(0),
+        )))
+    }
+
+    fn head_file(&self, path: &str) -> Result<Option<Vec<u8>>, A4Error> {
+        let output = std::process::Command::new("git")
+            .arg("show")
+            .arg(format!("HEAD:{path}"))
+            .current_dir(self.repo.work_dir().unwrap())
+            .output()
+            .map_err(|e| A4Error::Git(format!("Failed to read {path} at HEAD: {e}")))?;
+
+        Ok(output.status.success().then_some(output.stdout))
+    }
+
+    fn pending_diff(&self) -> Result<String, A4Error> {
+        let workdir = self
+            .repo
+            .work_dir()
+            .ok_or_else(|| A4Error::Git("No working directory".to_string()))?;
+
+        // Stage into a scratch copy of the index so the real one is untouched;
+        // the name is unique so concurrent runs never share it.
+        let index = temp_path(&self.repo.git_dir().join("a4-pending.index"));
+        let diff = diff_with_index(workdir, &self.repo.index_path(), &index);
+        let _ = std::fs::remove_file(&index);
+        diff
+    }
+}
+
+/// List the pattern of note temporary files in `<git dir>/info/exclude`, so
+/// one a crash left behind is neither staged nor reported. The file stays
+/// local to this clone.
+fn exclude_temp_paths(git_dir: &Path) -> Result<(), A4Error> {
+    let path = git_dir.join("info").join("exclude");
+    let existing = match std::fs::read_to_string(&path) {
+        Ok(existing) => existing,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+    if existing
+        .lines()
+        .any(|line| line.trim() == TEMP_PATH_PATTERN)
+    {
+        return Ok(());
+    }
+
+    std::fs::create_dir_all(git_dir.join("info"))?;
+    let separator = if existing.is_empty() || existing.ends_with('\n') {
+        ""
+    } else {
+        "\n"
+    };
+    std::fs::OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(&path)?
+        .write_all(format!("{separator}{TEMP_PATH_PATTERN}\n").as_bytes())?;
+    Ok(())
+}
+
+/// `git add -A` into `index` (seeded from `real_index`), then diff it
+/// against HEAD.
+fn diff_with_index(workdir: &Path, real_index: &Path, index: &Path) -> Result<String, A4Error> {
+    if real_index.exists() {
+        std::fs::copy(real_index, index)?;
+    }
+
+    let git = |args: &[&str]| {
+        let output = std::process::Command::new("git")
+            .args(args)
+            .env("GIT_INDEX_FILE", index)
+            .current_dir(workdir)
+            .output()
+            .map_err(|e| A4Error::Git(format!("Failed to diff pending changes: {e}")))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(A4Error::Git(format!(
+                "git {} failed: {stderr}",
+                args.join(" ")
+            )));
+        }
+        Ok(output.stdout)
+    };
+
+    git(&["add", "-A"])?;
+    let diff = git(&[
+        "-c",
+        "core.quotePath=false",
+        "diff",
+        "--cached",
+        "--no-color",
+        "--no-ext-diff",
+        "--no-renames",
+        "--unified=0",
+    ])?;
+    Ok(String::from_utf8_lossy(&diff).into_owned())
 }
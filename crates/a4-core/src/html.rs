@@ -0,0 +1,552 @@
+//! Offline HTML → Markdown conversion for ingesting saved web pages.
+//!
+//! This is deliberately small: it builds a forgiving element tree (unclosed
+//! tags are closed implicitly) and renders the common article elements.
+//! Scripts, styles and other non-content elements are dropped.
+
+/// An element that is still open while parsing: name, attributes, children.
+type OpenElement = (String, Vec<(String, String)>, Vec<Node>);
+
+#[derive(Debug)]
+enum Node {
+    Element {
+        name: String,
+        attrs: Vec<(String, String)>,
+        children: Vec<Node>,
+    },
+    Text(String),
+}
+
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+const SKIPPED_ELEMENTS: &[&str] = &[
+    "script", "style", "noscript", "template", "head", "svg", "iframe", "form", "button",
+];
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style"];
+const BLOCK_ELEMENTS: &[&str] = &[
+    "p",
+    "div",
+    "section",
+    "article",
+    "main",
+    "header",
+    "footer",
+    "aside",
+    "nav",
+    "figure",
+    "figcaption",
+    "table",
+    "tr",
+    "dl",
+    "dt",
+    "dd",
+    "body",
+    "html",
+];
+
+/// The converted document.
+#[derive(Debug, PartialEq, Eq)]
+pub struct HtmlDocument {
+    /// Text of the `<title>` element, if any.
+    pub title: Option<String>,
+    pub markdown: String,
+}
+
+pub fn html_to_markdown(html: &str) -> HtmlDocument {
+    let nodes = parse(html);
+    let title = find_title(&nodes);
+    let markdown = normalize(&render_nodes(&nodes));
+    HtmlDocument { title, markdown }
+}
+
+fn parse(html: &str) -> Vec<Node> {
+    // Stack of open elements; index 0 is a synthetic root.
+    let mut stack: Vec<OpenElement> = vec![(String::new(), Vec::new(), Vec::new())];
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix("<!--") {
+            rest = after.find("-->").map_or("", |end| &after[end + 3..]);
+            continue;
+        }
+
+        if rest.starts_with("<!") || rest.starts_with("<?") {
+            rest = rest.find('>').map_or("", |end| &rest[end + 1..]);
+            continue;
+        }
+
+        if let Some(after) = rest.strip_prefix("</") {
+            let end = after.find('>').unwrap_or(after.len());
+            let name = after[..end].trim().to_ascii_lowercase();
+            rest = after.get(end + 1..).unwrap_or("");
+
+            if let Some(pos) = stack.iter().rposition(|(open, _, _)| *open == name) {
+                if pos > 0 {
+                    while stack.len() > pos {
+                        close_top(&mut stack);
+                    }
+                }
+            }
+            continue;
+        }
+
+        if rest.starts_with('<') && rest[1..].starts_with(|c: char| c.is_ascii_alphabetic()) {
+            let end = tag_end(rest);
+            let (name, attrs, self_closing) = parse_tag(&rest[1..end]);
+            rest = rest.get(end + 1..).unwrap_or("");
+
+            if RAW_TEXT_ELEMENTS.contains(&name.as_str()) {
+                let close = format!("</{name}");
+                let lower = rest.to_ascii_lowercase();
+                rest = lower
+                    .find(&close)
+                    .and_then(|pos| rest[pos..].find('>').map(|end| &rest[pos + end + 1..]))
+                    .unwrap_or("");
+                continue;
+            }
+
+            // A new <p> or <li> implicitly closes an open one of the same kind.
+            if name == "p" || name == "li" {
+                if let Some(pos) = stack.iter().rposition(|(open, _, _)| *open == name) {
+                    let crosses_list = stack[pos + 1..]
+                        .iter()
+                        .any(|(open, _, _)| open == "ul" || open == "ol");
+                    if !crosses_list {
+                        while stack.len() > pos {
+                            close_top(&mut stack);
+                        }
+                    }
+                }
+            }
+
+            if self_closing || VOID_ELEMENTS.contains(&name.as_str()) {
+                stack.last_mut().unwrap().2.push(Node::Element {
+                    name,
+                    attrs,
+                    children: Vec::new(),
+                });
+            } else {
+                stack.push((name, attrs, Vec::new()));
+            }
+            continue;
+        }
+
+        let end = rest
+            .char_indices()
+            .skip(1)
+            .find(|(_, c)| *c == '<')
+            .map_or(rest.len(), |(pos, _)| pos);
+        stack
+            .last_mut()
+            .unwrap()
+            .2
+            .push(Node::Text(decode_entities(&rest[..end])));
+        rest = &rest[end..];
+    }
+
+    while stack.len() > 1 {
+        close_top(&mut stack);
+    }
+    stack
+        .pop()
+        .map(|(_, _, children)| children)
+        .unwrap_or_default()
+}
+
+fn close_top(stack: &mut Vec<OpenElement>) {
+    if let Some((name, attrs, children)) = stack.pop() {
+        stack.last_mut().unwrap().2.push(Node::Element {
+            name,
+            attrs,
+            children,
+        });
+    }
+}
+
+/// Index of the `>` closing a tag, skipping over quoted attribute values.
+fn tag_end(s: &str) -> usize {
+    let mut quote = None;
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '>') => return i,
+            _ => {}
+        }
+    }
+    s.len()
+}
+
+fn parse_tag(tag: &str) -> (String, Vec<(String, String)>, bool) {
+    let self_closing = tag.trim_end().ends_with('/');
+    let tag = tag.trim_end().trim_end_matches('/');
+
+    let name_end = tag.find(|c: char| c.is_whitespace()).unwrap_or(tag.len());
+    let name = tag[..name_end].to_ascii_lowercase();
+
+    let mut attrs = Vec::new();
+    let mut rest = tag[name_end..].trim_start();
+    while !rest.is_empty() {
+        let key_end = rest
+            .find(|c: char| c == '=' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        let key = rest[..key_end].to_ascii_lowercase();
+        rest = rest[key_end..].trim_start();
+
+        let value = if let Some(after) = rest.strip_prefix('=') {
+            let after = after.trim_start();
+            if let Some(q) = after.chars().next().filter(|c| *c == '"' || *c == '\'') {
+                let body = &after[1..];
+                let end = body.find(q).unwrap_or(body.len());
+                rest = body.get(end + 1..).unwrap_or("");
+                body[..end].to_string()
+            } else {
+                let end = after.find(char::is_whitespace).unwrap_or(after.len());
+                rest = &after[end..];
+                after[..end].to_string()
+            }
+        } else {
+            String::new()
+        };
+
+        if !key.is_empty() {
+            attrs.push((key, decode_entities(&value)));
+        }
+        rest = rest.trim_start();
+    }
+
+    (name, attrs, self_closing)
+}
+
+fn decode_entities(s: &str) -> String {
+    if !s.contains('&') {
+        return s.to_string();
+    }
+
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(pos) = rest.find('&') {
+        out.push_str(&rest[..pos]);
+        rest = &rest[pos..];
+
+        let decoded = rest[1..]
+            .find(';')
+            .filter(|end| *end <= 10)
+            .and_then(|end| {
+                let entity = &rest[1..end + 1];
+                let c = match entity {
+                    "amp" => Some('&'),
+                    "lt" => Some('<'),
+                    "gt" => Some('>'),
+                    "quot" => Some('"'),
+                    "apos" => Some('\''),
+                    "nbsp" => Some(' '),
+                    "mdash" => Some('—'),
+                    "ndash" => Some('–'),
+                    "hellip" => Some('…'),
+                    "rsquo" => Some('’'),
+                    "lsquo" => Some('‘'),
+                    "rdquo" => Some('”'),
+                    "ldquo" => Some('“'),
+                    _ => entity
+                        .strip_prefix("#x")
+                        .or_else(|| entity.strip_prefix("#X"))
+                        .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                        .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                        .and_then(char::from_u32),
+                };
+                c.map(|c| (c, end + 2))
+            });
+
+        match decoded {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn find_title(nodes: &[Node]) -> Option<String> {
+    for node in nodes {
+        if let Node::Element { name, children, .. } = node {
+            if name == "title" {
+                let title = collapse_whitespace(&text_content(children));
+                return Some(title.trim().to_string()).filter(|t| !t.is_empty());
+            }
+            if let Some(title) = find_title(children) {
+                return Some(title);
+            }
+        }
+    }
+    None
+}
+
+fn text_content(nodes: &[Node]) -> String {
+    nodes
+        .iter()
+        .map(|node| match node {
+            Node::Text(text) => text.clone(),
+            Node::Element { children, .. } => text_content(children),
+        })
+        .collect()
+}
+
+fn collapse_whitespace(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut last_space = false;
+    for c in s.chars() {
+        if c.is_whitespace() {
+            if !last_space {
+                out.push(' ');
+            }
+            last_space = true;
+        } else {
+            out.push(c);
+            last_space = false;
+        }
+    }
+    out
+}
+
+fn attr<'a>(attrs: &'a [(String, String)], key: &str) -> Option<&'a str> {
+    attrs
+        .iter()
+        .find(|(k, _)| k == key)
+        .map(|(_, v)| v.as_str())
+}
+
+fn render_nodes(nodes: &[Node]) -> String {
+    nodes.iter().map(render_node).collect()
+}
+
+fn render_node(node: &Node) -> String {
+    let (name, attrs, children) = match node {
+        Node::Text(text) => return collapse_whitespace(text),
+        Node::Element {
+            name,
+            attrs,
+            children,
+        } => (name.as_str(), attrs, children),
+    };
+
+    if SKIPPED_ELEMENTS.contains(&name) || name == "title" {
+        return String::new();
+    }
+
+    match name {
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+            let level = name[1..].parse::<usize>().unwrap_or(1);
+            let text = render_nodes(children).trim().replace('\n', " ");
+            format!("\n\n{} {}\n\n", "#".repeat(level), text)
+        }
+        "br" => "\n".to_string(),
+        "hr" => "\n\n---\n\n".to_string(),
+        "strong" | "b" => wrap_inline(children, "**"),
+        "em" | "i" => wrap_inline(children, "*"),
+        "code" => {
+            let text = text_content(children);
+            format!("`{}`", text.trim())
+        }
+        "pre" => {
+            let text = text_content(children);
+            format!("\n\n```\n{}\n```\n\n", text.trim_matches('\n'))
+        }
+        "a" => {
+            let text = render_nodes(children).trim().to_string();
+            match attr(attrs, "href") {
+                Some(href) if !href.is_empty() && !href.starts_with("javascript:") => {
+                    if text.is_empty() {
+                        format!("<{href}>")
+                    } else {
+                        format!("[{text}]({href})")
+                    }
+                }
+                _ => text,
+            }
+        }
+        "img" => {
+            let alt = attr(attrs, "alt").unwrap_or("");
+            match attr(attrs, "src") {
+                Some(src) => format!("![{alt}]({src})"),
+                None => String::new(),
+            }
+        }
+        "ul" | "ol" => render_list(children, name == "ol"),
+        "blockquote" => {
+            let inner = normalize(&render_nodes(children));
+            let quoted: Vec<String> = inner
+                .lines()
+                .map(|line| {
+                    if line.is_empty() {
+                        ">".to_string()
+                    } else {
+                        format!("> {line}")
+                    }
+                })
+                .collect();
+            format!("\n\n{}\n\n", quoted.join("\n"))
+        }
+        "li" => render_nodes(children),
+        "td" | "th" => format!("{} ", render_nodes(children).trim()),
+        _ if BLOCK_ELEMENTS.contains(&name) => format!("\n\n{}\n\n", render_nodes(children)),
+        _ => render_nodes(children),
+    }
+}
+
+fn wrap_inline(children: &[Node], marker: &str) -> String {
+    let text = render_nodes(children);
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return text;
+    }
+    let lead = if text.starts_with(char::is_whitespace) {
+        " "
+    } else {
+        ""
+    };
+    let trail = if text.ends_with(char::is_whitespace) {
+        " "
+    } else {
+        ""
+    };
+    format!("{lead}{marker}{trimmed}{marker}{trail}")
+}
+
+fn render_list(children: &[Node], ordered: bool) -> String {
+    let mut items = Vec::new();
+    for child in children {
+        let Node::Element {
+            name,
+            children: item_children,
+            ..
+        } = child
+        else {
+            continue;
+        };
+        if name != "li" {
+            continue;
+        }
+
+        let content = normalize(&render_nodes(item_children)).replace("\n\n", "\n");
+        let bullet = if ordered {
+            format!("{}. ", items.len() + 1)
+        } else {
+            "- ".to_string()
+        };
+        let indent = " ".repeat(bullet.len());
+
+        let mut lines = content.lines();
+        let mut item = format!("{bullet}{}", lines.next().unwrap_or(""));
+        for line in lines {
+            item.push('\n');
+            item.push_str(&indent);
+            item.push_str(line);
+        }
+        items.push(item);
+    }
+    format!("\n\n{}\n\n", items.join("\n"))
+}
+
+/// Trim trailing spaces, strip leading spaces outside code fences and list
+/// continuations, and collapse runs of blank lines.
+fn normalize(markdown: &str) -> String {
+    let mut out: Vec<String> = Vec::new();
+    let mut in_fence = false;
+
+    for line in markdown.lines() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+            out.push(line.trim().to_string());
+            continue;
+        }
+
+        if in_fence {
+            out.push(line.to_string());
+            continue;
+        }
+
+        let trimmed = line.trim_end();
+        let in_list = out.last().is_some_and(|prev| is_list_line(prev));
+        let line = if in_list && trimmed.starts_with("  ") {
+            trimmed.to_string()
+        } else {
+            trimmed.trim_start().to_string()
+        };
+
+        if line.is_empty() && out.last().is_none_or(|prev| prev.is_empty()) {
+            continue;
+        }
+        out.push(line);
+    }
+
+    while out.last().is_some_and(|line| line.is_empty()) {
+        out.pop();
+    }
+    out.join("\n")
+}
+
+fn is_list_line(line: &str) -> bool {
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    line.starts_with(' ')
+        || line.starts_with("- ")
+        || (digits > 0 && line[digits..].starts_with(". "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_article_conversion() {
+        let html = r#"<!DOCTYPE html>
+<html><head><title>State of GPT &amp; friends</title>
+<style>body { color: red; }</style><script>alert("<p>x</p>")</script></head>
+<body>
+  <h1>State of GPT</h1>
+  <p>Models are <strong>trained</strong> in
+     stages. See <a href="https://example.com/talk">the talk</a>.</p>
+  <ul><li>Pretraining</li><li>Fine-tuning<ul><li>SFT</li></ul></li></ul>
+  <blockquote><p>Quote one</p><p>Quote two</p></blockquote>
+  <pre><code>fn main() {
+    println!("hi");
+}</code></pre>
+  <p>Fish &amp; chips &#8212; &#x263A;<br>next line</p>
+</body></html>"#;
+
+        let doc = html_to_markdown(html);
+        assert_eq!(doc.title.as_deref(), Some("State of GPT & friends"));
+        assert_eq!(
+            doc.markdown,
+            "# State of GPT\n\n\
+             Models are **trained** in stages. See [the talk](https://example.com/talk).\n\n\
+             - Pretraining\n\
+             - Fine-tuning\n  - SFT\n\n\
+             > Quote one\n>\n> Quote two\n\n\
+             ```\nfn main() {\n    println!(\"hi\");\n}\n```\n\n\
+             Fish & chips — ☺\nnext line"
+        );
+    }
+
+    #[test]
+    fn test_ordered_list_and_image() {
+        let html = "<ol><li>One<li>Two</ol><p><img src=\"a.png\" alt=\"Diagram\"></p>";
+        let doc = html_to_markdown(html);
+        assert_eq!(doc.title, None);
+        assert_eq!(doc.markdown, "1. One\n2. Two\n\n![Diagram](a.png)");
+    }
+
+    #[test]
+    fn test_unclosed_paragraphs() {
+        let doc = html_to_markdown("<p>First<p>Second");
+        assert_eq!(doc.markdown, "First\n\nSecond");
+    }
+}
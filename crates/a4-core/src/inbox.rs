@@ -0,0 +1,249 @@
+use crate::anchors::AnchorToken;
+use crate::append::{append_block_suffixed, interpret_escapes, AppendOptions};
+use crate::blocks::{find_block, parse_blocks, Block};
+use crate::crypt;
+use crate::date::UtcDay;
+use crate::error::A4Error;
+use crate::notes::{read_note, split_front_matter};
+use crate::sparse;
+use crate::vault::Vault;
+use fs_err as fs;
+use regex::Regex;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use walkdir::WalkDir;
+
+pub const INBOX_HEADING: &str = "Inbox";
+pub const TRIAGE_HEADING: &str = "Triage";
+pub const TRIAGE_PREFIX: &str = "triage";
+
+static MOVED_REGEX: OnceLock<Regex> = OnceLock::new();
+
+/// An inbox block that has not been triaged yet.
+#[derive(Debug)]
+pub struct InboxItem {
+    pub day: UtcDay,
+    pub path: PathBuf,
+    pub block: Block,
+}
+
+/// Append a quick drop to the day's paste zone, creating the file if needed.
+/// Returns the anchor actually written (suffixed if `anchor` was already taken).
+pub fn drop_block(
+    vault: &Vault,
+    day: UtcDay,
+    anchor: AnchorToken,
+    content: &str,
+) -> Result<AnchorToken, A4Error> {
+    let path = vault.inbox_day_path(day);
+    let content = interpret_escapes(content);
+
+    append_block_suffixed(
+        vault,
+        &path,
+        AppendOptions {
+            heading: INBOX_HEADING,
+            anchor,
+            content: &content,
+        },
+    )
+}
+
+/// All inbox blocks without a triage reference, oldest day first.
+pub fn pending_items(vault: &Vault) -> Result<Vec<InboxItem>, A4Error> {
+    let inbox_dir = vault.root().join("inbox");
+    if !inbox_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut items = Vec::new();
+    for entry in WalkDir::new(&inbox_dir).sort_by_file_name() {
+        let entry = entry.map_err(|e| A4Error::Io(e.into()))?;
+        let name = entry.file_name().to_string_lossy();
+        let Some(day) = name
+            .strip_suffix("--paste-zone.md")
+            .and_then(|day| UtcDay::parse(day).ok())
+        else {
+            continue;
+        };
+
+        let note = read_note(entry.path())?;
+        let blocks = parse_blocks(&note.body);
+        let moved = moved_tokens(&blocks);
+
+        items.extend(
+            blocks
+                .into_iter()
+                .filter(|block| block.anchor.prefix != TRIAGE_PREFIX)
+                .filter(|block| !moved.contains(&block.anchor.to_marker()))
+                .map(|block| InboxItem {
+                    day,
+                    path: entry.path().to_path_buf(),
+                    block,
+                }),
+        );
+    }
+
+    Ok(items)
+}
+
+/// Move an inbox block into `target` as a new anchored block under `heading`,
+/// then append a `^triage-HHMM` reference to the paste zone. The inbox block
+/// itself is left untouched. Returns the anchor written in `target`.
+pub fn triage(
+    vault: &Vault,
+    day: UtcDay,
+    token: &str,
+    target: &Path,
+    heading: &str,
+    hhmm: &str,
+) -> Result<AnchorToken, A4Error> {
+    let inbox_path = vault.inbox_day_path(day);
+    let inbox_body = existing_body(vault, &inbox_path)?;
+    let block = find_block(&inbox_body, token).ok_or_else(|| A4Error::BlockNotFound {
+        path: inbox_path.clone(),
+        anchor: token.trim_start_matches('^').to_string(),
+    })?;
+
+    let marker = block.anchor.to_marker();
+    let source_link = link_for(vault, &inbox_path);
+
+    let mut content = block.content.clone();
+    if !content.is_empty() {
+        content.push_str("\n\n");
+    }
+    content.push_str(&format!("(from [[{source_link}#{marker}]])"));
+
+    let moved_anchor = append_block_suffixed(
+        vault,
+        target,
+        AppendOptions {
+            heading,
+            anchor: block.anchor.clone(),
+            content: &content,
+        },
+    )?;
+
+    let reference = format!(
+        "moved {marker} → [[{}#{}]]",
+        link_for(vault, target),
+        moved_anchor.to_marker()
+    );
+
+    append_block_suffixed(
+        vault,
+        &inbox_path,
+        AppendOptions {
+            heading: TRIAGE_HEADING,
+            anchor: AnchorToken::parse(&format!("{TRIAGE_PREFIX}-{hhmm}"))?,
+            content: &reference,
+        },
+    )?;
+
+    Ok(moved_anchor)
+}
+
+fn moved_tokens(blocks: &[Block]) -> HashSet<String> {
+    let re = MOVED_REGEX.get_or_init(|| Regex::new(r"(?m)^moved (\^\S+) →").unwrap());
+    blocks
+        .iter()
+        .filter(|block| block.anchor.prefix == TRIAGE_PREFIX)
+        .flat_map(|block| {
+            re.captures_iter(&block.content)
+                .map(|caps| caps[1].to_string())
+        })
+        .collect()
+}
+
+/// The body of `path`, decrypted, or empty if it does not exist yet.
+fn existing_body(vault: &Vault, path: &Path) -> Result<String, A4Error> {
+    if !path.exists() {
+        return Ok(String::new());
+    }
+    sparse::ensure_checked_out(vault, path)?;
+    let raw = crypt::reveal(vault, path, fs::read(path)?)?;
+    Ok(split_front_matter(&String::from_utf8_lossy(&raw))
+        .1
+        .to_string())
+}
+
+fn link_for(vault: &Vault, path: &Path) -> String {
+    vault
+        .note_link(path)
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault::VaultOpts;
+    use tempfile::TempDir;
+
+    fn day() -> UtcDay {
+        UtcDay::parse("2025-09-14").unwrap()
+    }
+
+    #[test]
+    fn test_drop_block_creates_paste_zone() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+
+        let anchor = AnchorToken::parse("inbox-0930").unwrap();
+        drop_block(&vault, day(), anchor.clone(), "first").unwrap();
+        let second = drop_block(&vault, day(), anchor, "second").unwrap();
+        assert_eq!(second.to_marker(), "^inbox-0930__2");
+
+        let path = vault.inbox_day_path(day());
+        assert!(path.ends_with("inbox/2025/2025-09/2025-09-14--paste-zone.md"));
+        let content = std::fs::read_to_string(path).unwrap();
+        assert_eq!(
+            content,
+            "\n\n## Inbox\n\n^inbox-0930\n\nfirst\n\n^inbox-0930__2\n\nsecond\n"
+        );
+    }
+
+    #[test]
+    fn test_triage_moves_block_and_leaves_reference() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+
+        let anchor = AnchorToken::parse("inbox-0930").unwrap();
+        drop_block(&vault, day(), anchor, "- call dentist").unwrap();
+        let anchor = AnchorToken::parse("inbox-1000").unwrap();
+        drop_block(&vault, day(), anchor, "- buy milk").unwrap();
+        assert_eq!(pending_items(&vault).unwrap().len(), 2);
+
+        let target = vault.capture_day_path(day());
+        let moved = triage(&vault, day(), "inbox-0930", &target, "Tasks", "1100").unwrap();
+        assert_eq!(moved.to_marker(), "^inbox-0930");
+
+        let daily = std::fs::read_to_string(&target).unwrap();
+        assert_eq!(
+            daily,
+            "\n\n## Tasks\n\n^inbox-0930\n\n- call dentist\n\n\
+             (from [[inbox/2025/2025-09/2025-09-14--paste-zone#^inbox-0930]])\n"
+        );
+
+        let inbox = std::fs::read_to_string(vault.inbox_day_path(day())).unwrap();
+        assert!(inbox.contains("^inbox-0930\n\n- call dentist\n"));
+        assert!(inbox.ends_with(
+            "## Triage\n\n^triage-1100\n\n\
+             moved ^inbox-0930 → [[capture/2025/2025-09/2025-09-14#^inbox-0930]]\n"
+        ));
+
+        let pending = pending_items(&vault).unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].block.anchor.to_marker(), "^inbox-1000");
+    }
+
+    #[test]
+    fn test_triage_unknown_block() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+        let target = vault.capture_day_path(day());
+
+        let result = triage(&vault, day(), "inbox-0930", &target, "Tasks", "1100");
+        assert!(matches!(result, Err(A4Error::BlockNotFound { .. })));
+    }
+}
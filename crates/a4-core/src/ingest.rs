@@ -0,0 +1,210 @@
+use crate::error::A4Error;
+use crate::notes::write_note;
+use crate::slug::validate_slug;
+use crate::vault::Vault;
+use std::path::PathBuf;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SourceKind {
+    Article,
+    Transcript,
+}
+
+impl SourceKind {
+    /// Directory under `sources/`.
+    pub fn dir(&self) -> &'static str {
+        match self {
+            SourceKind::Article => "articles",
+            SourceKind::Transcript => "transcripts",
+        }
+    }
+
+    /// Front matter `kind` value.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SourceKind::Article => "source.article",
+            SourceKind::Transcript => "source.transcript",
+        }
+    }
+}
+
+/// Everything needed to render a source note.
+pub struct SourceNote<'a> {
+    pub kind: SourceKind,
+    pub slug: &'a str,
+    pub title: &'a str,
+    pub url: Option<&'a str>,
+    pub via: &'a str,
+    /// ISO 8601 creation timestamp.
+    pub created: &'a str,
+    /// Highlights pasted by the user, rendered under `## Highlights`.
+    pub highlights: Option<&'a str>,
+    /// Converted document text, rendered under `## Content`.
+    pub content: Option<&'a str>,
+    /// Wikilink target of the daily block that references this source.
+    pub referenced_in: Option<&'a str>,
+}
+
+/// `sources/<articles|transcripts>/<slug>.md`
+pub fn source_path(vault: &Vault, kind: SourceKind, slug: &str) -> PathBuf {
+    vault
+        .root()
+        .join("sources")
+        .join(kind.dir())
+        .join(format!("{slug}.md"))
+}
+
+/// Vault-relative wikilink target for a source (`sources/articles/<slug>`).
+pub fn source_link(kind: SourceKind, slug: &str) -> String {
+    format!("sources/{}/{slug}", kind.dir())
+}
+
+/// Write a new source note. Existing sources are never overwritten.
+pub fn write_source(vault: &Vault, note: &SourceNote) -> Result<PathBuf, A4Error> {
+    validate_slug(note.slug)?;
+
+    let path = source_path(vault, note.kind, note.slug);
+    if path.exists() {
+        return Err(A4Error::SourceExists { path });
+    }
+
+    write_note(&path, &render_source(note))?;
+    Ok(path)
+}
+
+pub fn render_source(note: &SourceNote) -> String {
+    let mut out = String::new();
+    out.push_str("---\n");
+    out.push_str(&format!("kind: {}\n", note.kind.kind()));
+    out.push_str(&format!("created: {}\n", note.created));
+    out.push_str(&format!("title: {}\n", yaml_quote(note.title)));
+    out.push_str(&format!(
+        "origin: {{ url: {}, via: {} }}\n",
+        yaml_quote(note.url.unwrap_or("")),
+        yaml_quote(note.via)
+    ));
+    out.push_str("tags: []\n");
+    out.push_str("---\n\n");
+    out.push_str(&format!("# {}\n\n", note.title));
+
+    out.push_str("## Highlights\n\n");
+    out.push_str(&section_body(note.highlights));
+
+    out.push_str("## Notes\n\n");
+    out.push_str("- …\n\n");
+
+    if let Some(content) = note.content {
+        out.push_str("## Content\n\n");
+        out.push_str(&section_body(Some(content)));
+    }
+
+    out.push_str("## Links\n\n");
+    match note.referenced_in {
+        Some(link) => out.push_str(&format!("- Referenced in [[{link}]]\n")),
+        None => out.push_str("- …\n"),
+    }
+
+    out
+}
+
+fn section_body(text: Option<&str>) -> String {
+    match text.map(str::trim).filter(|t| !t.is_empty()) {
+        Some(text) => format!("{text}\n\n"),
+        None => "- …\n\n".to_string(),
+    }
+}
+
+fn yaml_quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault::VaultOpts;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_render_source_note() {
+        let note = SourceNote {
+            kind: SourceKind::Article,
+            slug: "state-of-gpt",
+            title: "State of \"GPT\"",
+            url: Some("https://example.com/gpt"),
+            via: "a4 ingest",
+            created: "2025-09-14T10:45:00Z",
+            highlights: Some("- Models are trained in stages\n"),
+            content: None,
+            referenced_in: Some("capture/2025/2025-09/2025-09-14#^read-1102"),
+        };
+
+        assert_eq!(
+            render_source(&note),
+            "---\n\
+             kind: source.article\n\
+             created: 2025-09-14T10:45:00Z\n\
+             title: \"State of \\\"GPT\\\"\"\n\
+             origin: { url: \"https://example.com/gpt\", via: \"a4 ingest\" }\n\
+             tags: []\n\
+             ---\n\n\
+             # State of \"GPT\"\n\n\
+             ## Highlights\n\n\
+             - Models are trained in stages\n\n\
+             ## Notes\n\n\
+             - …\n\n\
+             ## Links\n\n\
+             - Referenced in [[capture/2025/2025-09/2025-09-14#^read-1102]]\n"
+        );
+    }
+
+    #[test]
+    fn test_front_matter_is_valid_yaml() {
+        let note = SourceNote {
+            kind: SourceKind::Transcript,
+            slug: "talk",
+            title: "A: tricky \\ title",
+            url: None,
+            via: "a4 ingest",
+            created: "2025-09-14T10:45:00Z",
+            highlights: None,
+            content: Some("Transcript text"),
+            referenced_in: None,
+        };
+
+        let rendered = render_source(&note);
+        let (fm, _) = crate::notes::split_front_matter(&rendered);
+        let fm = fm
+            .unwrap()
+            .trim_start_matches("---\n")
+            .trim_end_matches("---");
+        let value: serde_yaml::Value = serde_yaml::from_str(fm).unwrap();
+        assert_eq!(value["kind"].as_str(), Some("source.transcript"));
+        assert_eq!(value["title"].as_str(), Some("A: tricky \\ title"));
+        assert_eq!(value["origin"]["url"].as_str(), Some(""));
+        assert!(rendered.contains("## Content\n\nTranscript text\n\n"));
+    }
+
+    #[test]
+    fn test_write_source_never_overwrites() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+        let note = SourceNote {
+            kind: SourceKind::Article,
+            slug: "state-of-gpt",
+            title: "State of GPT",
+            url: None,
+            via: "a4 ingest",
+            created: "2025-09-14T10:45:00Z",
+            highlights: None,
+            content: None,
+            referenced_in: None,
+        };
+
+        let path = write_source(&vault, &note).unwrap();
+        assert!(path.ends_with("sources/articles/state-of-gpt.md"));
+        assert!(matches!(
+            write_source(&vault, &note),
+            Err(A4Error::SourceExists { .. })
+        ));
+    }
+}
@@ -1,16 +1,48 @@
 pub mod anchors;
 pub mod append;
+pub mod assets;
+pub mod blocks;
+pub mod calendar;
+pub mod clone;
+pub mod commit_message;
+pub mod config;
+pub mod crypt;
 pub mod date;
+pub mod diff;
 pub mod error;
 pub mod git_backend;
 pub mod headings;
+pub mod html;
+pub mod inbox;
+pub mod ingest;
 pub mod notes;
+pub mod obsidian;
+pub mod projects;
+pub mod publish;
+pub mod rollup;
+pub mod search;
+pub mod secrets;
+pub mod site;
+pub mod slug;
+pub mod sparse;
+pub mod stats;
+pub mod status;
+pub mod stitch;
+pub mod sync;
+pub mod tasks;
+pub mod template;
 pub mod util;
 pub mod vault;
+pub mod verify;
+pub mod watch;
 
 pub use anchors::AnchorToken;
-pub use append::{append_block, AppendOptions};
-pub use date::{IsoWeek, LocalClock, UtcDay};
+pub use append::{
+    append_block, append_block_suffixed, append_block_verbatim, append_block_with, AppendOptions,
+};
+pub use blocks::Block;
+pub use config::Config;
+pub use date::{Clock, IsoWeek, LocalClock, UtcDay, UtcMonth};
 pub use error::A4Error;
 pub use git_backend::{GitBackend, RebaseResult};
-pub use vault::{Vault, VaultOpts, VaultRoot};
+pub use vault::{Created, Vault, VaultOpts, VaultRoot};
@@ -1,5 +1,9 @@
 use crate::error::A4Error;
+use crate::vault::Vault;
+use fs4::fs_err3::FileExt;
 use fs_err as fs;
+use sha2::{Digest, Sha256};
+use std::io::Write;
 use std::path::{Path, PathBuf};
 
 pub struct Note {
@@ -19,6 +23,10 @@ pub fn read_note(path: &Path) -> Result<Note, A4Error> {
     })
 }
 
+/// Replace `path` with `body`. The new content goes to a temporary sibling
+/// that is synced and renamed over the note, so a crash leaves either the
+/// old note or the new one. Callers that read the note first should hold
+/// its [`lock_note`].
 pub fn write_note(path: &Path, body: &str) -> Result<(), A4Error> {
     let parent = path.parent().ok_or_else(|| A4Error::InvalidVaultPath {
         path: path.to_path_buf(),
@@ -26,13 +34,191 @@ pub fn write_note(path: &Path, body: &str) -> Result<(), A4Error> {
 
     fs::create_dir_all(parent)?;
 
-    let temp_path = path.with_extension("tmp");
-    fs::write(&temp_path, body)?;
-    fs::rename(&temp_path, path)?;
+    let temp_path = temp_path(path);
+    let written = write_synced(&temp_path, body).and_then(|()| {
+        fs::rename(&temp_path, path)?;
+        Ok(())
+    });
+    if written.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    written?;
+    sync_dir(parent)
+}
+
+/// An exclusive advisory lock on one note, released when dropped.
+pub struct NoteLock {
+    _file: fs::File,
+}
+
+/// Number of lock files under `.a4/cache/locks/`. Notes share them by path
+/// hash, so the directory stays the same size however many notes are written;
+/// two notes in one slot merely wait for each other.
+const LOCK_SLOTS: u8 = 64;
+
+/// Wait for and take the lock on `path`, so concurrent appends from other
+/// processes (a launcher, the watcher, the server) cannot read the same old
+/// content and overwrite each other's blocks. The lock lives under
+/// `.a4/cache/locks/`, since the note itself is replaced on write.
+pub fn lock_note(vault: &Vault, path: &Path) -> Result<NoteLock, A4Error> {
+    let dir = vault.private_file_path("cache").join("locks");
+    if !dir.exists() {
+        vault.ignore_private("cache")?;
+        fs::create_dir_all(&dir)?;
+    }
+
+    // Every spelling of the path (relative, through a symlink) takes the
+    // same lock.
+    let digest = Sha256::digest(canonical(path).to_string_lossy().as_bytes());
+    let slot = digest[0] % LOCK_SLOTS;
 
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(dir.join(format!("{slot:02}.lock")))?;
+    file.lock()?;
+    Ok(NoteLock { _file: file })
+}
+
+/// `path` with its deepest existing ancestor canonicalized, since a note
+/// being created does not exist yet.
+fn canonical(path: &Path) -> PathBuf {
+    let mut missing = Vec::new();
+    let mut existing = path;
+    loop {
+        if let Ok(resolved) = existing.canonicalize() {
+            return missing
+                .iter()
+                .rev()
+                .fold(resolved, |dir, name| dir.join(name));
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                missing.push(name);
+                existing = parent;
+            }
+            _ => return path.to_path_buf(),
+        }
+    }
+}
+
+fn write_synced(path: &Path, body: &str) -> Result<(), A4Error> {
+    let mut file = fs::File::create(path)?;
+    file.write_all(body.as_bytes())?;
+    file.sync_all()?;
+    Ok(())
+}
+
+/// Persist a rename or link into `dir`. Directories cannot be opened for
+/// syncing on Windows, where the rename is durable on its own.
+fn sync_dir(dir: &Path) -> Result<(), A4Error> {
+    #[cfg(unix)]
+    fs::File::open(dir)?.sync_all()?;
+    #[cfg(not(unix))]
+    let _ = dir;
     Ok(())
 }
 
+/// Create `path` with `body` unless it already exists, returning whether it
+/// was created. The note appears complete or not at all, and when several
+/// processes race only one of them creates it.
+pub fn create_note(path: &Path, body: &str) -> Result<bool, A4Error> {
+    let parent = path.parent().ok_or_else(|| A4Error::InvalidVaultPath {
+        path: path.to_path_buf(),
+    })?;
+
+    fs::create_dir_all(parent)?;
+    if path.exists() {
+        return Ok(false);
+    }
+
+    let temp_path = temp_path(path);
+    // A hard link never replaces an existing file, unlike rename.
+    let linked = write_synced(&temp_path, body)
+        .and_then(|()| std::fs::hard_link(&temp_path, path).map_err(A4Error::from));
+    let _ = fs::remove_file(&temp_path);
+    match linked {
+        Ok(()) => {
+            sync_dir(parent)?;
+            Ok(true)
+        }
+        Err(A4Error::Io(e)) if e.kind() == std::io::ErrorKind::AlreadyExists => Ok(false),
+        // FAT, exFAT and some network file systems have no hard links.
+        Err(A4Error::Io(e))
+            if matches!(
+                e.kind(),
+                std::io::ErrorKind::Unsupported | std::io::ErrorKind::PermissionDenied
+            ) =>
+        {
+            create_in_place(path, body)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// [`create_note`] without hard links: still only one racing writer creates
+/// the note, but readers may briefly see it partly written.
+fn create_in_place(path: &Path, body: &str) -> Result<bool, A4Error> {
+    let mut file = match fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+    {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => return Ok(false),
+        Err(e) => return Err(e.into()),
+    };
+    if let Err(e) = file
+        .write_all(body.as_bytes())
+        .and_then(|()| file.sync_all())
+    {
+        let _ = fs::remove_file(path);
+        return Err(e.into());
+    }
+    if let Some(parent) = path.parent() {
+        sync_dir(parent)?;
+    }
+    Ok(true)
+}
+
+/// A sibling of `path` no other writer uses: `.<name>.<pid>-<random>.tmp`.
+pub(crate) fn temp_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(
+        ".{name}.{}-{:016x}.tmp",
+        std::process::id(),
+        rand::random::<u64>()
+    ))
+}
+
+/// Git ignore pattern matching every [`temp_path`], so one a crash left
+/// behind is never committed.
+pub(crate) const TEMP_PATH_PATTERN: &str = ".*.*-*.tmp";
+
+/// Whether `path` is a [`temp_path`] of some note.
+pub(crate) fn is_temp_path(path: &Path) -> bool {
+    let Some(name) = path.file_name().map(|name| name.to_string_lossy()) else {
+        return false;
+    };
+    let Some((_, unique)) = name
+        .strip_prefix('.')
+        .and_then(|name| name.strip_suffix(".tmp"))
+        .and_then(|name| name.rsplit_once('.'))
+    else {
+        return false;
+    };
+    unique.split_once('-').is_some_and(|(pid, random)| {
+        !pid.is_empty()
+            && pid.bytes().all(|b| b.is_ascii_digit())
+            && random.len() == 16
+            && random.bytes().all(|b| b.is_ascii_hexdigit())
+    })
+}
+
 pub fn split_front_matter(raw: &str) -> (Option<&str>, &str) {
     if !raw.starts_with("---\n") && !raw.starts_with("---\r\n") {
         return (None, raw);
@@ -84,6 +270,63 @@ pub fn join_front_matter(fm: Option<&str>, body: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_create_note_keeps_existing() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("capture/2025/2025-09/2025-09-14.md");
+
+        assert!(create_note(&path, "first\n").unwrap());
+        assert!(!create_note(&path, "second\n").unwrap());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "first\n");
+        assert_eq!(
+            std::fs::read_dir(path.parent().unwrap()).unwrap().count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_create_in_place_keeps_existing() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("2025-09-14.md");
+
+        assert!(create_in_place(&path, "first\n").unwrap());
+        assert!(!create_in_place(&path, "second\n").unwrap());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "first\n");
+    }
+
+    #[test]
+    fn test_canonical_lock_key() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join("capture");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let canonical_dir = dir.canonicalize().unwrap();
+        assert_eq!(
+            canonical(&dir.join("../capture/new/note.md")),
+            canonical_dir.join("new/note.md")
+        );
+        #[cfg(unix)]
+        {
+            let link = temp_dir.path().join("link");
+            std::os::unix::fs::symlink(&dir, &link).unwrap();
+            assert_eq!(
+                canonical(&link.join("note.md")),
+                canonical_dir.join("note.md")
+            );
+        }
+    }
+
+    #[test]
+    fn test_is_temp_path() {
+        let note = Path::new("/vault/capture/2025/2025-09/2025-09-14.md");
+        assert!(is_temp_path(&temp_path(note)));
+        assert!(is_temp_path(Path::new(".log.md.42-00000000deadbeef.tmp")));
+        assert!(!is_temp_path(note));
+        assert!(!is_temp_path(Path::new(".draft.tmp")));
+        assert!(!is_temp_path(Path::new(".log.md.42-beef.tmp")));
+    }
 
     #[test]
     fn test_split_no_front_matter() {
@@ -0,0 +1,539 @@
+//! Interop with Obsidian vaults: the Daily Notes and Templates core plugin
+//! settings under `.obsidian/`, their moment.js date formats and template
+//! placeholders, and Obsidian's block-ID grammar.
+
+use crate::blocks::parse_marker_line;
+use crate::config::Config;
+use crate::date::{format_moment, parse_moment, UtcDay};
+use crate::error::A4Error;
+use crate::vault::Vault;
+use fs_err as fs;
+use regex::Regex;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use time::{PrimitiveDateTime, Time};
+
+/// `.obsidian/daily-notes.json`. Obsidian only writes the keys a user changed.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct DailyNotesSettings {
+    /// Folder for new daily notes, relative to the vault root.
+    pub folder: Option<String>,
+    /// moment.js format of the note name; may contain `/` for subfolders.
+    pub format: Option<String>,
+    /// Template note, relative to the vault root, usually without `.md`.
+    pub template: Option<String>,
+}
+
+/// `.obsidian/templates.json`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TemplatesSettings {
+    pub folder: Option<String>,
+    /// Format of a bare `{{date}}`.
+    pub date_format: Option<String>,
+    /// Format of a bare `{{time}}`.
+    pub time_format: Option<String>,
+}
+
+/// Plugin settings found under `.obsidian/`; `None` where the file is absent.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ObsidianSettings {
+    pub daily_notes: Option<DailyNotesSettings>,
+    pub templates: Option<TemplatesSettings>,
+}
+
+const DEFAULT_DATE_FORMAT: &str = "YYYY-MM-DD";
+const DEFAULT_TIME_FORMAT: &str = "HH:mm";
+
+impl ObsidianSettings {
+    /// Read the Daily Notes and Templates settings of the vault.
+    pub fn detect(vault: &Vault) -> Result<Self, A4Error> {
+        let dir = vault.root().join(".obsidian");
+        Ok(ObsidianSettings {
+            daily_notes: read_json(&dir.join("daily-notes.json"))?,
+            templates: read_json(&dir.join("templates.json"))?,
+        })
+    }
+
+    /// Moment format of daily note names, `YYYY-MM-DD` unless configured.
+    pub fn daily_format(&self) -> &str {
+        self.daily_notes
+            .as_ref()
+            .and_then(|d| non_empty(&d.format))
+            .unwrap_or(DEFAULT_DATE_FORMAT)
+    }
+
+    fn date_format(&self) -> &str {
+        self.templates
+            .as_ref()
+            .and_then(|t| non_empty(&t.date_format))
+            .unwrap_or(DEFAULT_DATE_FORMAT)
+    }
+
+    fn time_format(&self) -> &str {
+        self.templates
+            .as_ref()
+            .and_then(|t| non_empty(&t.time_format))
+            .unwrap_or(DEFAULT_TIME_FORMAT)
+    }
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|s| !s.is_empty())
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<Option<T>, A4Error> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let raw = fs::read_to_string(path)?;
+    serde_json::from_str(&raw)
+        .map(Some)
+        .map_err(|e| A4Error::InvalidObsidianSettings {
+            path: path.to_path_buf(),
+            reason: e.to_string(),
+        })
+}
+
+/// Where a day's note lives and the template it starts from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DailyNote {
+    pub path: PathBuf,
+    pub template: PathBuf,
+    /// The template is an Obsidian one whose placeholders need converting.
+    pub obsidian_template: bool,
+}
+
+/// The daily note for `day`: `capture/YYYY/YYYY-MM/YYYY-MM-DD.md` from
+/// `routines/templates/daily.md`, or, with `[obsidian] daily_notes = true`,
+/// wherever Obsidian's Daily Notes plugin would put it (its defaults apply
+/// when `.obsidian/daily-notes.json` is absent). The day is still the UTC day.
+pub fn daily_note(vault: &Vault, config: &Config, day: UtcDay) -> Result<DailyNote, A4Error> {
+    let default = DailyNote {
+        path: vault.capture_day_path(day),
+        template: vault.template_path(),
+        obsidian_template: false,
+    };
+    if !config.obsidian.daily_notes {
+        return Ok(default);
+    }
+
+    let settings = ObsidianSettings::detect(vault)?;
+    let daily = settings.daily_notes.clone().unwrap_or_default();
+
+    let midnight = PrimitiveDateTime::new(day.to_date(), Time::MIDNIGHT);
+    let mut relative = non_empty(&daily.folder)
+        .map(|folder| format!("{}/", folder.trim_matches('/')))
+        .unwrap_or_default();
+    relative.push_str(&format_moment(settings.daily_format(), midnight));
+    relative.push_str(".md");
+    let path = vault.resolve_relative(&relative)?;
+
+    Ok(match non_empty(&daily.template) {
+        Some(template) => {
+            let template = if template.ends_with(".md") {
+                template.to_string()
+            } else {
+                format!("{template}.md")
+            };
+            DailyNote {
+                path,
+                template: vault.resolve_relative(&template)?,
+                obsidian_template: true,
+            }
+        }
+        None => DailyNote { path, ..default },
+    })
+}
+
+/// Every daily note and its day, oldest first: the notes [`daily_note`] would
+/// name for some day. With `[obsidian] daily_notes = true` these are the
+/// notes in the Daily Notes folder whose names read back in its format.
+pub fn daily_notes(vault: &Vault, config: &Config) -> Result<Vec<(UtcDay, PathBuf)>, A4Error> {
+    if !config.obsidian.daily_notes {
+        return vault.capture_days();
+    }
+
+    let settings = ObsidianSettings::detect(vault)?;
+    let daily = settings.daily_notes.clone().unwrap_or_default();
+    let folder = match non_empty(&daily.folder) {
+        Some(folder) => vault.resolve_relative(folder.trim_matches('/'))?,
+        None => vault.root().to_path_buf(),
+    };
+
+    let mut days = Vec::new();
+    for path in vault.note_paths()? {
+        let Ok(relative) = path.strip_prefix(&folder) else {
+            continue;
+        };
+        let name = relative
+            .with_extension("")
+            .to_string_lossy()
+            .replace('\\', "/");
+        if let Some(date) = parse_moment(settings.daily_format(), &name) {
+            days.push((UtcDay::from_date(date), path));
+        }
+    }
+
+    days.sort();
+    Ok(days)
+}
+
+/// An Obsidian template rewritten for A4, with the tags that have no A4
+/// equivalent left in place.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Converted {
+    pub text: String,
+    pub unmapped: Vec<String>,
+}
+
+static CORE_PLACEHOLDER: OnceLock<Regex> = OnceLock::new();
+static TEMPLATER_TAG: OnceLock<Regex> = OnceLock::new();
+static TEMPLATER_DATE: OnceLock<Regex> = OnceLock::new();
+
+/// Map Templates-plugin `{{date}}`, `{{time}}`, `{{title}}` (with optional
+/// `:FORMAT`) and Templater `tp.date.now/yesterday/tomorrow(...)` and
+/// `tp.file.title` tags to A4 template variables. Bare `{{date}}`/`{{time}}`
+/// use the formats from `templates.json`; a daily note's title is its name
+/// in the daily format. Other Templater tags are left for the user.
+pub fn convert_template(text: &str, settings: &ObsidianSettings) -> Converted {
+    let core = CORE_PLACEHOLDER
+        .get_or_init(|| Regex::new(r"\{\{\s*(date|time|title)\s*(?::([^}]*))?\}\}").unwrap());
+    let templater = TEMPLATER_TAG.get_or_init(|| Regex::new(r"(?s)<%[-_*]?(.*?)[-_]?%>").unwrap());
+    let date = TEMPLATER_DATE.get_or_init(|| {
+        Regex::new(
+            r#"^tp\.date\.(now|yesterday|tomorrow)\(\s*(?:"([^"]*)"|'([^']*)')?\s*(?:,\s*(-?\d+)\s*)?\)$"#,
+        )
+        .unwrap()
+    });
+
+    let title_format = settings
+        .daily_format()
+        .rsplit('/')
+        .next()
+        .unwrap_or_default();
+    let mut unmapped = Vec::new();
+
+    let text = core.replace_all(text, |caps: &regex::Captures| {
+        let format = match (&caps[1], caps.get(2).map(|m| m.as_str().trim())) {
+            ("title", _) => title_format,
+            (_, Some(format)) if !format.is_empty() => format,
+            ("date", _) => settings.date_format(),
+            _ => settings.time_format(),
+        };
+        a4_variable(format, 0).unwrap_or_else(|| {
+            unmapped.push(caps[0].to_string());
+            caps[0].to_string()
+        })
+    });
+
+    let text = templater.replace_all(&text, |caps: &regex::Captures| {
+        let expr = caps[1].trim();
+        let variable = match date.captures(expr) {
+            Some(date) => {
+                let format = date
+                    .get(2)
+                    .or_else(|| date.get(3))
+                    .map_or(DEFAULT_DATE_FORMAT, |m| m.as_str());
+                let days = match &date[1] {
+                    "yesterday" => Some(-1),
+                    "tomorrow" => Some(1),
+                    _ => date.get(4).map_or(Some(0), |m| m.as_str().parse().ok()),
+                };
+                days.and_then(|days| a4_variable(format, days))
+            }
+            None if expr == "tp.file.title" => a4_variable(title_format, 0),
+            None => None,
+        };
+        variable.unwrap_or_else(|| {
+            unmapped.push(caps[0].to_string());
+            caps[0].to_string()
+        })
+    });
+
+    Converted {
+        text: text.into_owned(),
+        unmapped,
+    }
+}
+
+/// The A4 template variable rendering a moment format `days` from the
+/// note's day. Obsidian formats with time tokens show the current time.
+fn a4_variable(format: &str, days: i64) -> Option<String> {
+    if format.contains('}') {
+        return None;
+    }
+    let offset = match days {
+        0 => String::new(),
+        days => format!(" {days:+}d"),
+    };
+    Some(match format {
+        "YYYY-MM-DD" => format!("{{{{date{offset}}}}}"),
+        "HHmm" if days == 0 => "{{hhmm}}".to_string(),
+        _ if has_time_tokens(format) => {
+            if days != 0 {
+                return None;
+            }
+            format!("{{{{time:{format}}}}}")
+        }
+        _ => format!("{{{{date:{format}{offset}}}}}"),
+    })
+}
+
+fn has_time_tokens(format: &str) -> bool {
+    let mut literal = false;
+    format.chars().any(|c| match c {
+        '[' => {
+            literal = true;
+            false
+        }
+        ']' => {
+            literal = false;
+            false
+        }
+        'H' | 'h' | 'm' | 's' | 'A' | 'a' => !literal,
+        _ => false,
+    })
+}
+
+static BLOCK_ID: OnceLock<Regex> = OnceLock::new();
+
+/// Obsidian block IDs may only contain Latin letters, digits and `-`, so
+/// A4 anchors with a `__suffix` cannot be linked to from Obsidian.
+pub fn is_block_id(id: &str) -> bool {
+    BLOCK_ID
+        .get_or_init(|| Regex::new(r"^[A-Za-z0-9-]+$").unwrap())
+        .is_match(id.strip_prefix('^').unwrap_or(id))
+}
+
+/// An A4 anchor marker Obsidian will not treat as a block ID.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidBlockId {
+    pub path: PathBuf,
+    /// 1-based line number of the marker line.
+    pub line: usize,
+    pub marker: String,
+}
+
+/// Every anchor marker in the vault that is not an Obsidian block ID.
+pub fn invalid_block_ids(vault: &Vault) -> Result<Vec<InvalidBlockId>, A4Error> {
+    let mut invalid = Vec::new();
+    for path in vault.note_paths()? {
+        let Ok(body) = fs::read_to_string(&path) else {
+            continue;
+        };
+        for (index, line) in body.lines().enumerate() {
+            let Some(token) = parse_marker_line(line) else {
+                continue;
+            };
+            let marker = token.to_marker();
+            if !is_block_id(&marker) {
+                invalid.push(InvalidBlockId {
+                    path: path.clone(),
+                    line: index + 1,
+                    marker,
+                });
+            }
+        }
+    }
+    Ok(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::anchors::AnchorToken;
+    use crate::blocks::next_free_anchor;
+    use crate::vault::VaultOpts;
+    use tempfile::TempDir;
+
+    fn vault_with(files: &[(&str, &str)]) -> (TempDir, Vault) {
+        let temp_dir = TempDir::new().unwrap();
+        for (path, content) in files {
+            let path = temp_dir.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+        (temp_dir, vault)
+    }
+
+    fn obsidian_config() -> Config {
+        toml::from_str("[obsidian]\ndaily_notes = true\n").unwrap()
+    }
+
+    #[test]
+    fn test_default_daily_note() {
+        let (_temp_dir, vault) = vault_with(&[]);
+        let day = UtcDay::parse("2025-09-14").unwrap();
+
+        let note = daily_note(&vault, &Config::default(), day).unwrap();
+        assert_eq!(note.path, vault.capture_day_path(day));
+        assert_eq!(note.template, vault.template_path());
+        assert!(!note.obsidian_template);
+    }
+
+    #[test]
+    fn test_obsidian_daily_note() {
+        let (_temp_dir, vault) = vault_with(&[(
+            ".obsidian/daily-notes.json",
+            r#"{"folder":"Journal/","format":"YYYY/MM-MMM/YYYY-MM-DD ddd","template":"Templates/Daily","autorun":false}"#,
+        )]);
+        let day = UtcDay::parse("2025-09-14").unwrap();
+
+        let note = daily_note(&vault, &obsidian_config(), day).unwrap();
+        assert_eq!(
+            note.path,
+            vault.root().join("Journal/2025/09-Sep/2025-09-14 Sun.md")
+        );
+        assert_eq!(note.template, vault.root().join("Templates/Daily.md"));
+        assert!(note.obsidian_template);
+    }
+
+    #[test]
+    fn test_obsidian_defaults_without_settings() {
+        let (_temp_dir, vault) = vault_with(&[]);
+        let day = UtcDay::parse("2025-09-14").unwrap();
+
+        let note = daily_note(&vault, &obsidian_config(), day).unwrap();
+        assert_eq!(note.path, vault.root().join("2025-09-14.md"));
+        assert_eq!(note.template, vault.template_path());
+        assert!(!note.obsidian_template);
+    }
+
+    #[test]
+    fn test_obsidian_daily_notes() {
+        let (_temp_dir, vault) = vault_with(&[
+            (
+                ".obsidian/daily-notes.json",
+                r#"{"folder":"Journal","format":"YYYY/MM-MMM/YYYY-MM-DD ddd"}"#,
+            ),
+            ("Journal/2025/09-Sep/2025-09-15 Mon.md", "b\n"),
+            ("Journal/2025/09-Sep/2025-09-14 Sun.md", "a\n"),
+            ("Journal/2025/09-Sep/2025-09-14 Mon.md", "wrong weekday\n"),
+            ("Journal/2025/09-Sep/ideas.md", "not a day\n"),
+            ("capture/2025/2025-09/2025-09-13.md", "elsewhere\n"),
+        ]);
+
+        let days: Vec<_> = daily_notes(&vault, &obsidian_config())
+            .unwrap()
+            .into_iter()
+            .map(|(day, path)| (day.to_string(), path))
+            .collect();
+        assert_eq!(
+            days,
+            [
+                (
+                    "2025-09-14".to_string(),
+                    vault.root().join("Journal/2025/09-Sep/2025-09-14 Sun.md")
+                ),
+                (
+                    "2025-09-15".to_string(),
+                    vault.root().join("Journal/2025/09-Sep/2025-09-15 Mon.md")
+                ),
+            ]
+        );
+
+        let days = daily_notes(&vault, &Config::default()).unwrap();
+        assert_eq!(days.len(), 1);
+        assert_eq!(days[0].0, UtcDay::parse("2025-09-13").unwrap());
+    }
+
+    #[test]
+    fn test_daily_note_rejects_traversal() {
+        let (_temp_dir, vault) =
+            vault_with(&[(".obsidian/daily-notes.json", r#"{"folder":"../out"}"#)]);
+        let day = UtcDay::parse("2025-09-14").unwrap();
+
+        assert!(matches!(
+            daily_note(&vault, &obsidian_config(), day),
+            Err(A4Error::PathTraversal { .. })
+        ));
+    }
+
+    #[test]
+    fn test_invalid_settings() {
+        let (_temp_dir, vault) = vault_with(&[(".obsidian/templates.json", "{folder:")]);
+        assert!(matches!(
+            ObsidianSettings::detect(&vault),
+            Err(A4Error::InvalidObsidianSettings { .. })
+        ));
+    }
+
+    #[test]
+    fn test_convert_template() {
+        let settings = ObsidianSettings::default();
+        let converted = convert_template(
+            "# {{title}}\n\nCreated {{date}} at {{time:HHmm}}\n\
+             Week {{date:GGGG-[W]WW}}\n<% tp.date.now(\"YYYY-MM-DD\") %> <%tp.file.title%>\n\
+             <% tp.date.now('dddd', -7) %> <% tp.date.tomorrow() %> {{time}}\n\
+             <% tp.file.cursor() %>\n",
+            &settings,
+        );
+
+        assert_eq!(
+            converted.text,
+            "# {{date}}\n\nCreated {{date}} at {{hhmm}}\n\
+             Week {{date:GGGG-[W]WW}}\n{{date}} {{date}}\n\
+             {{date:dddd -7d}} {{date +1d}} {{time:HH:mm}}\n\
+             <% tp.file.cursor() %>\n"
+        );
+        assert_eq!(converted.unmapped, vec!["<% tp.file.cursor() %>"]);
+    }
+
+    #[test]
+    fn test_convert_template_uses_configured_formats() {
+        let settings = ObsidianSettings {
+            daily_notes: Some(DailyNotesSettings {
+                format: Some("YYYY/DD.MM.YYYY".to_string()),
+                ..Default::default()
+            }),
+            templates: Some(TemplatesSettings {
+                time_format: Some("h:mm A".to_string()),
+                date_format: Some("dddd".to_string()),
+                ..Default::default()
+            }),
+        };
+
+        let converted = convert_template(
+            "{{title}} {{date}} {{time}} <% tp.date.now(\"HH:mm\", 1) %>",
+            &settings,
+        );
+        assert_eq!(
+            converted.text,
+            "{{date:DD.MM.YYYY}} {{date:dddd}} {{time:h:mm A}} <% tp.date.now(\"HH:mm\", 1) %>"
+        );
+        assert_eq!(converted.unmapped, vec!["<% tp.date.now(\"HH:mm\", 1) %>"]);
+    }
+
+    #[test]
+    fn test_generated_anchors_are_block_ids() {
+        let anchor = AnchorToken::parse("focus-0930").unwrap();
+        assert!(is_block_id(&anchor.to_marker()));
+
+        let taken = next_free_anchor("^focus-0930\n", anchor);
+        assert_eq!(taken.to_marker(), "^focus-0930__2");
+        assert!(!is_block_id(&taken.to_marker()));
+        assert!(!is_block_id("^jrnl-0812__iphone"));
+    }
+
+    #[test]
+    fn test_invalid_block_ids() {
+        let (_temp_dir, vault) = vault_with(&[(
+            "capture/2025/2025-09/2025-09-14.md",
+            "^jrnl-0812\nOne\n\n^jrnl-0812__iphone\nTwo\n",
+        )]);
+
+        let invalid = invalid_block_ids(&vault).unwrap();
+        assert_eq!(
+            invalid,
+            vec![InvalidBlockId {
+                path: vault.root().join("capture/2025/2025-09/2025-09-14.md"),
+                line: 4,
+                marker: "^jrnl-0812__iphone".to_string(),
+            }]
+        );
+    }
+}
@@ -0,0 +1,219 @@
+use crate::anchors::AnchorToken;
+use crate::append::{append_block_suffixed, interpret_escapes, AppendOptions};
+use crate::date::UtcDay;
+use crate::error::A4Error;
+use crate::notes::{read_note, write_note};
+use crate::slug::validate_slug;
+use crate::vault::Vault;
+use fs_err as fs;
+use regex::Regex;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+pub const INDEX_FILE: &str = "index.md";
+pub const LOG_FILE: &str = "log.md";
+
+/// Used when `routines/templates/project-index.md` is absent.
+pub const DEFAULT_INDEX_TEMPLATE: &str = "---
+kind: hub.project
+created: {{now_utc}}
+---
+# {{slug}}
+
+## Log
+
+- [[projects/{{slug}}/log]]
+";
+
+/// Used when `routines/templates/project-log.md` is absent.
+pub const DEFAULT_LOG_TEMPLATE: &str = "# {{slug}} log
+";
+
+static DATED_HEADING_REGEX: OnceLock<Regex> = OnceLock::new();
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ProjectSummary {
+    pub slug: String,
+    /// Date of the newest `## YYYY-MM-DD` entry in `log.md`.
+    pub last_activity: Option<UtcDay>,
+}
+
+/// Create `projects/<slug>/index.md` and `log.md` from already-filled content.
+/// Refuses to touch an existing project.
+pub fn create_project(
+    vault: &Vault,
+    slug: &str,
+    index: &str,
+    log: &str,
+) -> Result<PathBuf, A4Error> {
+    validate_slug(slug)?;
+
+    let dir = vault.project_dir(slug);
+    if dir.exists() {
+        return Err(A4Error::ProjectExists { path: dir });
+    }
+
+    write_note(&dir.join(INDEX_FILE), index)?;
+    write_note(&dir.join(LOG_FILE), log)?;
+
+    Ok(dir)
+}
+
+/// Path to a project's append-only log, checking that the project exists.
+pub fn log_path(vault: &Vault, slug: &str) -> Result<PathBuf, A4Error> {
+    validate_slug(slug)?;
+
+    let dir = vault.project_dir(slug);
+    if !dir.is_dir() {
+        return Err(A4Error::ProjectNotFound {
+            slug: slug.to_string(),
+        });
+    }
+
+    Ok(dir.join(LOG_FILE))
+}
+
+/// Append an anchored entry to `log.md` under a `## YYYY-MM-DD` heading for `day`.
+/// Returns the anchor actually written (suffixed if `anchor` was already taken).
+pub fn append_log_entry(
+    vault: &Vault,
+    slug: &str,
+    day: UtcDay,
+    anchor: AnchorToken,
+    content: &str,
+) -> Result<AnchorToken, A4Error> {
+    let path = log_path(vault, slug)?;
+
+    append_block_suffixed(
+        vault,
+        &path,
+        AppendOptions {
+            heading: &day.to_string(),
+            anchor,
+            content: &interpret_escapes(content),
+        },
+    )
+}
+
+/// All project hubs under `projects/`, sorted by slug.
+pub fn list_projects(vault: &Vault) -> Result<Vec<ProjectSummary>, A4Error> {
+    let projects_dir = vault.root().join("projects");
+    if !projects_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut projects = Vec::new();
+    for entry in fs::read_dir(&projects_dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+
+        let slug = entry.file_name().to_string_lossy().into_owned();
+        let log = entry.path().join(LOG_FILE);
+        let last_activity = if log.exists() {
+            last_dated_heading(&read_note(&log)?.body)
+        } else {
+            None
+        };
+
+        projects.push(ProjectSummary {
+            slug,
+            last_activity,
+        });
+    }
+
+    projects.sort_by(|a, b| a.slug.cmp(&b.slug));
+    Ok(projects)
+}
+
+fn last_dated_heading(body: &str) -> Option<UtcDay> {
+    let re =
+        DATED_HEADING_REGEX.get_or_init(|| Regex::new(r"(?m)^## (\d{4}-\d{2}-\d{2})\s*$").unwrap());
+
+    re.captures_iter(body)
+        .filter_map(|caps| UtcDay::parse(&caps[1]).ok())
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault::VaultOpts;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_create_and_log() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+
+        let dir = create_project(&vault, "gb-ppu", "# gb-ppu\n", "# gb-ppu log\n").unwrap();
+        assert!(dir.join(INDEX_FILE).exists());
+
+        let day = UtcDay::parse("2025-09-14").unwrap();
+        let anchor = AnchorToken::parse("log-0930").unwrap();
+        append_log_entry(&vault, "gb-ppu", day, anchor.clone(), "Sprite fetch works").unwrap();
+        let second = append_log_entry(&vault, "gb-ppu", day, anchor, "Window layer").unwrap();
+        assert_eq!(second.to_marker(), "^log-0930__2");
+
+        let log = std::fs::read_to_string(dir.join(LOG_FILE)).unwrap();
+        assert_eq!(
+            log,
+            "# gb-ppu log\n\n## 2025-09-14\n\n^log-0930\n\nSprite fetch works\n\n\
+             ^log-0930__2\n\nWindow layer\n"
+        );
+    }
+
+    #[test]
+    fn test_create_rejects_existing_and_bad_slug() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+
+        create_project(&vault, "gb-ppu", "", "").unwrap();
+        assert!(matches!(
+            create_project(&vault, "gb-ppu", "", ""),
+            Err(A4Error::ProjectExists { .. })
+        ));
+        assert!(matches!(
+            create_project(&vault, "GB PPU", "", ""),
+            Err(A4Error::InvalidSlug { .. })
+        ));
+    }
+
+    #[test]
+    fn test_log_requires_project() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+
+        let day = UtcDay::parse("2025-09-14").unwrap();
+        let anchor = AnchorToken::parse("log-0930").unwrap();
+        assert!(matches!(
+            append_log_entry(&vault, "missing", day, anchor, "text"),
+            Err(A4Error::ProjectNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_list_projects_last_activity() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+
+        create_project(&vault, "beta", "", "## 2025-09-01\n\n## 2025-09-14\n").unwrap();
+        create_project(&vault, "alpha", "", "").unwrap();
+
+        let projects = list_projects(&vault).unwrap();
+        assert_eq!(
+            projects,
+            vec![
+                ProjectSummary {
+                    slug: "alpha".to_string(),
+                    last_activity: None,
+                },
+                ProjectSummary {
+                    slug: "beta".to_string(),
+                    last_activity: Some(UtcDay::parse("2025-09-14").unwrap()),
+                },
+            ]
+        );
+    }
+}
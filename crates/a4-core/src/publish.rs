@@ -0,0 +1,405 @@
+use crate::blocks::parse_marker_line;
+use crate::config::PublishConfig;
+use crate::date::UtcDay;
+use crate::error::A4Error;
+use crate::notes::{read_note, split_front_matter};
+use crate::slug::slugify;
+use crate::stitch::stitch_with;
+use crate::vault::Vault;
+use fs_err as fs;
+use regex::{Captures, Regex};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+static ASSET_LINK_REGEX: OnceLock<Regex> = OnceLock::new();
+static CAPTURE_LINK_REGEX: OnceLock<Regex> = OnceLock::new();
+
+/// A published note and the media copied next to it.
+#[derive(Debug)]
+pub struct Publication {
+    pub path: PathBuf,
+    pub media: Vec<PathBuf>,
+}
+
+/// Rendered output before anything is written.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Rendered {
+    pub slug: String,
+    pub markdown: String,
+    /// Paths under `assets/` the markdown links to, `/`-separated.
+    pub assets: Vec<String>,
+}
+
+fn is_heading(line: &str) -> bool {
+    line.starts_with('#') && line.trim_start_matches('#').starts_with(' ')
+}
+
+/// Whether `text` carries an inline `#tag` from `tags`.
+fn has_tag(text: &str, tags: &[String]) -> bool {
+    text.split_whitespace().any(|word| {
+        word.strip_prefix('#').is_some_and(|tag| {
+            let tag = tag.trim_end_matches(|c: char| c.is_ascii_punctuation());
+            tags.iter().any(|private| private == tag)
+        })
+    })
+}
+
+/// Empty every private block in `body`: blocks whose anchor prefix is in
+/// `private_prefixes` or whose content has a `private_tags` tag. The marker
+/// stays, so an embed of the block resolves to nothing instead of failing.
+/// Text outside any block, such as the prefix before the first anchor, is
+/// emptied when it has a `private_tags` tag; headings always stay.
+pub fn redact(body: &str, config: &PublishConfig) -> String {
+    let lines: Vec<&str> = body.lines().collect();
+    let mut keep = vec![true; lines.len()];
+
+    let mut in_fence = false;
+    // First content line of the current span, and whether its anchor prefix
+    // alone makes it private.
+    let mut block: Option<(usize, bool)> = Some((0, false));
+    let finish = |block: Option<(usize, bool)>, end: usize, keep: &mut [bool]| {
+        if let Some((start, prefix_private)) = block {
+            let content = &lines[start..end];
+            if prefix_private || content.iter().any(|l| has_tag(l, &config.private_tags)) {
+                keep[start..end].iter_mut().for_each(|k| *k = false);
+            }
+        }
+    };
+
+    for (index, line) in lines.iter().enumerate() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        }
+        if in_fence {
+            continue;
+        }
+        if let Some(token) = parse_marker_line(line) {
+            finish(block.take(), index, &mut keep);
+            block = Some((index + 1, config.private_prefixes.contains(&token.prefix)));
+        } else if is_heading(line) {
+            finish(block.take(), index, &mut keep);
+            block = Some((index + 1, false));
+        }
+    }
+    finish(block, lines.len(), &mut keep);
+
+    let mut out: String = lines
+        .iter()
+        .zip(keep)
+        .filter(|(_, keep)| *keep)
+        .map(|(line, _)| format!("{line}\n"))
+        .collect();
+    if !body.ends_with('\n') {
+        out.pop();
+    }
+    out
+}
+
+/// Drop anchor marker lines and collapse the blank lines they leave behind.
+fn strip_markers(body: &str) -> String {
+    let mut out = String::new();
+    let mut blank_run = 0;
+    let mut in_fence = false;
+    for line in body.lines() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        }
+        if !in_fence && parse_marker_line(line).is_some() {
+            continue;
+        }
+        if line.trim().is_empty() {
+            blank_run += 1;
+            if blank_run > 1 || out.is_empty() {
+                continue;
+            }
+        } else {
+            blank_run = 0;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    while out.ends_with("\n\n") {
+        out.pop();
+    }
+    out
+}
+
+/// Point `assets/...` links at the media folder, returning the assets used.
+fn rewrite_asset_links(body: &str, media_url: &str) -> (String, Vec<String>) {
+    let re = ASSET_LINK_REGEX.get_or_init(|| {
+        Regex::new(r"(!?\[[^\]]*\]\()(?:\./|\.\./|/)*assets/([^)\s]+)(\))").unwrap()
+    });
+    let mut assets = Vec::new();
+    let rewritten = re.replace_all(body, |caps: &Captures| {
+        assets.push(caps[2].to_string());
+        format!(
+            "{}{}/{}{}",
+            &caps[1],
+            media_url.trim_end_matches('/'),
+            &caps[2],
+            &caps[3]
+        )
+    });
+    assets.sort();
+    assets.dedup();
+    (rewritten.into_owned(), assets)
+}
+
+/// Lines still linking into `capture/`, as `  line N: link`.
+fn capture_links(body: &str) -> String {
+    let re = CAPTURE_LINK_REGEX.get_or_init(|| {
+        Regex::new(r"\[\[capture/[^\]]*\]\]|\]\((?:\./|\.\./|/)*capture/[^)]*\)").unwrap()
+    });
+    body.lines()
+        .enumerate()
+        .flat_map(|(index, line)| {
+            re.find_iter(line)
+                .map(move |m| format!("  line {}: {}\n", index + 1, m.as_str()))
+        })
+        .collect()
+}
+
+/// Front matter fields of the source note.
+pub(crate) fn source_fields(front_matter: Option<&str>) -> serde_yaml::Mapping {
+    let yaml = front_matter
+        .map(|fm| {
+            fm.trim_end()
+                .trim_start_matches("---")
+                .trim_end_matches("---")
+        })
+        .unwrap_or_default();
+    match serde_yaml::from_str(yaml) {
+        Ok(serde_yaml::Value::Mapping(fields)) => fields,
+        _ => serde_yaml::Mapping::new(),
+    }
+}
+
+/// Stitch and redact `note` for publishing, with site front matter: `title`
+/// (front matter, first `# ` heading, or file name), `date` (front matter or
+/// `today`), `slug` (front matter or the title, slugified, or the date when
+/// the title has no usable characters), then the
+/// configured extra fields. Fails if links into `capture/` remain.
+pub fn render(
+    vault: &Vault,
+    note: &Path,
+    config: &PublishConfig,
+    today: UtcDay,
+) -> Result<Rendered, A4Error> {
+    let stitched = stitch_with(vault, note, &|body| redact(body, config))?;
+    let body = split_front_matter(&stitched).1;
+    let body = strip_markers(body);
+
+    let media_url = config.media_url.as_deref().unwrap_or(&config.media_dir);
+    let (body, assets) = rewrite_asset_links(&body, media_url);
+
+    let report = capture_links(&body);
+    if !report.is_empty() {
+        return Err(A4Error::PublishLeaksCapture { report });
+    }
+
+    let source = source_fields(read_note(note)?.front_matter.as_deref());
+    let field = |key: &str| {
+        source
+            .get(key)
+            .and_then(|value| value.as_str())
+            .map(str::to_string)
+    };
+    let title = field("title")
+        .or_else(|| {
+            body.lines()
+                .find_map(|line| line.strip_prefix("# "))
+                .map(|title| title.trim().to_string())
+        })
+        .unwrap_or_else(|| {
+            note.file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        });
+    let date = field("date").unwrap_or_else(|| today.to_string());
+    // A front-matter slug names the output file, so it is slugified too.
+    let slug = match field("slug") {
+        Some(slug) => slugify(&slug)?,
+        None => slugify(&title).or_else(|_| slugify(&date))?,
+    };
+
+    let mut front_matter = serde_yaml::Mapping::new();
+    front_matter.insert("title".into(), title.into());
+    front_matter.insert("date".into(), date.into());
+    front_matter.insert("slug".into(), slug.clone().into());
+    for (key, value) in &config.front_matter {
+        let value =
+            serde_yaml::to_value(value).map_err(|e| A4Error::FrontMatterParse(e.to_string()))?;
+        front_matter.insert(key.clone().into(), value);
+    }
+    let yaml = serde_yaml::to_string(&front_matter)
+        .map_err(|e| A4Error::FrontMatterParse(e.to_string()))?;
+
+    Ok(Rendered {
+        slug,
+        markdown: format!("---\n{yaml}---\n{body}"),
+        assets,
+    })
+}
+
+/// Render `note` into `<out>/<slug>.md` and copy linked assets into
+/// `<out>/<media_dir>/`.
+pub fn publish(
+    vault: &Vault,
+    note: &Path,
+    out: &Path,
+    config: &PublishConfig,
+    today: UtcDay,
+) -> Result<Publication, A4Error> {
+    let rendered = render(vault, note, config, today)?;
+
+    // Check every asset before writing anything.
+    let mut copies = Vec::new();
+    for asset in &rendered.assets {
+        let source = vault.resolve_relative(&format!("assets/{asset}"))?;
+        if !source.is_file() {
+            return Err(A4Error::PublishAssetMissing { path: source });
+        }
+        copies.push((source, out.join(&config.media_dir).join(asset)));
+    }
+
+    let mut media = Vec::new();
+    for (source, dest) in copies {
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(&source, &dest)?;
+        media.push(dest);
+    }
+
+    fs::create_dir_all(out)?;
+    let path = out.join(format!("{}.md", rendered.slug));
+    fs::write(&path, rendered.markdown)?;
+
+    Ok(Publication { path, media })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault::VaultOpts;
+    use tempfile::TempDir;
+
+    fn config() -> PublishConfig {
+        PublishConfig::default()
+    }
+
+    fn write(root: &Path, relative: &str, content: &str) -> PathBuf {
+        let path = root.join(relative);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_redact() {
+        let body = "## Day\n\n^focus-0930\n\nParser work\n\n^jrnl-2200\n\nFelt tired\n\n## Notes\n\n^idea-1000\n\nSurprise party #private\n\n^idea-1100\n\nUse a trie\n";
+        assert_eq!(
+            redact(body, &config()),
+            "## Day\n\n^focus-0930\n\nParser work\n\n^jrnl-2200\n## Notes\n\n^idea-1000\n^idea-1100\n\nUse a trie\n"
+        );
+        assert_eq!(
+            strip_markers(&redact(body, &config())),
+            "## Day\n\nParser work\n\n## Notes\n\nUse a trie\n"
+        );
+
+        let body =
+            "Call the doctor #private\n\n## Day\n\nPublic intro\n\n^idea-1100\n\nUse a trie\n";
+        assert_eq!(
+            redact(body, &config()),
+            "## Day\n\nPublic intro\n\n^idea-1100\n\nUse a trie\n"
+        );
+    }
+
+    #[test]
+    fn test_rewrite_asset_links_and_capture_check() {
+        let (body, assets) = rewrite_asset_links(
+            "![d](assets/img/2025/d.png) and [pdf](../assets/docs/p.pdf) ![d](assets/img/2025/d.png)\n",
+            "/media",
+        );
+        assert_eq!(
+            body,
+            "![d](/media/img/2025/d.png) and [pdf](/media/docs/p.pdf) ![d](/media/img/2025/d.png)\n"
+        );
+        assert_eq!(assets, vec!["docs/p.pdf", "img/2025/d.png"]);
+
+        assert_eq!(
+            capture_links("ok\nsee [[capture/2025-09-14]] and [x](../capture/2025/a.md)\n"),
+            "  line 2: [[capture/2025-09-14]]\n  line 2: ](../capture/2025/a.md)\n"
+        );
+        assert_eq!(capture_links("[[collections/capture/notes]]\n"), "");
+    }
+
+    #[test]
+    fn test_render_front_matter_and_embeds() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write(
+            root,
+            "capture/2025/2025-09/2025-09-14.md",
+            "## End of Day\n\n^eod-2215\n\nShipped the parser ![shot](assets/img/shot.png)\n\n^jrnl-2230\n\nPrivate thoughts\n",
+        );
+        let memo = write(
+            root,
+            "collections/memos/parser.md",
+            "---\nkind: collection.memo\n---\n# Parser Retrospective\n\n![[capture/2025-09-14#^eod-2215]]\n\n![[capture/2025-09-14#^jrnl-2230]]\n",
+        );
+        let vault = Vault::open(root, VaultOpts::default()).unwrap();
+        let mut config = config();
+        config
+            .front_matter
+            .insert("layout".to_string(), toml::Value::from("post"));
+
+        let rendered =
+            render(&vault, &memo, &config, UtcDay::parse("2025-09-20").unwrap()).unwrap();
+        assert_eq!(rendered.slug, "parser-retrospective");
+        assert_eq!(
+            rendered.markdown,
+            "---\ntitle: Parser Retrospective\ndate: 2025-09-20\nslug: parser-retrospective\nlayout: post\n---\n# Parser Retrospective\n\nShipped the parser ![shot](media/img/shot.png)\n"
+        );
+        assert_eq!(rendered.assets, vec!["img/shot.png"]);
+
+        let leaky = write(
+            root,
+            "collections/memos/leaky.md",
+            "# Leaky\n\nsee [[capture/2025-09-14]]\n",
+        );
+        assert!(matches!(
+            render(
+                &vault,
+                &leaky,
+                &config,
+                UtcDay::parse("2025-09-20").unwrap()
+            ),
+            Err(A4Error::PublishLeaksCapture { .. })
+        ));
+
+        let untitled = write(root, "collections/memos/untitled.md", "# …\n\nBody\n");
+        let rendered = render(
+            &vault,
+            &untitled,
+            &config,
+            UtcDay::parse("2025-09-20").unwrap(),
+        )
+        .unwrap();
+        assert_eq!(rendered.slug, "2025-09-20");
+
+        let escaping = write(
+            root,
+            "collections/memos/escaping.md",
+            "---\nslug: ../../Escape Hatch\n---\n# Escaping\n",
+        );
+        let rendered = render(
+            &vault,
+            &escaping,
+            &config,
+            UtcDay::parse("2025-09-20").unwrap(),
+        )
+        .unwrap();
+        assert_eq!(rendered.slug, "escape-hatch");
+    }
+}
@@ -0,0 +1,152 @@
+use crate::blocks::parse_blocks;
+use crate::date::UtcMonth;
+use crate::error::A4Error;
+use crate::notes::{read_note, write_note};
+use crate::vault::Vault;
+use std::path::PathBuf;
+
+pub struct RollupOptions<'a> {
+    /// Anchor prefixes to gather (e.g. `jrnl`).
+    pub prefixes: &'a [String],
+    /// Copy block content instead of writing `![[...]]` transclusion markers.
+    pub inline: bool,
+}
+
+#[derive(Debug)]
+pub struct Rollup {
+    pub path: PathBuf,
+    pub blocks: usize,
+}
+
+/// Render the month roll-up from the month's daily notes. Capture files are
+/// only read, never modified.
+pub fn render_month(
+    vault: &Vault,
+    month: UtcMonth,
+    opts: &RollupOptions,
+) -> Result<(String, usize), A4Error> {
+    let mut out = format!("---\nkind: journal.month\nmonth: {month}\n---\n# Journal {month}\n");
+    let mut count = 0;
+
+    for (day, path) in vault.daily_notes()? {
+        if day.month() != month {
+            continue;
+        }
+
+        let link = vault
+            .note_link(&path)
+            .unwrap_or_else(|| path.display().to_string());
+        let blocks: Vec<_> = parse_blocks(&read_note(&path)?.body)
+            .into_iter()
+            .filter(|block| opts.prefixes.contains(&block.anchor.prefix))
+            .collect();
+        if blocks.is_empty() {
+            continue;
+        }
+
+        out.push_str(&format!("\n## {day}\n"));
+        for block in blocks {
+            let target = format!("{link}#{}", block.anchor.to_marker());
+            if opts.inline {
+                if !block.content.is_empty() {
+                    out.push_str(&format!("\n{}\n", block.content));
+                }
+                out.push_str(&format!("\n— [[{target}]]\n"));
+            } else {
+                out.push_str(&format!("\n![[{target}]]\n"));
+            }
+            count += 1;
+        }
+    }
+
+    Ok((out, count))
+}
+
+/// Write (or refresh) `collections/journals/YYYY/YYYY-MM.md`.
+pub fn write_month(
+    vault: &Vault,
+    month: UtcMonth,
+    opts: &RollupOptions,
+) -> Result<Rollup, A4Error> {
+    let (content, blocks) = render_month(vault, month, opts)?;
+    let path = vault.journal_rollup_path(month);
+    write_note(&path, &content)?;
+    Ok(Rollup { path, blocks })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date::UtcDay;
+    use crate::vault::VaultOpts;
+    use tempfile::TempDir;
+
+    fn setup() -> (TempDir, Vault) {
+        let temp_dir = TempDir::new().unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+
+        let days = [
+            (
+                "2025-09-14",
+                "## Journal\n\n^jrnl-0812\n\nSlept well\n\n^focus-0930\n\nWork\n",
+            ),
+            ("2025-09-02", "^jrnl-2130__iphone\n\nLong day\n"),
+            ("2025-10-01", "^jrnl-0700\n\nNext month\n"),
+        ];
+        for (day, content) in days {
+            let path = vault.capture_day_path(UtcDay::parse(day).unwrap());
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+
+        (temp_dir, vault)
+    }
+
+    #[test]
+    fn test_rollup_with_transclusions() {
+        let (_temp_dir, vault) = setup();
+        let prefixes = vec!["jrnl".to_string()];
+        let opts = RollupOptions {
+            prefixes: &prefixes,
+            inline: false,
+        };
+
+        let rollup = write_month(&vault, UtcMonth::parse("2025-09").unwrap(), &opts).unwrap();
+        assert_eq!(rollup.blocks, 2);
+        assert!(rollup
+            .path
+            .ends_with("collections/journals/2025/2025-09.md"));
+
+        let content = std::fs::read_to_string(&rollup.path).unwrap();
+        assert_eq!(
+            content,
+            "---\nkind: journal.month\nmonth: 2025-09\n---\n# Journal 2025-09\n\n\
+             ## 2025-09-02\n\n![[capture/2025/2025-09/2025-09-02#^jrnl-2130__iphone]]\n\n\
+             ## 2025-09-14\n\n![[capture/2025/2025-09/2025-09-14#^jrnl-0812]]\n"
+        );
+    }
+
+    #[test]
+    fn test_rollup_inline_and_refresh() {
+        let (_temp_dir, vault) = setup();
+        let prefixes = vec!["jrnl".to_string(), "focus".to_string()];
+        let opts = RollupOptions {
+            prefixes: &prefixes,
+            inline: true,
+        };
+        let month = UtcMonth::parse("2025-09").unwrap();
+        let capture = vault.capture_day_path(UtcDay::parse("2025-09-14").unwrap());
+        let before = std::fs::read_to_string(&capture).unwrap();
+
+        write_month(&vault, month, &opts).unwrap();
+        let rollup = write_month(&vault, month, &opts).unwrap();
+        assert_eq!(rollup.blocks, 3);
+
+        let content = std::fs::read_to_string(&rollup.path).unwrap();
+        assert!(content.ends_with(
+            "## 2025-09-14\n\nSlept well\n\n— [[capture/2025/2025-09/2025-09-14#^jrnl-0812]]\n\n\
+             Work\n\n— [[capture/2025/2025-09/2025-09-14#^focus-0930]]\n"
+        ));
+        assert_eq!(std::fs::read_to_string(&capture).unwrap(), before);
+    }
+}
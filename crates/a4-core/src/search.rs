@@ -0,0 +1,95 @@
+use crate::blocks::parse_marker_line;
+use crate::error::A4Error;
+use crate::notes::read_note;
+use crate::vault::Vault;
+use serde::Serialize;
+
+/// A matching line in a vault note.
+#[derive(Debug, Serialize)]
+pub struct SearchHit {
+    /// Vault-relative path, `/`-separated.
+    pub path: String,
+    /// 1-based line number within the note body.
+    pub line: usize,
+    /// Nearest anchor marker above the match, if any.
+    pub anchor: Option<String>,
+    pub text: String,
+}
+
+/// Case-insensitive substring search over every note in the vault. Stops
+/// after `limit` hits.
+pub fn search(vault: &Vault, query: &str, limit: usize) -> Result<Vec<SearchHit>, A4Error> {
+    let needle = query.to_lowercase();
+    let mut hits = Vec::new();
+    if needle.trim().is_empty() {
+        return Ok(hits);
+    }
+
+    for path in vault.note_paths()? {
+        let relative = path
+            .strip_prefix(vault.root())
+            .unwrap_or(&path)
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        let note = read_note(&path)?;
+        let mut anchor = None;
+        for (index, line) in note.body.lines().enumerate() {
+            if let Some(token) = parse_marker_line(line) {
+                anchor = Some(token.to_marker());
+                continue;
+            }
+            if line.to_lowercase().contains(&needle) {
+                if hits.len() >= limit {
+                    return Ok(hits);
+                }
+                hits.push(SearchHit {
+                    path: relative.clone(),
+                    line: index + 1,
+                    anchor: anchor.clone(),
+                    text: line.trim().to_string(),
+                });
+            }
+        }
+    }
+
+    Ok(hits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault::VaultOpts;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_search_reports_anchor_and_limit() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("capture/2025/2025-09")).unwrap();
+        std::fs::write(
+            root.join("capture/2025/2025-09/2025-09-14.md"),
+            "## Focus\n\n^focus-0930\n\nRefactor the Parser\n\nparser tests\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("notes.md"), "No anchors, but a parser\n").unwrap();
+        std::fs::create_dir_all(root.join(".a4")).unwrap();
+        std::fs::write(root.join(".a4/cache.md"), "parser\n").unwrap();
+        let vault = Vault::open(root, VaultOpts::default()).unwrap();
+
+        let hits = search(&vault, "PARSER", 10).unwrap();
+        assert_eq!(hits.len(), 3);
+        assert_eq!(hits[0].path, "capture/2025/2025-09/2025-09-14.md");
+        assert_eq!(hits[0].line, 5);
+        assert_eq!(hits[0].anchor.as_deref(), Some("^focus-0930"));
+        assert_eq!(hits[0].text, "Refactor the Parser");
+        assert_eq!(hits[2].path, "notes.md");
+        assert_eq!(hits[2].anchor, None);
+
+        assert_eq!(search(&vault, "parser", 1).unwrap().len(), 1);
+        assert!(search(&vault, "parser", 0).unwrap().is_empty());
+        assert!(search(&vault, "  ", 10).unwrap().is_empty());
+    }
+}
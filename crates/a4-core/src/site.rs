@@ -0,0 +1,654 @@
+//! Static HTML export of the whole vault for read-only browsing: one page per
+//! note with resolved wikilinks, anchor and heading IDs, backlinks and
+//! rendered embeds, plus an index with a calendar of daily notes.
+
+use crate::blocks::parse_marker_line;
+use crate::crypt;
+use crate::date::UtcDay;
+use crate::error::A4Error;
+use crate::notes::read_note;
+use crate::publish::source_fields;
+use crate::stitch::{stitch_body, Embedder};
+use crate::vault::Vault;
+use fs_err as fs;
+use pulldown_cmark::{html, CowStr, Event, LinkType, Options, Parser, Tag, TagEnd};
+use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+static LINK_REGEX: OnceLock<Regex> = OnceLock::new();
+
+const STYLE: &str = "\
+body { max-width: 46rem; margin: 0 auto; padding: 1rem; font: 17px/1.55 -apple-system, system-ui, sans-serif; color: #222; }
+nav { font-size: 0.9rem; color: #777; margin-bottom: 1.5rem; }
+a { color: #2457a6; }
+a.missing { color: #a33; text-decoration: line-through; }
+.anchor { margin-top: 1.2rem; font-size: 0.8rem; }
+.anchor a { color: #999; text-decoration: none; }
+.anchor:target a { color: #d08000; }
+.broken-embed { color: #a33; }
+pre, code { background: #f4f4f4; border-radius: 3px; }
+pre { padding: 0.6rem; overflow-x: auto; }
+blockquote { margin-left: 0; padding-left: 1rem; border-left: 3px solid #ddd; color: #555; }
+.backlinks { margin-top: 3rem; border-top: 1px solid #ddd; font-size: 0.9rem; }
+table.calendar { border-collapse: collapse; margin-bottom: 1rem; }
+table.calendar caption { text-align: left; font-weight: bold; }
+table.calendar td, table.calendar th { width: 2.4rem; text-align: center; padding: 0.2rem; }
+table.calendar td a { font-weight: bold; }
+";
+
+/// What [`export`] wrote.
+#[derive(Debug)]
+pub struct SiteReport {
+    pub index: PathBuf,
+    pub pages: usize,
+    /// Files copied from `assets/`.
+    pub assets: Vec<PathBuf>,
+    /// Notes left out because they are still encrypted on this device.
+    pub skipped: Vec<PathBuf>,
+}
+
+struct Page {
+    path: PathBuf,
+    /// Vault-relative link without `.md`, also the page path without `.html`.
+    link: String,
+    title: String,
+    day: Option<UtcDay>,
+}
+
+impl Page {
+    /// `../` repeated to reach the site root from this page.
+    fn prefix(&self) -> String {
+        "../".repeat(self.link.matches('/').count())
+    }
+}
+
+/// Render every note in the vault into `out` as `<path>.html`, with
+/// `index.html` and `style.css` at the top. Linked files under `assets/` are
+/// copied alongside.
+pub fn export(vault: &Vault, out: &Path) -> Result<SiteReport, A4Error> {
+    let mut pages = Vec::new();
+    let mut skipped = Vec::new();
+    let daily_notes: HashMap<_, _> = vault
+        .daily_notes()?
+        .into_iter()
+        .map(|(day, path)| (path, day))
+        .collect();
+    for path in vault.note_paths()? {
+        if path.starts_with(out) {
+            continue;
+        }
+        if crypt::is_encrypted(&fs::read(&path)?) {
+            skipped.push(path);
+            continue;
+        }
+        let Some(link) = vault.note_link(&path) else {
+            continue;
+        };
+        let note = read_note(&path)?;
+        pages.push(Page {
+            title: note_title(&path, note.front_matter.as_deref(), &note.body),
+            day: daily_notes.get(&path).copied(),
+            link,
+            path,
+        });
+    }
+
+    let site = Site {
+        vault,
+        backlinks: backlinks(vault, &pages)?,
+        pages: pages
+            .iter()
+            .map(|page| (page.link.as_str(), page))
+            .collect(),
+    };
+
+    let mut assets = BTreeSet::new();
+    for page in &pages {
+        let html = site.render_page(page, &mut assets)?;
+        write(&out.join(format!("{}.html", page.link)), &html)?;
+    }
+
+    let index = out.join("index.html");
+    write(&index, &site.render_index(&pages, &vault_name(vault)))?;
+    write(&out.join("style.css"), STYLE)?;
+
+    let mut copied = Vec::new();
+    for asset in assets {
+        let Ok(source) = vault.resolve_relative(&asset) else {
+            continue;
+        };
+        if !source.is_file() {
+            tracing::warn!("Linked asset {} not found", source.display());
+            continue;
+        }
+        let dest = out.join(&asset);
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(&source, &dest)?;
+        copied.push(dest);
+    }
+
+    Ok(SiteReport {
+        index,
+        pages: pages.len(),
+        assets: copied,
+        skipped,
+    })
+}
+
+struct Site<'a> {
+    vault: &'a Vault,
+    pages: HashMap<&'a str, &'a Page>,
+    /// Page link → links of the pages that link to or embed it.
+    backlinks: HashMap<String, BTreeSet<String>>,
+}
+
+/// Embeds that cannot be resolved are shown in place instead of failing the
+/// export.
+struct SiteEmbedder;
+
+impl Embedder for SiteEmbedder {
+    fn broken(&self, error: A4Error) -> Result<String, A4Error> {
+        let A4Error::BrokenEmbed { link, reason } = error else {
+            return Err(error);
+        };
+        // Entities keep the brackets from being parsed as a wikilink again.
+        Ok(format!(
+            "<span class=\"broken-embed\" title=\"{}\">!&#91;&#91;{}&#93;&#93;</span>",
+            escape(&reason),
+            escape(&link)
+        ))
+    }
+}
+
+impl Site<'_> {
+    fn render_page(&self, page: &Page, assets: &mut BTreeSet<String>) -> Result<String, A4Error> {
+        let note = read_note(&page.path)?;
+        let stitched = stitch_body(self.vault, &page.path, &note.body, &SiteEmbedder)?;
+        let content = self.render_markdown(page, &mark_anchors(&stitched), assets);
+        let prefix = page.prefix();
+
+        let mut backlinks = String::new();
+        if let Some(sources) = self.backlinks.get(&page.link) {
+            backlinks.push_str("<section class=\"backlinks\">\n<h2>Backlinks</h2>\n<ul>\n");
+            for source in sources {
+                let title = self.pages.get(source.as_str()).map_or("", |p| &p.title);
+                backlinks.push_str(&format!(
+                    "<li><a href=\"{prefix}{}.html\">{}</a> <small>{}</small></li>\n",
+                    escape(source),
+                    escape(title),
+                    escape(source)
+                ));
+            }
+            backlinks.push_str("</ul>\n</section>\n");
+        }
+
+        Ok(layout(
+            &page.title,
+            &prefix,
+            &format!(
+                "<a href=\"{prefix}index.html\">Index</a> · {}",
+                escape(&page.link)
+            ),
+            &format!("{content}{backlinks}"),
+        ))
+    }
+
+    /// Markdown to HTML, pointing wikilinks at pages and `assets/` links at
+    /// the copied files, and giving every heading an ID.
+    fn render_markdown(
+        &self,
+        page: &Page,
+        markdown: &str,
+        assets: &mut BTreeSet<String>,
+    ) -> String {
+        let options = Options::ENABLE_TABLES
+            | Options::ENABLE_STRIKETHROUGH
+            | Options::ENABLE_TASKLISTS
+            | Options::ENABLE_WIKILINKS;
+        let prefix = page.prefix();
+
+        let mut events = Vec::new();
+        let mut heading: Option<Vec<Event>> = None;
+        let mut ids = HashMap::new();
+        let mut missing_link = false;
+
+        for event in Parser::new_ext(markdown, options) {
+            let event = match event {
+                Event::Start(Tag::Link {
+                    link_type: LinkType::WikiLink { .. },
+                    dest_url,
+                    title,
+                    id,
+                }) => match self.wikilink_href(&dest_url, &prefix) {
+                    Some(href) => Event::Start(Tag::Link {
+                        link_type: LinkType::Inline,
+                        dest_url: href.into(),
+                        title,
+                        id,
+                    }),
+                    None => {
+                        missing_link = true;
+                        Event::InlineHtml(
+                            format!(
+                                "<a class=\"missing\" title=\"{} not found\">",
+                                escape(&dest_url)
+                            )
+                            .into(),
+                        )
+                    }
+                },
+                Event::End(TagEnd::Link) if missing_link => {
+                    missing_link = false;
+                    Event::InlineHtml("</a>".into())
+                }
+                Event::Start(Tag::Link {
+                    link_type,
+                    dest_url,
+                    title,
+                    id,
+                }) => Event::Start(Tag::Link {
+                    link_type,
+                    dest_url: asset_href(dest_url, &prefix, assets),
+                    title,
+                    id,
+                }),
+                Event::Start(Tag::Image {
+                    link_type,
+                    dest_url,
+                    title,
+                    id,
+                }) => Event::Start(Tag::Image {
+                    link_type,
+                    dest_url: asset_href(dest_url, &prefix, assets),
+                    title,
+                    id,
+                }),
+                event => event,
+            };
+
+            // Buffer each heading until its end to derive the ID from its text.
+            match event {
+                Event::Start(Tag::Heading { .. }) => heading = Some(vec![event]),
+                Event::End(TagEnd::Heading(_)) => {
+                    let mut buffered = heading.take().unwrap_or_default();
+                    buffered.push(event);
+                    assign_heading_id(&mut buffered, &mut ids);
+                    events.extend(buffered);
+                }
+                event => match heading.as_mut() {
+                    Some(buffered) => buffered.push(event),
+                    None => events.push(event),
+                },
+            }
+        }
+
+        let mut out = String::new();
+        html::push_html(&mut out, events.into_iter());
+        out
+    }
+
+    /// `target#fragment` from a wikilink as an href relative to the current
+    /// page, or `None` when the target is not an exported note.
+    fn wikilink_href(&self, dest: &str, prefix: &str) -> Option<String> {
+        let (target, fragment) = match dest.split_once('#') {
+            Some((target, fragment)) if fragment.starts_with('^') => {
+                (target, format!("#{fragment}"))
+            }
+            Some((target, heading)) => (target, format!("#{}", heading_id(heading))),
+            None => (dest, String::new()),
+        };
+        if target.trim().is_empty() {
+            return Some(fragment);
+        }
+
+        let path = self.vault.resolve_link(target)?;
+        let link = self.vault.note_link(&path)?;
+        self.pages
+            .contains_key(link.as_str())
+            .then(|| format!("{prefix}{link}.html{fragment}"))
+    }
+
+    fn render_index(&self, pages: &[Page], name: &str) -> String {
+        let mut content = format!("<h1>{}</h1>\n", escape(name));
+
+        let mut months: BTreeMap<(i32, u8), BTreeMap<u8, &Page>> = BTreeMap::new();
+        for page in pages {
+            if let Some(day) = page.day {
+                months
+                    .entry((day.year, day.month))
+                    .or_default()
+                    .insert(day.day, page);
+            }
+        }
+        if !months.is_empty() {
+            content.push_str("<h2>Daily notes</h2>\n");
+            let mut year = None;
+            for ((y, m), days) in months.iter().rev() {
+                if year != Some(*y) {
+                    content.push_str(&format!("<h3>{y}</h3>\n"));
+                    year = Some(*y);
+                }
+                content.push_str(&calendar(*y, *m, days));
+            }
+        }
+
+        let mut sections: BTreeMap<&str, Vec<&Page>> = BTreeMap::new();
+        for page in pages.iter().filter(|page| page.day.is_none()) {
+            let section = match page.link.split_once('/') {
+                Some((dir, _)) => dir,
+                None => ".",
+            };
+            sections.entry(section).or_default().push(page);
+        }
+        if !sections.is_empty() {
+            content.push_str("<h2>Notes</h2>\n");
+            for (section, pages) in sections {
+                content.push_str(&format!("<h3>{}</h3>\n<ul>\n", escape(section)));
+                for page in pages {
+                    content.push_str(&format!(
+                        "<li><a href=\"{}.html\">{}</a> <small>{}</small></li>\n",
+                        escape(&page.link),
+                        escape(&page.title),
+                        escape(&page.link)
+                    ));
+                }
+                content.push_str("</ul>\n");
+            }
+        }
+
+        layout(name, "", "Index", &content)
+    }
+}
+
+/// A month grid, Monday first, linking the days that have a note.
+fn calendar(year: i32, month: u8, days: &BTreeMap<u8, &Page>) -> String {
+    let Ok(month_enum) = time::Month::try_from(month) else {
+        return String::new();
+    };
+    let Ok(first) = time::Date::from_calendar_date(year, month_enum, 1) else {
+        return String::new();
+    };
+
+    let mut out = format!(
+        "<table class=\"calendar\">\n<caption>{month_enum} {year}</caption>\n\
+         <tr><th>Mon</th><th>Tue</th><th>Wed</th><th>Thu</th><th>Fri</th><th>Sat</th><th>Sun</th></tr>\n<tr>"
+    );
+    let offset = first.weekday().number_days_from_monday();
+    out.push_str(&"<td></td>".repeat(offset as usize));
+
+    let length = month_enum.length(year);
+    for day in 1..=length {
+        if day > 1 && (offset + day - 1) % 7 == 0 {
+            out.push_str("</tr>\n<tr>");
+        }
+        match days.get(&day) {
+            Some(page) => out.push_str(&format!(
+                "<td><a href=\"{}.html\">{day}</a></td>",
+                escape(&page.link)
+            )),
+            None => out.push_str(&format!("<td>{day}</td>")),
+        }
+    }
+    out.push_str("</tr>\n</table>\n");
+    out
+}
+
+fn layout(title: &str, prefix: &str, nav: &str, content: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{}</title>\n<link rel=\"stylesheet\" href=\"{prefix}style.css\">\n</head>\n\
+         <body>\n<nav>{nav}</nav>\n<main>\n{content}</main>\n</body>\n</html>\n",
+        escape(title)
+    )
+}
+
+/// For every page, the pages whose bodies link to or embed it.
+fn backlinks(vault: &Vault, pages: &[Page]) -> Result<HashMap<String, BTreeSet<String>>, A4Error> {
+    let re = LINK_REGEX
+        .get_or_init(|| Regex::new(r"\[\[([^\]#|]+)(?:#[^\]|]*)?(?:\|[^\]]*)?\]\]").unwrap());
+
+    let mut backlinks: HashMap<String, BTreeSet<String>> = HashMap::new();
+    for page in pages {
+        let body = read_note(&page.path)?.body;
+        for caps in re.captures_iter(&body) {
+            let Some(target) = vault
+                .resolve_link(&caps[1])
+                .and_then(|path| vault.note_link(&path))
+            else {
+                continue;
+            };
+            if target != page.link {
+                backlinks
+                    .entry(target)
+                    .or_default()
+                    .insert(page.link.clone());
+            }
+        }
+    }
+    Ok(backlinks)
+}
+
+/// Replace each anchor marker line with an element carrying the marker as its
+/// ID, so `page.html#^focus-0930` lands on the block.
+fn mark_anchors(body: &str) -> String {
+    let mut out = String::new();
+    let mut in_fence = false;
+    for line in body.lines() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        }
+        match parse_marker_line(line).filter(|_| !in_fence) {
+            // The blank line ends the HTML block before the block's content.
+            Some(token) => out.push_str(&format!(
+                "<div class=\"anchor\" id=\"{0}\"><a href=\"#{0}\">{0}</a></div>\n\n",
+                escape(&token.to_marker())
+            )),
+            None => {
+                out.push_str(line);
+                out.push('\n');
+            }
+        }
+    }
+    out
+}
+
+/// Give the buffered heading events an ID from the heading text, numbering
+/// repeats (`plans`, `plans-2`).
+fn assign_heading_id<'a>(events: &mut [Event<'a>], ids: &mut HashMap<String, usize>) {
+    let text: String = events
+        .iter()
+        .filter_map(|event| match event {
+            Event::Text(text) | Event::Code(text) => Some(text.as_ref()),
+            _ => None,
+        })
+        .collect();
+
+    if let Some(Event::Start(Tag::Heading { id, .. })) = events.first_mut() {
+        let base = heading_id(&text);
+        let count = ids.entry(base.clone()).or_insert(0);
+        *count += 1;
+        let unique = match *count {
+            1 => base,
+            n => format!("{base}-{n}"),
+        };
+        *id = Some(CowStr::from(unique));
+    }
+}
+
+/// Fragment ID for a heading: lowercase words joined by `-`.
+pub fn heading_id(heading: &str) -> String {
+    let mut id = String::new();
+    for c in heading.trim().chars() {
+        if c.is_alphanumeric() {
+            id.extend(c.to_lowercase());
+        } else if (c.is_whitespace() || c == '-' || c == '_') && !id.ends_with('-') {
+            id.push('-');
+        }
+    }
+    let id = id.trim_matches('-');
+    if id.is_empty() {
+        "section".to_string()
+    } else {
+        id.to_string()
+    }
+}
+
+/// Point a link into `assets/` at the exported copy, recording it.
+fn asset_href<'a>(dest: CowStr<'a>, prefix: &str, assets: &mut BTreeSet<String>) -> CowStr<'a> {
+    let trimmed = dest.trim_start_matches(['.', '/']);
+    if !trimmed.starts_with("assets/") || dest.contains("://") {
+        return dest;
+    }
+    let asset = trimmed.to_string();
+    let href = format!("{prefix}{asset}");
+    assets.insert(asset);
+    href.into()
+}
+
+/// `title` from front matter, else the first `# ` heading, else the file name.
+fn note_title(path: &Path, front_matter: Option<&str>, body: &str) -> String {
+    source_fields(front_matter)
+        .get("title")
+        .and_then(|title| title.as_str())
+        .map(str::to_string)
+        .or_else(|| {
+            body.lines()
+                .find_map(|line| line.strip_prefix("# "))
+                .map(|title| title.trim().to_string())
+        })
+        .unwrap_or_else(|| {
+            path.file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        })
+}
+
+fn vault_name(vault: &Vault) -> String {
+    vault
+        .root()
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "Vault".to_string())
+}
+
+fn write(path: &Path, content: &str) -> Result<(), A4Error> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, content)?;
+    Ok(())
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault::VaultOpts;
+    use tempfile::TempDir;
+
+    fn write_note(root: &Path, relative: &str, content: &str) {
+        let path = root.join(relative);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_heading_id() {
+        assert_eq!(heading_id("End of Day"), "end-of-day");
+        assert_eq!(heading_id("  Q3 — Plans & Goals! "), "q3-plans-goals");
+        assert_eq!(heading_id("???"), "section");
+    }
+
+    #[test]
+    fn test_export_links_anchors_and_backlinks() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("vault");
+        write_note(
+            &root,
+            "capture/2025/2025-09/2025-09-14.md",
+            "---\nkind: capture.day\n---\n## Focus\n\n^focus-0930\n\nParser work\n\n## Focus\n\n![diagram](assets/img/2025/2025-09/d.png)\n",
+        );
+        write_note(
+            &root,
+            "collections/memo.md",
+            "# Memo\n\nSee [[capture/2025-09-14#^focus-0930|the session]] and [[capture/2025-09-14#Focus]].\n\n![[capture/2025-09-14#^focus-0930]]\n\n![[nowhere]] and [[nowhere]]\n",
+        );
+        write_note(&root, "assets/img/2025/2025-09/d.png", "png");
+        let vault = Vault::open(&root, VaultOpts::default()).unwrap();
+        let out = temp_dir.path().join("site");
+
+        let report = export(&vault, &out).unwrap();
+        assert_eq!(report.pages, 2);
+        assert_eq!(
+            report.assets,
+            vec![out.join("assets/img/2025/2025-09/d.png")]
+        );
+
+        let day =
+            std::fs::read_to_string(out.join("capture/2025/2025-09/2025-09-14.html")).unwrap();
+        assert!(day.contains("<h2 id=\"focus\">Focus</h2>"), "{day}");
+        assert!(day.contains("<h2 id=\"focus-2\">Focus</h2>"), "{day}");
+        assert!(day.contains("id=\"^focus-0930\""), "{day}");
+        assert!(
+            day.contains("src=\"../../../assets/img/2025/2025-09/d.png\""),
+            "{day}"
+        );
+        assert!(day.contains("<h2>Backlinks</h2>"), "{day}");
+        assert!(
+            day.contains("href=\"../../../collections/memo.html\">Memo</a>"),
+            "{day}"
+        );
+        assert!(day.contains("href=\"../../../style.css\""), "{day}");
+
+        let memo = std::fs::read_to_string(out.join("collections/memo.html")).unwrap();
+        assert!(
+            memo.contains(
+                "<a href=\"../capture/2025/2025-09/2025-09-14.html#^focus-0930\">the session</a>"
+            ),
+            "{memo}"
+        );
+        assert!(
+            memo.contains("href=\"../capture/2025/2025-09/2025-09-14.html#focus\""),
+            "{memo}"
+        );
+        // The embed is rendered in place
+        assert!(memo.contains("<p>Parser work</p>"), "{memo}");
+        assert!(memo.contains("class=\"broken-embed\""), "{memo}");
+        assert!(memo.contains("<a class=\"missing\""), "{memo}");
+        assert!(!memo.contains("Backlinks"), "{memo}");
+
+        let index = std::fs::read_to_string(out.join("index.html")).unwrap();
+        assert!(
+            index.contains("<caption>September 2025</caption>"),
+            "{index}"
+        );
+        assert!(
+            index.contains("<td><a href=\"capture/2025/2025-09/2025-09-14.html\">14</a></td>"),
+            "{index}"
+        );
+        // September 2025 starts on a Monday
+        assert!(index.contains("</tr>\n<tr><td>1</td>"), "{index}");
+        assert!(
+            index.contains("<a href=\"collections/memo.html\">Memo</a>"),
+            "{index}"
+        );
+    }
+}
@@ -0,0 +1,73 @@
+use crate::error::A4Error;
+use regex::Regex;
+use std::sync::OnceLock;
+
+static SLUG_REGEX: OnceLock<Regex> = OnceLock::new();
+
+/// Validate a human slug per protocol §17: `[a-z0-9][a-z0-9-]{1,63}`.
+pub fn validate_slug(slug: &str) -> Result<(), A4Error> {
+    let re = SLUG_REGEX.get_or_init(|| Regex::new(r"^[a-z0-9][a-z0-9-]{1,63}$").unwrap());
+
+    if re.is_match(slug) {
+        Ok(())
+    } else {
+        Err(A4Error::InvalidSlug {
+            slug: slug.to_string(),
+        })
+    }
+}
+
+/// Derive a protocol slug from free text: transliterated to ASCII, then
+/// lowercase letters and digits, everything else collapsed into single
+/// dashes, capped at 64 characters.
+pub fn slugify(text: &str) -> Result<String, A4Error> {
+    let mut slug = String::new();
+    for c in deunicode::deunicode(text).chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+
+    slug.truncate(64);
+    let slug = slug.trim_end_matches('-').to_string();
+
+    validate_slug(&slug)?;
+    Ok(slug)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_slugs() {
+        assert!(validate_slug("gb-ppu").is_ok());
+        assert!(validate_slug("karpathy-state-of-gpt-2025").is_ok());
+        assert!(validate_slug("42").is_ok());
+    }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(
+            slugify("Karpathy: State of GPT (2025)").unwrap(),
+            "karpathy-state-of-gpt-2025"
+        );
+        assert_eq!(slugify("  Ünïcode & spaces ").unwrap(), "unicode-spaces");
+        assert_eq!(slugify("Привет мир").unwrap(), "privet-mir");
+        assert_eq!(slugify("東京").unwrap(), "dong-jing");
+        assert_eq!(slugify(&"word ".repeat(20)).unwrap().len(), 64);
+        assert!(slugify("!!!").is_err());
+    }
+
+    #[test]
+    fn test_invalid_slugs() {
+        assert!(validate_slug("a").is_err());
+        assert!(validate_slug("-leading").is_err());
+        assert!(validate_slug("Upper").is_err());
+        assert!(validate_slug("has space").is_err());
+        assert!(validate_slug("../escape").is_err());
+        assert!(validate_slug(&"a".repeat(65)).is_err());
+    }
+}
@@ -0,0 +1,400 @@
+use crate::date::{UtcDay, UtcMonth};
+use crate::error::A4Error;
+use crate::vault::Vault;
+use fs_err as fs;
+use std::collections::BTreeSet;
+use std::path::{Component, Path};
+
+/// Which `capture/` directories a sparse checkout keeps. Everything outside
+/// `capture/` is always checked out.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CaptureScope {
+    /// Whole `capture/YYYY` directories.
+    pub years: BTreeSet<i32>,
+    /// Single `capture/YYYY/YYYY-MM` directories.
+    pub months: BTreeSet<UtcMonth>,
+}
+
+impl CaptureScope {
+    /// The last `months` months, counting the month of `today`.
+    pub fn recent(today: UtcDay, months: u32) -> Self {
+        let mut scope = CaptureScope::default();
+        let mut month = today.month();
+        for _ in 0..months {
+            scope.months.insert(month);
+            month = month.previous();
+        }
+        scope
+    }
+
+    pub fn includes(&self, month: UtcMonth) -> bool {
+        self.years.contains(&month.year) || self.months.contains(&month)
+    }
+
+    /// Whether the `/`-separated vault-relative file `path` is checked out
+    /// under [`CaptureScope::patterns`].
+    pub fn keeps(&self, path: &str) -> bool {
+        let parts: Vec<&str> = path.split('/').collect();
+        let (year, rest) = match parts.as_slice() {
+            ["capture", year, rest @ ..] if !rest.is_empty() => (*year, rest),
+            _ => return true,
+        };
+        let kept_year = |y: &i32| format!("{y:04}") == year;
+        if self.years.iter().any(kept_year) {
+            return true;
+        }
+        if !self.months.iter().any(|month| kept_year(&month.year)) {
+            return false;
+        }
+        match rest {
+            [dir, _, ..] => self
+                .months
+                .iter()
+                .any(|month| kept_year(&month.year) && month.to_string() == *dir),
+            // A file directly in a partially kept year
+            _ => true,
+        }
+    }
+
+    /// Add `capture/YYYY` or `capture/YYYY/YYYY-MM`.
+    pub fn add(&mut self, target: &str) -> Result<(), A4Error> {
+        let invalid = || A4Error::InvalidSparseTarget {
+            target: target.to_string(),
+        };
+        let parts: Vec<&str> = target.trim_matches('/').split('/').collect();
+        match parts.as_slice() {
+            ["capture", year] => {
+                let year = year
+                    .parse()
+                    .ok()
+                    .filter(|_| year.len() == 4)
+                    .ok_or_else(invalid)?;
+                self.years.insert(year);
+                self.months.retain(|month| month.year != year);
+            }
+            ["capture", year, month] => {
+                let month = UtcMonth::parse(month).map_err(|_| invalid())?;
+                if format!("{:04}", month.year) != *year {
+                    return Err(invalid());
+                }
+                if !self.years.contains(&month.year) {
+                    self.months.insert(month);
+                }
+            }
+            _ => return Err(invalid()),
+        }
+        Ok(())
+    }
+
+    /// Kept directories as vault-relative paths, e.g. `capture/2025/2025-09`.
+    pub fn dirs(&self) -> Vec<String> {
+        let mut dirs: Vec<(i32, String)> = self
+            .years
+            .iter()
+            .map(|year| (*year, format!("capture/{year:04}")))
+            .chain(
+                self.months
+                    .iter()
+                    .map(|m| (m.year, format!("capture/{:04}/{m}", m.year))),
+            )
+            .collect();
+        dirs.sort();
+        dirs.into_iter().map(|(_, dir)| dir).collect()
+    }
+
+    /// Sparse-checkout patterns (non-cone) for this scope. Each partially kept
+    /// year is re-included before its months, since a pattern cannot
+    /// re-include a path whose parent directory is excluded.
+    pub fn patterns(&self) -> Vec<String> {
+        let mut patterns = vec![ROOT_PATTERN.to_string(), CAPTURE_PATTERN.to_string()];
+        let years: BTreeSet<i32> = self
+            .years
+            .iter()
+            .copied()
+            .chain(self.months.iter().map(|month| month.year))
+            .collect();
+
+        for year in years {
+            patterns.push(format!("/capture/{year:04}/"));
+            if self.years.contains(&year) {
+                continue;
+            }
+            patterns.push(format!("!/capture/{year:04}/*/"));
+            for month in self.months.iter().filter(|month| month.year == year) {
+                patterns.push(format!("/capture/{year:04}/{month}/"));
+            }
+        }
+        patterns
+    }
+
+    /// Parse patterns written by [`CaptureScope::patterns`].
+    pub fn parse_patterns(text: &str) -> Result<Self, A4Error> {
+        let mut included = BTreeSet::new();
+        let mut partial = BTreeSet::new();
+        let mut scope = CaptureScope::default();
+
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line == ROOT_PATTERN || line == CAPTURE_PATTERN {
+                continue;
+            }
+            let unmanaged = || A4Error::UnmanagedSparsePattern {
+                pattern: line.to_string(),
+            };
+
+            if let Some(year) = line
+                .strip_prefix("!/capture/")
+                .and_then(|rest| rest.strip_suffix("/*/"))
+            {
+                partial.insert(year.parse::<i32>().map_err(|_| unmanaged())?);
+                continue;
+            }
+            let dir = line
+                .strip_prefix("/capture/")
+                .and_then(|rest| rest.strip_suffix('/'))
+                .ok_or_else(unmanaged)?;
+            match dir.split_once('/') {
+                None => {
+                    included.insert(dir.parse::<i32>().map_err(|_| unmanaged())?);
+                }
+                Some((_, month)) => {
+                    scope
+                        .months
+                        .insert(UtcMonth::parse(month).map_err(|_| unmanaged())?);
+                }
+            }
+        }
+
+        scope.years = included.difference(&partial).copied().collect();
+        Ok(scope)
+    }
+}
+
+const ROOT_PATTERN: &str = "/*";
+const CAPTURE_PATTERN: &str = "!/capture/*/";
+
+/// The repository at `root`. Its git dir is not necessarily `<root>/.git`: that
+/// may be a `gitdir:` file, as in submodules and linked worktrees.
+fn open(root: &Path) -> Result<gix::Repository, A4Error> {
+    gix::open(root).map_err(|e| A4Error::Git(format!("Failed to open repository: {e}")))
+}
+
+/// `<git dir>/info/sparse-checkout`
+fn patterns_path(repo: &gix::Repository) -> std::path::PathBuf {
+    repo.git_dir().join("info").join("sparse-checkout")
+}
+
+/// The scope of the sparse checkout in `root`, or `None` if the whole vault is
+/// checked out.
+pub fn load(root: &Path) -> Result<Option<CaptureScope>, A4Error> {
+    // A vault that is not (yet) a repository has nothing left out.
+    if !root.join(".git").exists() {
+        return Ok(None);
+    }
+    let repo = open(root)?;
+    let path = patterns_path(&repo);
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let enabled = repo
+        .config_snapshot()
+        .boolean("core.sparseCheckout")
+        .unwrap_or(false);
+    if !enabled {
+        return Ok(None);
+    }
+
+    CaptureScope::parse_patterns(&fs::read_to_string(&path)?).map(Some)
+}
+
+/// Enable non-cone sparse checkout for the repository at `git_dir` with
+/// `patterns`. Takes effect on the next checkout or reset.
+pub fn write_patterns(git_dir: &Path, patterns: &[String]) -> Result<(), A4Error> {
+    let config_error =
+        |e: &dyn std::fmt::Display| A4Error::Git(format!("Failed to update git config: {e}"));
+    let config_path = git_dir.join("config");
+    let mut config =
+        gix::config::File::from_path_no_includes(config_path.clone(), gix::config::Source::Local)
+            .map_err(|e| config_error(&e))?;
+    for (key, value) in [("sparseCheckout", "true"), ("sparseCheckoutCone", "false")] {
+        config
+            .set_raw_value_by("core", None, key, value)
+            .map_err(|e| config_error(&e))?;
+    }
+    let mut buf = Vec::new();
+    config.write_to(&mut buf)?;
+    fs::write(&config_path, buf)?;
+
+    let info = git_dir.join("info");
+    fs::create_dir_all(&info)?;
+    fs::write(
+        info.join("sparse-checkout"),
+        format!("{}\n", patterns.join("\n")),
+    )?;
+    Ok(())
+}
+
+/// Write `scope` and update the working tree to match. Files with local
+/// changes outside the new scope are left in place by git.
+pub fn apply(root: &Path, scope: &CaptureScope) -> Result<(), A4Error> {
+    write_patterns(open(root)?.git_dir(), &scope.patterns())?;
+    git(root, &["sparse-checkout", "reapply"])?;
+    Ok(())
+}
+
+/// Capture months committed at HEAD but outside `scope`.
+pub fn hidden_months(root: &Path, scope: &CaptureScope) -> Result<Vec<UtcMonth>, A4Error> {
+    let listing = git(
+        root,
+        &["ls-tree", "-r", "-d", "--name-only", "HEAD", "capture"],
+    )?;
+    Ok(listing
+        .lines()
+        .filter_map(|dir| capture_month(Path::new(dir)))
+        .filter(|month| !scope.includes(*month))
+        .collect())
+}
+
+/// A note for search results when the sparse checkout leaves capture months
+/// out, or `None` if every month was searched.
+pub fn unsearched_note(vault: &Vault) -> Result<Option<String>, A4Error> {
+    let Some(scope) = load(vault.root())? else {
+        return Ok(None);
+    };
+    let hidden = hidden_months(vault.root(), &scope)?;
+    Ok((!hidden.is_empty()).then(|| {
+        format!(
+            "{} capture months are not checked out and were not searched (see 'a4 sparse show')",
+            hidden.len()
+        )
+    }))
+}
+
+/// Fail if `path` lies in a capture month the sparse checkout leaves out, where
+/// writing would create a file that conflicts with the committed one.
+pub fn ensure_checked_out(vault: &Vault, path: &Path) -> Result<(), A4Error> {
+    let Ok(relative) = path.strip_prefix(vault.root()) else {
+        return Ok(());
+    };
+    let Some(month) = capture_month(relative) else {
+        return Ok(());
+    };
+
+    match load(vault.root())? {
+        Some(scope) if !scope.includes(month) => Err(A4Error::NotCheckedOut {
+            path: path.to_path_buf(),
+            dir: format!("capture/{:04}/{month}", month.year),
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// The month of a `capture/YYYY/YYYY-MM[/...]` path.
+fn capture_month(relative: &Path) -> Option<UtcMonth> {
+    let mut parts = relative.components().map(|c| match c {
+        Component::Normal(part) => part.to_str(),
+        _ => None,
+    });
+    if parts.next()? != Some("capture") {
+        return None;
+    }
+    let year = parts.next()??;
+    let month = UtcMonth::parse(parts.next()??).ok()?;
+    (format!("{:04}", month.year) == year).then_some(month)
+}
+
+fn git(workdir: &Path, args: &[&str]) -> Result<String, A4Error> {
+    let output = std::process::Command::new("git")
+        .args(args)
+        .current_dir(workdir)
+        .output()
+        .map_err(|e| A4Error::Git(format!("Failed to run git {}: {e}", args[0])))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(A4Error::Git(format!("git {} failed: {stderr}", args[0])));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recent_patterns_round_trip() {
+        let scope = CaptureScope::recent(UtcDay::parse("2025-02-14").unwrap(), 3);
+        let patterns = scope.patterns();
+        assert_eq!(
+            patterns,
+            vec![
+                "/*",
+                "!/capture/*/",
+                "/capture/2024/",
+                "!/capture/2024/*/",
+                "/capture/2024/2024-12/",
+                "/capture/2025/",
+                "!/capture/2025/*/",
+                "/capture/2025/2025-01/",
+                "/capture/2025/2025-02/",
+            ]
+        );
+        assert_eq!(
+            CaptureScope::parse_patterns(&patterns.join("\n")).unwrap(),
+            scope
+        );
+    }
+
+    #[test]
+    fn test_add_years_and_months() {
+        let mut scope = CaptureScope::recent(UtcDay::parse("2025-02-14").unwrap(), 1);
+        scope.add("capture/2023/2023-04/").unwrap();
+        scope.add("capture/2024/2024-12").unwrap();
+        scope.add("capture/2024").unwrap();
+
+        assert_eq!(
+            scope.dirs(),
+            vec![
+                "capture/2023/2023-04",
+                "capture/2024",
+                "capture/2025/2025-02"
+            ]
+        );
+        assert!(scope.includes(UtcMonth::parse("2024-07").unwrap()));
+        assert!(!scope.includes(UtcMonth::parse("2023-05").unwrap()));
+
+        let patterns = scope.patterns();
+        assert!(patterns.contains(&"/capture/2024/".to_string()));
+        assert!(!patterns.contains(&"!/capture/2024/*/".to_string()));
+        assert_eq!(
+            CaptureScope::parse_patterns(&patterns.join("\n")).unwrap(),
+            scope
+        );
+
+        for bad in [
+            "capture",
+            "capture/23",
+            "capture/2024/2023-01",
+            "sources/2024",
+        ] {
+            assert!(scope.add(bad).is_err(), "accepted {bad}");
+        }
+        assert!(matches!(
+            CaptureScope::parse_patterns("/*\n/sources/\n"),
+            Err(A4Error::UnmanagedSparsePattern { .. })
+        ));
+    }
+
+    #[test]
+    fn test_capture_month() {
+        assert_eq!(
+            capture_month(Path::new("capture/2025/2025-09/2025-09-14.md")),
+            Some(UtcMonth::parse("2025-09").unwrap())
+        );
+        assert_eq!(capture_month(Path::new("capture/2025/notes.md")), None);
+        assert_eq!(capture_month(Path::new("inbox/2025/2025-09")), None);
+    }
+}
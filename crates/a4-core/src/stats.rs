@@ -0,0 +1,281 @@
+use crate::blocks::parse_marker_line;
+use crate::date::UtcDay;
+use crate::error::A4Error;
+use crate::notes::read_note;
+use crate::vault::Vault;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+
+/// Key used for anchors without a `__suffix`.
+pub const NO_SUFFIX: &str = "(none)";
+
+#[derive(Debug, Serialize)]
+pub struct VaultStats {
+    pub since: Option<String>,
+    pub notes: usize,
+    /// Markdown notes per top-level directory (`.` for files at the root).
+    pub notes_per_directory: BTreeMap<String, usize>,
+    pub daily: DailyStats,
+    pub anchors: AnchorStats,
+    pub words: WordStats,
+    /// Anchor counts per local hour (`"09"`), from the anchors' HHMM tokens.
+    pub hours: BTreeMap<String, usize>,
+    /// Up to three hours with the most anchors, busiest first.
+    pub busiest_hours: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct DailyStats {
+    pub first_day: Option<String>,
+    pub last_day: Option<String>,
+    pub created: usize,
+    /// Days between the first day (or `since`) and today without a daily note.
+    /// Today is not counted: its note might just not exist yet.
+    pub missing: usize,
+    pub current_streak: usize,
+    pub longest_streak: usize,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct AnchorStats {
+    pub total: usize,
+    pub by_prefix: BTreeMap<String, usize>,
+    pub by_suffix: BTreeMap<String, usize>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct WordStats {
+    pub total: usize,
+    pub by_directory: BTreeMap<String, usize>,
+}
+
+/// Gather statistics for the whole vault. With `since`, only dated notes
+/// (daily notes and inbox paste zones) on or after that day are counted.
+pub fn compute(vault: &Vault, since: Option<UtcDay>, today: UtcDay) -> Result<VaultStats, A4Error> {
+    let mut stats = VaultStats {
+        since: since.map(|day| day.to_string()),
+        notes: 0,
+        notes_per_directory: BTreeMap::new(),
+        daily: DailyStats::default(),
+        anchors: AnchorStats::default(),
+        words: WordStats::default(),
+        hours: BTreeMap::new(),
+        busiest_hours: Vec::new(),
+    };
+    let mut daily_days = BTreeSet::new();
+    let daily_notes: HashMap<_, _> = vault
+        .daily_notes()?
+        .into_iter()
+        .map(|(day, path)| (path, day))
+        .collect();
+
+    for path in vault.note_paths()? {
+        let relative = path.strip_prefix(vault.root()).unwrap_or(&path);
+        let directory = top_level_dir(relative);
+        let daily_day = daily_notes.get(&path).copied();
+        let day = daily_day.or_else(|| paste_zone_day(relative));
+
+        if let Some(since) = since {
+            if day.is_none_or(|day| day < since) {
+                continue;
+            }
+        }
+
+        if let Some(day) = daily_day {
+            daily_days.insert(day);
+        }
+
+        let note = read_note(&path)?;
+        stats.notes += 1;
+        *stats
+            .notes_per_directory
+            .entry(directory.clone())
+            .or_default() += 1;
+
+        let mut words = 0;
+        for line in note.body.lines() {
+            if let Some(anchor) = parse_marker_line(line) {
+                stats.anchors.total += 1;
+                *stats
+                    .anchors
+                    .by_prefix
+                    .entry(anchor.prefix.clone())
+                    .or_default() += 1;
+                let suffix = anchor.suffix.as_deref().unwrap_or(NO_SUFFIX);
+                *stats
+                    .anchors
+                    .by_suffix
+                    .entry(suffix.to_string())
+                    .or_default() += 1;
+                *stats.hours.entry(anchor.hhmm[..2].to_string()).or_default() += 1;
+            } else {
+                words += line.split_whitespace().count();
+            }
+        }
+        stats.words.total += words;
+        *stats.words.by_directory.entry(directory).or_default() += words;
+    }
+
+    stats.daily = daily_stats(&daily_days, since, today);
+
+    let mut hours: Vec<_> = stats.hours.iter().collect();
+    hours.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+    stats.busiest_hours = hours
+        .into_iter()
+        .take(3)
+        .map(|(hour, _)| hour.clone())
+        .collect();
+
+    Ok(stats)
+}
+
+fn top_level_dir(relative: &Path) -> String {
+    let mut components = relative.components();
+    match (components.next(), components.next()) {
+        (Some(first), Some(_)) => first.as_os_str().to_string_lossy().into_owned(),
+        _ => ".".to_string(),
+    }
+}
+
+/// Day of a paste zone: `inbox/.../YYYY-MM-DD--paste-zone.md`.
+fn paste_zone_day(relative: &Path) -> Option<UtcDay> {
+    if top_level_dir(relative) != "inbox" {
+        return None;
+    }
+    let name = relative.file_name()?.to_string_lossy();
+    let stem = name.strip_suffix("--paste-zone.md")?;
+    UtcDay::parse(stem).ok()
+}
+
+fn daily_stats(days: &BTreeSet<UtcDay>, since: Option<UtcDay>, today: UtcDay) -> DailyStats {
+    let (Some(first), Some(last)) = (days.first().copied(), days.last().copied()) else {
+        return DailyStats::default();
+    };
+
+    let start = since.unwrap_or(first).to_date();
+    let end = today.to_date().max(last.to_date());
+    let span = (end - start).whole_days() + 1;
+    let mut missing = (span.max(0) as usize).saturating_sub(days.len());
+    if !days.contains(&today) && today.to_date() >= start {
+        missing = missing.saturating_sub(1);
+    }
+
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous: Option<time::Date> = None;
+    for day in days {
+        let date = day.to_date();
+        run = match previous {
+            Some(prev) if prev.next_day() == Some(date) => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        previous = Some(date);
+    }
+
+    // The current streak may end yesterday: today's note might just not exist yet.
+    let mut cursor = today.to_date();
+    if !days.contains(&today) {
+        cursor = cursor.previous_day().unwrap_or(cursor);
+    }
+    let mut current = 0;
+    while days.contains(&UtcDay::from_date(cursor)) {
+        current += 1;
+        match cursor.previous_day() {
+            Some(prev) => cursor = prev,
+            None => break,
+        }
+    }
+
+    DailyStats {
+        first_day: Some(first.to_string()),
+        last_day: Some(last.to_string()),
+        created: days.len(),
+        missing,
+        current_streak: current,
+        longest_streak: longest,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault::VaultOpts;
+    use tempfile::TempDir;
+
+    fn write(root: &Path, relative: &str, content: &str) {
+        let path = root.join(relative);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    fn setup() -> (TempDir, Vault) {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write(
+            root,
+            "capture/2025/2025-09/2025-09-10.md",
+            "^focus-0930\n\none two three\n",
+        );
+        write(
+            root,
+            "capture/2025/2025-09/2025-09-11.md",
+            "^focus-0945__iphone\n\nfour\n\n^jrnl-2130\n\nfive six\n",
+        );
+        write(
+            root,
+            "capture/2025/2025-09/2025-09-13.md",
+            "^jrnl-0905\n\nseven\n",
+        );
+        write(root, "capture/2025/2025-09/2025-09-14.md", "");
+        write(root, "projects/gb/log.md", "^log-0910\n\neight nine\n");
+        write(root, "README.md", "ten\n");
+        write(root, ".a4/ignored.md", "^focus-0000\n");
+        let vault = Vault::open(root, VaultOpts::default()).unwrap();
+        (temp_dir, vault)
+    }
+
+    #[test]
+    fn test_vault_stats() {
+        let (_temp_dir, vault) = setup();
+        let today = UtcDay::parse("2025-09-15").unwrap();
+        let stats = compute(&vault, None, today).unwrap();
+
+        assert_eq!(stats.notes, 6);
+        assert_eq!(stats.notes_per_directory["capture"], 4);
+        assert_eq!(stats.notes_per_directory["projects"], 1);
+        assert_eq!(stats.notes_per_directory["."], 1);
+
+        assert_eq!(stats.daily.created, 4);
+        assert_eq!(stats.daily.missing, 1); // 09-12; today (09-15) is exempt
+        assert_eq!(stats.daily.longest_streak, 2);
+        assert_eq!(stats.daily.current_streak, 2); // 13, 14 (today not written yet)
+
+        assert_eq!(stats.anchors.total, 5);
+        assert_eq!(stats.anchors.by_prefix["focus"], 2);
+        assert_eq!(stats.anchors.by_prefix["jrnl"], 2);
+        assert_eq!(stats.anchors.by_suffix["iphone"], 1);
+        assert_eq!(stats.anchors.by_suffix[NO_SUFFIX], 4);
+
+        assert_eq!(stats.words.total, 10);
+        assert_eq!(stats.words.by_directory["capture"], 7);
+
+        assert_eq!(stats.hours["09"], 4);
+        assert_eq!(stats.busiest_hours, vec!["09", "21"]);
+    }
+
+    #[test]
+    fn test_vault_stats_since() {
+        let (_temp_dir, vault) = setup();
+        let today = UtcDay::parse("2025-09-15").unwrap();
+        let since = UtcDay::parse("2025-09-12").unwrap();
+        let stats = compute(&vault, Some(since), today).unwrap();
+
+        assert_eq!(stats.notes, 2);
+        assert_eq!(stats.daily.created, 2);
+        assert_eq!(stats.daily.missing, 1); // 09-12; today (09-15) is exempt
+        assert_eq!(stats.anchors.total, 1);
+        assert_eq!(stats.since.as_deref(), Some("2025-09-12"));
+    }
+}
@@ -0,0 +1,136 @@
+use crate::crypt;
+use crate::date::UtcDay;
+use crate::error::A4Error;
+use crate::git_backend::{FileChange, GitBackend};
+use crate::sync::{remote_ref, SyncOptions};
+use crate::vault::Vault;
+use crate::verify::{self, EditKind};
+use serde::Serialize;
+use std::path::Path;
+
+/// Pending changes grouped the way `a4 sync` would commit them.
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+pub struct ChangeGroups {
+    pub new_daily_notes: Vec<String>,
+    pub new_files: Vec<String>,
+    /// Modified files the sync guard lets through: appends and new sections.
+    pub appended: Vec<String>,
+    /// Modified files where existing content changed (not append-only).
+    pub modified: Vec<String>,
+    pub deleted: Vec<String>,
+}
+
+impl ChangeGroups {
+    pub fn is_empty(&self) -> bool {
+        self.new_daily_notes.is_empty()
+            && self.new_files.is_empty()
+            && self.appended.is_empty()
+            && self.modified.is_empty()
+            && self.deleted.is_empty()
+    }
+}
+
+/// What `a4 sync` would have to do to reconcile with the remote.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncAction {
+    UpToDate,
+    Push,
+    FastForward,
+    /// Both sides have commits; sync rebases local commits onto the remote.
+    Rebase,
+    /// The remote-tracking ref does not exist yet (never fetched or pushed).
+    NoRemoteRef,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StatusReport {
+    pub remote_ref: String,
+    pub changes: ChangeGroups,
+    /// Commits on HEAD missing from the remote ref (as of the last fetch).
+    pub ahead: usize,
+    /// Commits on the remote ref missing from HEAD (as of the last fetch).
+    pub behind: usize,
+    pub action: SyncAction,
+}
+
+/// Describe what `a4 sync` would commit and how it would reconcile, without
+/// staging, committing or fetching.
+pub fn status<B: GitBackend>(
+    vault: &Vault,
+    backend: &B,
+    opts: &SyncOptions,
+) -> Result<StatusReport, A4Error> {
+    let mut changes = ChangeGroups::default();
+    for entry in backend.status_entries()? {
+        let group = match entry.change {
+            FileChange::Deleted => &mut changes.deleted,
+            FileChange::Added if is_daily_note(&entry.path) => &mut changes.new_daily_notes,
+            FileChange::Added => &mut changes.new_files,
+            FileChange::Modified => {
+                let path = vault.root().join(&entry.path);
+                let old = match backend.head_file(&entry.path)? {
+                    Some(old) => Some(crypt::reveal(vault, &path, old)?),
+                    None => None,
+                };
+                let new = std::fs::read(&path).ok();
+                let kind = match (old, new) {
+                    (Some(old), Some(new)) => {
+                        let old = String::from_utf8_lossy(&old);
+                        verify::classify(&old, &String::from_utf8_lossy(&new)).0
+                    }
+                    _ => EditKind::Rewrite,
+                };
+                match kind {
+                    EditKind::Append | EditKind::HeadingInsert => &mut changes.appended,
+                    EditKind::Rewrite => &mut changes.modified,
+                }
+            }
+        };
+        group.push(entry.path);
+    }
+
+    let remote_ref = remote_ref(backend, opts)?;
+    let counts = backend.ahead_behind(&remote_ref)?;
+    let (ahead, behind) = counts.unwrap_or((0, 0));
+
+    // Pending changes become one more local commit before reconciling.
+    let local = ahead + usize::from(!changes.is_empty());
+    let action = match counts {
+        None => SyncAction::NoRemoteRef,
+        Some(_) if behind == 0 && local == 0 => SyncAction::UpToDate,
+        Some(_) if behind == 0 => SyncAction::Push,
+        Some(_) if local == 0 => SyncAction::FastForward,
+        Some(_) => SyncAction::Rebase,
+    };
+
+    Ok(StatusReport {
+        remote_ref,
+        changes,
+        ahead,
+        behind,
+        action,
+    })
+}
+
+/// `capture/YYYY/YYYY-MM/YYYY-MM-DD.md`
+fn is_daily_note(path: &str) -> bool {
+    let path = Path::new(path);
+    path.starts_with("capture")
+        && path.extension().is_some_and(|ext| ext == "md")
+        && path
+            .file_stem()
+            .is_some_and(|stem| UtcDay::parse(&stem.to_string_lossy()).is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classification_helpers() {
+        assert!(is_daily_note("capture/2025/2025-09/2025-09-14.md"));
+        assert!(!is_daily_note("capture/2025/2025-09/notes.md"));
+        assert!(!is_daily_note("inbox/2025/2025-09/2025-09-14.md"));
+    }
+}
@@ -0,0 +1,201 @@
+use crate::blocks::{find_block, heading_section};
+use crate::error::A4Error;
+use crate::notes::{join_front_matter, read_note};
+use crate::vault::Vault;
+use regex::{Captures, Regex};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+static EMBED_REGEX: OnceLock<Regex> = OnceLock::new();
+
+/// Nested embeds deeper than this are treated as a cycle.
+const MAX_DEPTH: usize = 16;
+
+/// Render a note with every `![[target]]`, `![[target#^anchor]]` and
+/// `![[target#Heading]]` embed replaced by the referenced content, recursively.
+/// The note's own front matter is kept; embedded front matter is dropped.
+pub fn stitch(vault: &Vault, path: &Path) -> Result<String, A4Error> {
+    stitch_with(vault, path, &|body| body.to_string())
+}
+
+/// Like [`stitch`], but passes each note body, the stitched note's and every
+/// embedded one's, through `redact` before its embeds are resolved.
+pub fn stitch_with(
+    vault: &Vault,
+    path: &Path,
+    redact: &dyn Fn(&str) -> String,
+) -> Result<String, A4Error> {
+    let note = read_note(path)?;
+    let body = stitch_body(vault, path, &note.body, &Redacting(redact))?;
+    Ok(join_front_matter(note.front_matter.as_deref(), &body))
+}
+
+/// Hooks for [`stitch_body`].
+pub trait Embedder {
+    /// Transform each note body before its embeds are resolved.
+    fn redact(&self, body: &str) -> String {
+        body.to_string()
+    }
+
+    /// Replacement for an embed that cannot be resolved; by default the
+    /// [`A4Error::BrokenEmbed`] error is returned and stitching stops.
+    fn broken(&self, error: A4Error) -> Result<String, A4Error> {
+        Err(error)
+    }
+}
+
+struct Redacting<'a>(&'a dyn Fn(&str) -> String);
+
+impl Embedder for Redacting<'_> {
+    fn redact(&self, body: &str) -> String {
+        (self.0)(body)
+    }
+}
+
+/// Resolve the embeds in `body`, the body of the note at `path`.
+pub fn stitch_body(
+    vault: &Vault,
+    path: &Path,
+    body: &str,
+    embedder: &dyn Embedder,
+) -> Result<String, A4Error> {
+    let mut stitcher = Stitcher {
+        vault,
+        embedder,
+        stack: vec![path.to_path_buf()],
+    };
+    stitcher.body(&embedder.redact(body))
+}
+
+struct Stitcher<'a> {
+    vault: &'a Vault,
+    embedder: &'a dyn Embedder,
+    stack: Vec<PathBuf>,
+}
+
+impl Stitcher<'_> {
+    fn body(&mut self, body: &str) -> Result<String, A4Error> {
+        let re = EMBED_REGEX.get_or_init(|| {
+            Regex::new(r"!\[\[([^\]#|]+)(?:#([^\]|]+))?(?:\|[^\]]*)?\]\]").unwrap()
+        });
+
+        let mut error = None;
+        let stitched = re.replace_all(body, |caps: &Captures| {
+            if error.is_some() {
+                return String::new();
+            }
+            match self.embed(caps).or_else(|e| match e {
+                A4Error::BrokenEmbed { .. } => self.embedder.broken(e),
+                e => Err(e),
+            }) {
+                Ok(content) => content,
+                Err(e) => {
+                    error = Some(e);
+                    String::new()
+                }
+            }
+        });
+
+        match error {
+            Some(e) => Err(e),
+            None => Ok(stitched.into_owned()),
+        }
+    }
+
+    fn embed(&mut self, caps: &Captures) -> Result<String, A4Error> {
+        let link = caps[0].trim_start_matches("![[").trim_end_matches("]]");
+        let broken = |reason: &str| A4Error::BrokenEmbed {
+            link: link.to_string(),
+            reason: reason.to_string(),
+        };
+
+        let path = self
+            .vault
+            .resolve_link(&caps[1])
+            .ok_or_else(|| broken("note not found"))?;
+        if self.stack.len() >= MAX_DEPTH || self.stack.contains(&path) {
+            return Err(broken("embed cycle"));
+        }
+
+        let body = self.embedder.redact(&read_note(&path)?.body);
+        let content = match caps.get(2).map(|m| m.as_str()) {
+            None => body.trim_matches('\n').to_string(),
+            Some(fragment) if fragment.starts_with('^') => {
+                find_block(&body, fragment)
+                    .ok_or_else(|| broken("block not found"))?
+                    .content
+            }
+            Some(heading) => {
+                heading_section(&body, heading).ok_or_else(|| broken("heading not found"))?
+            }
+        };
+
+        self.stack.push(path);
+        let stitched = self.body(&content);
+        self.stack.pop();
+        stitched
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault::VaultOpts;
+    use tempfile::TempDir;
+
+    fn write(root: &Path, relative: &str, content: &str) -> PathBuf {
+        let path = root.join(relative);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_stitch_blocks_headings_and_notes() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write(
+            root,
+            "capture/2025/2025-09/2025-09-14.md",
+            "---\nkind: capture.day\n---\n## End of Day\n\n^eod-2215\n\nShipped the parser\n\n## Plans\n\nRest\n",
+        );
+        write(
+            root,
+            "sources/articles/gpt.md",
+            "---\nkind: source.article\n---\n# GPT\n\nHighlights\n",
+        );
+        let memo = write(
+            root,
+            "collections/memo.md",
+            "---\ntitle: Memo\n---\n# Memo\n\n- ![[capture/2025-09-14#^eod-2215]]\n\n![[capture/2025/2025-09/2025-09-14#Plans]]\n\n![[sources/articles/gpt|alias]]\n",
+        );
+        let vault = Vault::open(root, VaultOpts::default()).unwrap();
+
+        assert_eq!(
+            stitch(&vault, &memo).unwrap(),
+            "---\ntitle: Memo\n---\n# Memo\n\n- Shipped the parser\n\nRest\n\n# GPT\n\nHighlights\n"
+        );
+    }
+
+    #[test]
+    fn test_stitch_errors() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let a = write(root, "a.md", "![[b]]\n");
+        write(root, "b.md", "![[a]]\n");
+        let missing = write(root, "c.md", "![[a#^nope-0000]]\n");
+        let outside = write(root, "d.md", "![[../secret]]\n");
+        let vault = Vault::open(root, VaultOpts::default()).unwrap();
+
+        for (path, reason) in [
+            (a, "embed cycle"),
+            (missing, "block not found"),
+            (outside, "note not found"),
+        ] {
+            match stitch(&vault, &path) {
+                Err(A4Error::BrokenEmbed { reason: r, .. }) => assert_eq!(r, reason),
+                other => panic!("unexpected result: {other:?}"),
+            }
+        }
+    }
+}
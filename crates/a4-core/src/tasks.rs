@@ -0,0 +1,252 @@
+use crate::anchors::AnchorToken;
+use crate::append::{append_block_suffixed, AppendOptions};
+use crate::blocks::parse_blocks;
+use crate::date::UtcDay;
+use crate::error::A4Error;
+use crate::notes::read_note;
+use crate::vault::Vault;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+pub const TASKS_PREFIX: &str = "tasks";
+
+static CHECKBOX_REGEX: OnceLock<Regex> = OnceLock::new();
+static FROM_REGEX: OnceLock<Regex> = OnceLock::new();
+
+/// A `- [ ]` / `- [x]` item inside an anchored block of a daily note.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Task {
+    pub day: UtcDay,
+    pub path: PathBuf,
+    pub anchor: AnchorToken,
+    /// Item text without the checkbox or a trailing `(from [[...]])` reference.
+    pub text: String,
+    pub done: bool,
+}
+
+impl Task {
+    /// Key used to recognise the same task across days (carried copies).
+    pub fn key(&self) -> String {
+        self.text
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase()
+    }
+}
+
+/// Parse a checkbox line into `(text, done)`.
+pub fn parse_checkbox_line(line: &str) -> Option<(String, bool)> {
+    let re =
+        CHECKBOX_REGEX.get_or_init(|| Regex::new(r"^\s*[-*+] \[([ xX])\]\s+(.+?)\s*$").unwrap());
+    let from_re = FROM_REGEX.get_or_init(|| Regex::new(r"\s*\(from \[\[[^\]]*\]\]\)$").unwrap());
+
+    let caps = re.captures(line)?;
+    let text = from_re.replace(&caps[2], "").to_string();
+    if text.is_empty() {
+        return None;
+    }
+    Some((text, &caps[1] != " "))
+}
+
+/// Every checkbox item in the vault's daily notes, oldest day first. With
+/// `since`, earlier days are skipped.
+pub fn scan(vault: &Vault, since: Option<UtcDay>) -> Result<Vec<Task>, A4Error> {
+    let mut tasks = Vec::new();
+
+    for (day, path) in vault.daily_notes()? {
+        if since.is_some_and(|since| day < since) {
+            continue;
+        }
+
+        let note = read_note(&path)?;
+        for block in parse_blocks(&note.body) {
+            let mut in_fence = false;
+            for line in block.content.lines() {
+                if line.trim_start().starts_with("```") {
+                    in_fence = !in_fence;
+                }
+                if in_fence {
+                    continue;
+                }
+                if let Some((text, done)) = parse_checkbox_line(line) {
+                    tasks.push(Task {
+                        day,
+                        path: path.clone(),
+                        anchor: block.anchor.clone(),
+                        text,
+                        done,
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(tasks)
+}
+
+/// Collapse carried copies: keep each task's latest occurrence, which holds
+/// its current status. Order follows the latest occurrences.
+pub fn latest(tasks: Vec<Task>) -> Vec<Task> {
+    let mut last_index = HashMap::new();
+    for (index, task) in tasks.iter().enumerate() {
+        last_index.insert(task.key(), index);
+    }
+
+    tasks
+        .into_iter()
+        .enumerate()
+        .filter(|(index, task)| last_index[&task.key()] == *index)
+        .map(|(_, task)| task)
+        .collect()
+}
+
+/// Open tasks from days before `today` that are not already in today's note.
+pub fn carry_candidates(vault: &Vault, today: UtcDay) -> Result<Vec<Task>, A4Error> {
+    let tasks = latest(scan(vault, None)?);
+    let in_today: HashSet<String> = tasks
+        .iter()
+        .filter(|task| task.day == today)
+        .map(Task::key)
+        .collect();
+
+    Ok(tasks
+        .into_iter()
+        .filter(|task| task.day < today && !task.done && !in_today.contains(&task.key()))
+        .collect())
+}
+
+/// Append open tasks from previous days to today's note as a new
+/// `^tasks-HHMM` block under `heading`, each linking back to where it was
+/// last seen. Old notes are never edited. Returns `None` when nothing is open.
+pub fn carry(
+    vault: &Vault,
+    today: UtcDay,
+    heading: &str,
+    hhmm: &str,
+) -> Result<Option<(PathBuf, AnchorToken, usize)>, A4Error> {
+    let tasks = carry_candidates(vault, today)?;
+    if tasks.is_empty() {
+        return Ok(None);
+    }
+
+    let content = tasks
+        .iter()
+        .map(|task| {
+            let link = vault
+                .note_link(&task.path)
+                .unwrap_or_else(|| task.path.display().to_string());
+            format!(
+                "- [ ] {} (from [[{link}#{}]])",
+                task.text,
+                task.anchor.to_marker()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let path = vault.daily_note_path(today)?;
+    let anchor = append_block_suffixed(
+        vault,
+        &path,
+        AppendOptions {
+            heading,
+            anchor: AnchorToken::parse(&format!("{TASKS_PREFIX}-{hhmm}"))?,
+            content: &content,
+        },
+    )?;
+
+    Ok(Some((path, anchor, tasks.len())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault::VaultOpts;
+    use tempfile::TempDir;
+
+    fn setup() -> (TempDir, Vault) {
+        let temp_dir = TempDir::new().unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+
+        let days = [
+            (
+                "2025-09-12",
+                "## Tasks\n\n^tasks-0900\n\n- [ ] Renew passport\n- [x] Pay rent\n- [ ] Call  Mom\n",
+            ),
+            (
+                "2025-09-13",
+                "## Tasks\n\n^tasks-0905\n\n- [x] call mom\n\n```\n- [ ] not a task\n```\n",
+            ),
+        ];
+        for (day, content) in days {
+            let path = vault.capture_day_path(UtcDay::parse(day).unwrap());
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+
+        (temp_dir, vault)
+    }
+
+    #[test]
+    fn test_parse_checkbox_line() {
+        assert_eq!(
+            parse_checkbox_line("- [ ] Write tests"),
+            Some(("Write tests".to_string(), false))
+        );
+        assert_eq!(
+            parse_checkbox_line(
+                "  * [X] Done (from [[capture/2025/2025-09/2025-09-12#^tasks-0900]])"
+            ),
+            Some(("Done".to_string(), true))
+        );
+        assert_eq!(parse_checkbox_line("- [] nope"), None);
+        assert_eq!(parse_checkbox_line("plain text"), None);
+    }
+
+    #[test]
+    fn test_scan_and_latest() {
+        let (_temp_dir, vault) = setup();
+
+        let tasks = scan(&vault, None).unwrap();
+        assert_eq!(tasks.len(), 4);
+        assert_eq!(tasks[0].anchor.to_marker(), "^tasks-0900");
+
+        let latest = latest(tasks);
+        let summary: Vec<_> = latest.iter().map(|t| (t.text.as_str(), t.done)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Renew passport", false),
+                ("Pay rent", true),
+                ("call mom", true),
+            ]
+        );
+
+        let since = scan(&vault, Some(UtcDay::parse("2025-09-13").unwrap())).unwrap();
+        assert_eq!(since.len(), 1);
+    }
+
+    #[test]
+    fn test_carry_appends_block_once() {
+        let (_temp_dir, vault) = setup();
+        let today = UtcDay::parse("2025-09-14").unwrap();
+        let old = vault.capture_day_path(UtcDay::parse("2025-09-12").unwrap());
+        let before = std::fs::read_to_string(&old).unwrap();
+
+        let (path, anchor, count) = carry(&vault, today, "Tasks", "0800").unwrap().unwrap();
+        assert_eq!(anchor.to_marker(), "^tasks-0800");
+        assert_eq!(count, 1);
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.contains(
+            "^tasks-0800\n\n\
+             - [ ] Renew passport (from [[capture/2025/2025-09/2025-09-12#^tasks-0900]])\n"
+        ));
+        assert_eq!(std::fs::read_to_string(&old).unwrap(), before);
+
+        assert!(carry(&vault, today, "Tasks", "0810").unwrap().is_none());
+    }
+}
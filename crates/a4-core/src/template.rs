@@ -0,0 +1,532 @@
+//! Template rendering for notes created from `routines/templates/`.
+//!
+//! Placeholders are `{{name}}` or `{{name:argument}}`:
+//!
+//! - `{{date}}`, `{{date:FORMAT}}`: the note's day in a moment.js format
+//!   (default `YYYY-MM-DD`); `{{YYYY-MM-DD}}` is the short form.
+//! - `{{time}}`, `{{time:FORMAT}}`: the local time of rendering (default
+//!   `HH:mm`); `{{hhmm}}` gives `HHmm` for anchors.
+//! - `{{now_utc}}`: the UTC instant of rendering, ISO 8601.
+//! - `{{week}}` (`2025-W37`), `{{weekday}}` (`Monday`).
+//! - `{{link}}`, `{{yesterday}}`, `{{tomorrow}}`: wikilinks to daily notes.
+//! - `{{include:NAME}}`: `routines/templates/NAME.md`, rendered in place.
+//! - `{{#if monday}}…{{else}}…{{/if}}`: weekday names, `weekday` or
+//!   `weekend`, several separated by commas.
+//!
+//! `date`, `week`, `weekday` and `link` take a trailing offset such as
+//! `{{date:YYYY-MM-DD -1d}}` or `{{link +1w}}` (units `d`, `w`, `m`, `y`).
+//! Anything else is an error rather than being left in the note.
+
+use crate::date::{format_moment, Clock, LocalClock, UtcDay};
+use crate::error::A4Error;
+use crate::vault::Vault;
+use fs_err as fs;
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+use time::{Date, Duration, Month, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset, Weekday};
+
+const MAX_INCLUDE_DEPTH: usize = 8;
+
+/// What a template is rendered against.
+pub struct TemplateContext<'a> {
+    pub vault: &'a Vault,
+    /// Day of the note; `date` and its offsets count from it.
+    pub day: UtcDay,
+    /// Instant of rendering, in the local offset.
+    pub now: OffsetDateTime,
+    /// Extra variables, such as `slug` for project templates.
+    pub vars: BTreeMap<String, String>,
+}
+
+impl<'a> TemplateContext<'a> {
+    /// Render for `day` at the current local time.
+    pub fn new(vault: &'a Vault, day: UtcDay) -> Self {
+        Self::at(vault, day, &LocalClock)
+    }
+
+    /// Render for `day` at the time `clock` gives.
+    pub fn at(vault: &'a Vault, day: UtcDay, clock: &dyn Clock) -> Self {
+        TemplateContext {
+            vault,
+            day,
+            now: clock.now(),
+            vars: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_var(mut self, name: &str, value: &str) -> Self {
+        self.vars.insert(name.to_string(), value.to_string());
+        self
+    }
+}
+
+/// Render `template` (the contents of a template note).
+pub fn render(template: &str, ctx: &TemplateContext) -> Result<String, A4Error> {
+    render_nested(template, ctx, &mut Vec::new())
+}
+
+/// Render `routines/templates/<name>.md`, or `default` when it is absent.
+pub fn render_named(name: &str, default: &str, ctx: &TemplateContext) -> Result<String, A4Error> {
+    let path = ctx.vault.named_template_path(name);
+    if !path.exists() {
+        return render(default, ctx);
+    }
+    render_nested(
+        &fs::read_to_string(&path)?,
+        ctx,
+        &mut vec![name.to_string()],
+    )
+}
+
+fn render_nested(
+    template: &str,
+    ctx: &TemplateContext,
+    includes: &mut Vec<String>,
+) -> Result<String, A4Error> {
+    let nodes = parse(template)?;
+    let mut out = String::new();
+    write_nodes(&nodes, ctx, includes, &mut out)?;
+    Ok(out)
+}
+
+enum Node<'t> {
+    Text(&'t str),
+    Tag(&'t str),
+    If {
+        condition: &'t str,
+        then: Vec<Node<'t>>,
+        otherwise: Vec<Node<'t>>,
+    },
+}
+
+/// An `{{#if}}` being parsed: the nodes before it and, once `{{else}}` is
+/// seen, the finished `then` branch.
+struct OpenIf<'t> {
+    condition: &'t str,
+    outer: Vec<Node<'t>>,
+    then: Option<Vec<Node<'t>>>,
+}
+
+static TAG_REGEX: OnceLock<Regex> = OnceLock::new();
+
+fn parse(template: &str) -> Result<Vec<Node<'_>>, A4Error> {
+    let re = TAG_REGEX.get_or_init(|| Regex::new(r"\{\{\s*(.*?)\s*\}\}").unwrap());
+
+    let mut stack: Vec<OpenIf> = Vec::new();
+    let mut current = Vec::new();
+    let mut last = 0;
+
+    for caps in re.captures_iter(template) {
+        let whole = caps.get(0).unwrap();
+        if whole.start() > last {
+            current.push(Node::Text(&template[last..whole.start()]));
+        }
+        last = whole.end();
+
+        let tag = caps.get(1).unwrap().as_str();
+        if let Some(condition) = tag.strip_prefix("#if ") {
+            stack.push(OpenIf {
+                condition: condition.trim(),
+                outer: std::mem::take(&mut current),
+                then: None,
+            });
+        } else if tag == "else" {
+            let Some(OpenIf {
+                then: then @ None, ..
+            }) = stack.last_mut()
+            else {
+                return Err(invalid("{{else}} outside {{#if}}"));
+            };
+            *then = Some(std::mem::take(&mut current));
+        } else if tag == "/if" {
+            let OpenIf {
+                condition,
+                outer,
+                then,
+            } = stack
+                .pop()
+                .ok_or_else(|| invalid("{{/if}} without {{#if}}"))?;
+            let branch = std::mem::replace(&mut current, outer);
+            let (then, otherwise) = match then {
+                Some(then) => (then, branch),
+                None => (branch, Vec::new()),
+            };
+            current.push(Node::If {
+                condition,
+                then,
+                otherwise,
+            });
+        } else {
+            current.push(Node::Tag(tag));
+        }
+    }
+    if !stack.is_empty() {
+        return Err(invalid("{{#if}} without {{/if}}"));
+    }
+    if last < template.len() {
+        current.push(Node::Text(&template[last..]));
+    }
+    Ok(current)
+}
+
+fn invalid(reason: &str) -> A4Error {
+    A4Error::InvalidTemplate {
+        reason: reason.to_string(),
+    }
+}
+
+fn write_nodes(
+    nodes: &[Node],
+    ctx: &TemplateContext,
+    includes: &mut Vec<String>,
+    out: &mut String,
+) -> Result<(), A4Error> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Tag(tag) => out.push_str(&expand(tag, ctx, includes)?),
+            Node::If {
+                condition,
+                then,
+                otherwise,
+            } => {
+                let branch = if holds(condition, ctx.day.to_date())? {
+                    then
+                } else {
+                    otherwise
+                };
+                write_nodes(branch, ctx, includes, out)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn holds(condition: &str, date: Date) -> Result<bool, A4Error> {
+    let weekday = date.weekday();
+    let mut any = false;
+    for name in condition.split(',').map(str::trim) {
+        any |= match name {
+            "weekday" => !matches!(weekday, Weekday::Saturday | Weekday::Sunday),
+            "weekend" => matches!(weekday, Weekday::Saturday | Weekday::Sunday),
+            _ => match parse_weekday(name) {
+                Some(day) => day == weekday,
+                None => {
+                    return Err(A4Error::UnknownTemplateVariable {
+                        name: name.to_string(),
+                    })
+                }
+            },
+        };
+    }
+    Ok(any)
+}
+
+fn parse_weekday(name: &str) -> Option<Weekday> {
+    Some(match name {
+        "monday" => Weekday::Monday,
+        "tuesday" => Weekday::Tuesday,
+        "wednesday" => Weekday::Wednesday,
+        "thursday" => Weekday::Thursday,
+        "friday" => Weekday::Friday,
+        "saturday" => Weekday::Saturday,
+        "sunday" => Weekday::Sunday,
+        _ => return None,
+    })
+}
+
+static OFFSET_REGEX: OnceLock<Regex> = OnceLock::new();
+
+fn expand(tag: &str, ctx: &TemplateContext, includes: &mut Vec<String>) -> Result<String, A4Error> {
+    let unknown = || A4Error::UnknownTemplateVariable {
+        name: tag.to_string(),
+    };
+
+    // A trailing `+3d` / `-1w` offsets the day for date-based variables.
+    let offset_re = OFFSET_REGEX.get_or_init(|| Regex::new(r"\s+([+-]\d+)([dwmy])$").unwrap());
+    let (body, day) = match offset_re.captures(tag) {
+        Some(caps) => {
+            let out_of_range =
+                || invalid(&format!("offset {}{} is out of range", &caps[1], &caps[2]));
+            let amount: i32 = caps[1].parse().map_err(|_| out_of_range())?;
+            let day = shift(ctx.day.to_date(), amount, &caps[2]).ok_or_else(out_of_range)?;
+            (&tag[..caps.get(0).unwrap().start()], Some(day))
+        }
+        None => (tag, None),
+    };
+    let date = day.unwrap_or_else(|| ctx.day.to_date());
+    let (name, argument) = match body.split_once(':') {
+        Some((name, argument)) => (name.trim(), Some(argument.trim())),
+        None => (body.trim(), None),
+    };
+    let midnight = PrimitiveDateTime::new(date, Time::MIDNIGHT);
+
+    let value = match (name, argument) {
+        ("date", format) => format_moment(format.unwrap_or("YYYY-MM-DD"), midnight),
+        ("week", None) => {
+            let (year, week, _) = date.to_iso_week_date();
+            format!("{year}-W{week:02}")
+        }
+        ("weekday", None) => date.weekday().to_string(),
+        ("link", None) => daily_link(ctx.vault, date)?,
+        ("yesterday", None) if day.is_none() => daily_link(ctx.vault, date - Duration::days(1))?,
+        ("tomorrow", None) if day.is_none() => daily_link(ctx.vault, date + Duration::days(1))?,
+        _ if day.is_some() => return Err(unknown()),
+        ("YYYY-MM-DD", None) => ctx.day.to_string(),
+        ("time", format) => {
+            let now = PrimitiveDateTime::new(ctx.now.date(), ctx.now.time());
+            format_moment(format.unwrap_or("HH:mm"), now)
+        }
+        ("hhmm", None) => format!("{:02}{:02}", ctx.now.hour(), ctx.now.minute()),
+        ("now_utc", None) => {
+            let now = ctx.now.to_offset(UtcOffset::UTC);
+            now.format(&time::format_description::well_known::Iso8601::DEFAULT)
+                .unwrap_or_else(|_| now.to_string())
+        }
+        ("include", Some(template)) => include(template, ctx, includes)?,
+        (name, None) => ctx.vars.get(name).cloned().ok_or_else(unknown)?,
+        _ => return Err(unknown()),
+    };
+    Ok(value)
+}
+
+/// `date` moved by `amount` units, or `None` if that leaves the supported
+/// range of dates.
+fn shift(date: Date, amount: i32, unit: &str) -> Option<Date> {
+    match unit {
+        "d" => date.checked_add(Duration::days(amount.into())),
+        "w" => date.checked_add(Duration::weeks(amount.into())),
+        "m" | "y" => {
+            let months = if unit == "y" {
+                amount.checked_mul(12)?
+            } else {
+                amount
+            };
+            let index =
+                (date.year() * 12 + i32::from(date.month() as u8) - 1).checked_add(months)?;
+            let (year, month) = (index.div_euclid(12), index.rem_euclid(12) as u8 + 1);
+            let month = Month::try_from(month).ok()?;
+            // Clamp to the end of shorter months, as moment.js does.
+            Date::from_calendar_date(year, month, date.day().min(month.length(year))).ok()
+        }
+        _ => None,
+    }
+}
+
+fn daily_link(vault: &Vault, date: Date) -> Result<String, A4Error> {
+    let path = vault.daily_note_path(UtcDay::from_date(date))?;
+    let link = vault
+        .note_link(&path)
+        .unwrap_or_else(|| UtcDay::from_date(date).to_string());
+    Ok(format!("[[{link}]]"))
+}
+
+fn include(
+    name: &str,
+    ctx: &TemplateContext,
+    includes: &mut Vec<String>,
+) -> Result<String, A4Error> {
+    if includes.iter().any(|included| included == name) || includes.len() >= MAX_INCLUDE_DEPTH {
+        return Err(A4Error::InvalidTemplate {
+            reason: format!("{{{{include:{name}}}}} includes itself"),
+        });
+    }
+    // Template names are vault-relative below routines/templates/.
+    let relative = format!("routines/templates/{name}.md");
+    let path = ctx.vault.resolve_relative(&relative)?;
+    if !path.exists() {
+        return Err(A4Error::InvalidTemplate {
+            reason: format!("{{{{include:{name}}}}}: {relative} not found"),
+        });
+    }
+
+    includes.push(name.to_string());
+    let rendered = render_nested(&fs::read_to_string(&path)?, ctx, includes);
+    includes.pop();
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault::VaultOpts;
+    use tempfile::TempDir;
+
+    fn vault() -> (TempDir, Vault) {
+        let temp_dir = TempDir::new().unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+        (temp_dir, vault)
+    }
+
+    fn context<'a>(vault: &'a Vault, day: &str) -> TemplateContext<'a> {
+        TemplateContext {
+            vault,
+            day: UtcDay::parse(day).unwrap(),
+            now: OffsetDateTime::from_unix_timestamp(1_757_836_692)
+                .unwrap()
+                .to_offset(UtcOffset::from_hms(2, 0, 0).unwrap()),
+            vars: BTreeMap::new(),
+        }
+    }
+
+    fn write_template(vault: &Vault, name: &str, content: &str) {
+        let path = vault.named_template_path(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_legacy_variables() {
+        let (_temp_dir, vault) = vault();
+        let ctx = context(&vault, "2025-09-14");
+
+        let rendered = render("{{YYYY-MM-DD}} ^eod-{{hhmm}} {{now_utc}}", &ctx).unwrap();
+        assert_eq!(
+            rendered,
+            "2025-09-14 ^eod-0958 2025-09-14T07:58:12.000000000Z"
+        );
+    }
+
+    #[test]
+    fn test_dates_and_offsets() {
+        let (_temp_dir, vault) = vault();
+        let ctx = context(&vault, "2025-03-31");
+
+        let rendered = render(
+            "{{date}} {{date:dddd D MMM -1d}} {{ date:YYYY-MM-DD +1w }} {{date:YYYY-MM-DD -1m}} \
+             {{date:YYYY -1y}} {{week}} {{week +1w}} {{weekday}} {{time}} {{time:h:mm a}}",
+            &ctx,
+        )
+        .unwrap();
+        assert_eq!(
+            rendered,
+            "2025-03-31 Sunday 30 Mar 2025-04-07 2025-02-28 2024 2025-W14 2025-W15 Monday 09:58 9:58 am"
+        );
+    }
+
+    #[test]
+    fn test_links() {
+        let (_temp_dir, vault) = vault();
+        let ctx = context(&vault, "2025-09-01");
+
+        let rendered = render("{{yesterday}} {{tomorrow}} {{link -7d}}", &ctx).unwrap();
+        assert_eq!(
+            rendered,
+            "[[capture/2025/2025-08/2025-08-31]] [[capture/2025/2025-09/2025-09-02]] \
+             [[capture/2025/2025-08/2025-08-25]]"
+        );
+    }
+
+    #[test]
+    fn test_links_follow_obsidian_daily_notes() {
+        let (temp_dir, vault) = vault();
+        for dir in [".a4", ".obsidian"] {
+            std::fs::create_dir_all(temp_dir.path().join(dir)).unwrap();
+        }
+        std::fs::write(vault.config_path(), "[obsidian]\ndaily_notes = true\n").unwrap();
+        std::fs::write(
+            temp_dir.path().join(".obsidian/daily-notes.json"),
+            r#"{"folder":"Journal","format":"YYYY/YYYY-MM-DD"}"#,
+        )
+        .unwrap();
+        let ctx = context(&vault, "2025-09-01");
+
+        let rendered = render("{{yesterday}} {{link}}", &ctx).unwrap();
+        assert_eq!(
+            rendered,
+            "[[Journal/2025/2025-08-31]] [[Journal/2025/2025-09-01]]"
+        );
+    }
+
+    #[test]
+    fn test_weekday_conditionals() {
+        let (_temp_dir, vault) = vault();
+        let template = "# {{date}}\n{{#if monday}}## Plan the week\n{{/if}}\
+                        {{#if saturday, sunday}}Rest{{else}}Work{{/if}}\n";
+
+        let monday = render(template, &context(&vault, "2025-09-15")).unwrap();
+        assert_eq!(monday, "# 2025-09-15\n## Plan the week\nWork\n");
+
+        let sunday = render(template, &context(&vault, "2025-09-14")).unwrap();
+        assert_eq!(sunday, "# 2025-09-14\nRest\n");
+
+        let nested = "{{#if weekday}}A{{#if friday}}B{{/if}}{{else}}C{{/if}}";
+        assert_eq!(
+            render(nested, &context(&vault, "2025-09-19")).unwrap(),
+            "AB"
+        );
+        assert_eq!(render(nested, &context(&vault, "2025-09-20")).unwrap(), "C");
+    }
+
+    #[test]
+    fn test_includes() {
+        let (_temp_dir, vault) = vault();
+        write_template(&vault, "habits", "- [ ] Walk ({{weekday}})\n");
+        write_template(&vault, "daily", "# {{date}}\n{{include:habits}}");
+        let ctx = context(&vault, "2025-09-14");
+
+        let rendered = render_named("daily", "", &ctx).unwrap();
+        assert_eq!(rendered, "# 2025-09-14\n- [ ] Walk (Sunday)\n");
+
+        write_template(&vault, "habits", "{{include:daily}}");
+        assert!(matches!(
+            render_named("daily", "", &ctx),
+            Err(A4Error::InvalidTemplate { .. })
+        ));
+        assert!(matches!(
+            render("{{include:missing}}", &ctx),
+            Err(A4Error::InvalidTemplate { .. })
+        ));
+        assert!(matches!(
+            render("{{include:../secret}}", &ctx),
+            Err(A4Error::PathTraversal { .. })
+        ));
+    }
+
+    #[test]
+    fn test_extra_variables() {
+        let (_temp_dir, vault) = vault();
+        let ctx = context(&vault, "2025-09-14").with_var("slug", "garden");
+
+        assert_eq!(render("# {{slug}}", &ctx).unwrap(), "# garden");
+        assert_eq!(
+            render_named("project-log", "# {{slug}} log\n", &ctx).unwrap(),
+            "# garden log\n"
+        );
+    }
+
+    #[test]
+    fn test_unknown_variables() {
+        let (_temp_dir, vault) = vault();
+        let ctx = context(&vault, "2025-09-14");
+
+        for template in [
+            "{{title}}",
+            "{{hhmm -1d}}",
+            "{{#if someday}}x{{/if}}",
+            "{{weekday:x}}",
+        ] {
+            assert!(
+                matches!(
+                    render(template, &ctx),
+                    Err(A4Error::UnknownTemplateVariable { .. })
+                ),
+                "{template}"
+            );
+        }
+        for template in [
+            "{{#if monday}}",
+            "{{/if}}",
+            "{{else}}",
+            "{{date +999999999y}}",
+            "{{date -2147483648m}}",
+            "{{date +99999999999d}}",
+        ] {
+            assert!(
+                matches!(render(template, &ctx), Err(A4Error::InvalidTemplate { .. })),
+                "{template}"
+            );
+        }
+    }
+}
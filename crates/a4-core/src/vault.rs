@@ -1,8 +1,16 @@
-use crate::date::UtcDay;
+use crate::config::Config;
+use crate::date::{Clock, UtcDay, UtcMonth};
 use crate::error::A4Error;
+use crate::notes::create_note;
+use crate::obsidian;
+use crate::sparse;
+use crate::template::{self, TemplateContext};
+use fs4::fs_err3::FileExt;
 use fs_err as fs;
 use std::env;
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
 
 pub struct Vault {
     root: PathBuf,
@@ -102,20 +110,213 @@ impl Vault {
     }
 
     pub fn capture_day_path(&self, utc_day: UtcDay) -> PathBuf {
+        self.dated_path("capture", &utc_day, &utc_day.filename())
+    }
+
+    /// Paste zone for quick drops: `inbox/YYYY/YYYY-MM/YYYY-MM-DD--paste-zone.md`.
+    pub fn inbox_day_path(&self, utc_day: UtcDay) -> PathBuf {
+        self.dated_path("inbox", &utc_day, &format!("{utc_day}--paste-zone.md"))
+    }
+
+    /// Month roll-up: `collections/journals/YYYY/YYYY-MM.md`.
+    pub fn journal_rollup_path(&self, month: UtcMonth) -> PathBuf {
+        self.root
+            .join("collections")
+            .join("journals")
+            .join(format!("{:04}", month.year))
+            .join(format!("{month}.md"))
+    }
+
+    /// Where the daily note for `day` lives; see [`obsidian::daily_note`].
+    pub fn daily_note_path(&self, day: UtcDay) -> Result<PathBuf, A4Error> {
+        let config = Config::load(self)?;
+        Ok(obsidian::daily_note(self, &config, day)?.path)
+    }
+
+    /// Every daily note and its day, oldest first; see
+    /// [`obsidian::daily_notes`].
+    pub fn daily_notes(&self) -> Result<Vec<(UtcDay, PathBuf)>, A4Error> {
+        let config = Config::load(self)?;
+        obsidian::daily_notes(self, &config)
+    }
+
+    /// All daily notes under `capture/`, oldest first.
+    pub(crate) fn capture_days(&self) -> Result<Vec<(UtcDay, PathBuf)>, A4Error> {
+        let capture_dir = self.root.join("capture");
+        if !capture_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut days = Vec::new();
+        for entry in WalkDir::new(&capture_dir) {
+            let entry = entry.map_err(|e| A4Error::Io(e.into()))?;
+            let name = entry.file_name().to_string_lossy();
+            if let Some(day) = name
+                .strip_suffix(".md")
+                .and_then(|day| UtcDay::parse(day).ok())
+            {
+                days.push((day, entry.into_path()));
+            }
+        }
+
+        days.sort();
+        Ok(days)
+    }
+
+    /// Every Markdown note in the vault, skipping dot-directories (`.git`, `.a4`).
+    pub fn note_paths(&self) -> Result<Vec<PathBuf>, A4Error> {
+        let walker = WalkDir::new(&self.root)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|entry| {
+                entry.depth() == 0 || !entry.file_name().to_string_lossy().starts_with('.')
+            });
+
+        let mut notes = Vec::new();
+        for entry in walker {
+            let entry = entry.map_err(|e| A4Error::Io(e.into()))?;
+            if entry.file_type().is_file()
+                && entry.path().extension().is_some_and(|ext| ext == "md")
+            {
+                notes.push(entry.into_path());
+            }
+        }
+        Ok(notes)
+    }
+
+    /// `.a4/config.toml`
+    pub fn config_path(&self) -> PathBuf {
+        self.root.join(".a4").join("config.toml")
+    }
+
+    /// `.a4/secrets-allowlist`: secret-scanner false positives, one per line.
+    pub fn secrets_allowlist_path(&self) -> PathBuf {
+        self.root.join(".a4").join("secrets-allowlist")
+    }
+
+    /// `.a4/age-recipients`: public keys private notes are encrypted to.
+    pub fn crypt_recipients_path(&self) -> PathBuf {
+        self.root.join(".a4").join("age-recipients")
+    }
+
+    /// A machine-local file under `.a4/` (tokens, keys). Whoever creates it
+    /// first calls [`Vault::ignore_private`] so `a4 sync` never commits it.
+    pub fn private_file_path(&self, name: &str) -> PathBuf {
+        self.root.join(".a4").join(name)
+    }
+
+    /// List `name` in `.a4/.gitignore`. The file is only ever appended to,
+    /// under a lock, so concurrent callers neither lose nor repeat entries.
+    pub fn ignore_private(&self, name: &str) -> Result<(), A4Error> {
+        let dir = self.root.join(".a4");
+        fs::create_dir_all(&dir)?;
+
+        let mut file = fs::OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(dir.join(".gitignore"))?;
+        file.lock()?;
+        let mut ignore = String::new();
+        file.read_to_string(&mut ignore)?;
+        if !ignore.lines().any(|line| line.trim() == name) {
+            let separator = if ignore.is_empty() || ignore.ends_with('\n') {
+                ""
+            } else {
+                "\n"
+            };
+            file.write_all(format!("{separator}{name}\n").as_bytes())?;
+            file.sync_all()?;
+        }
+        Ok(())
+    }
+
+    /// `assets/`: mount point of the optional `a4-assets` submodule.
+    pub fn assets_dir(&self) -> PathBuf {
+        self.root.join("assets")
+    }
+
+    /// Hub directory for a project: `projects/<slug>`.
+    pub fn project_dir(&self, slug: &str) -> PathBuf {
+        self.root.join("projects").join(slug)
+    }
+
+    fn dated_path(&self, area: &str, utc_day: &UtcDay, filename: &str) -> PathBuf {
         let year_dir = format!("{:04}", utc_day.year);
         let month_dir = format!("{:04}-{:02}", utc_day.year, utc_day.month);
-        let filename = format!(
-            "{:04}-{:02}-{:02}.md",
-            utc_day.year, utc_day.month, utc_day.day
-        );
 
         self.root
-            .join("capture")
+            .join(area)
             .join(year_dir)
             .join(month_dir)
             .join(filename)
     }
 
+    /// Vault-relative wikilink target for a note (`capture/2025/2025-09/2025-09-14`),
+    /// or `None` when the path lies outside the vault.
+    pub fn note_link(&self, path: &Path) -> Option<String> {
+        let relative = path.strip_prefix(&self.root).ok()?;
+        let relative = relative.with_extension("");
+        let parts: Vec<_> = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        Some(parts.join("/"))
+    }
+
+    /// Join a vault-relative path from an untrusted caller, rejecting absolute
+    /// paths and `..` components.
+    pub fn resolve_relative(&self, relative: &str) -> Result<PathBuf, A4Error> {
+        let path = Path::new(relative);
+        if relative.is_empty()
+            || !path
+                .components()
+                .all(|c| matches!(c, std::path::Component::Normal(_)))
+        {
+            return Err(A4Error::PathTraversal {
+                path: path.to_path_buf(),
+            });
+        }
+        Ok(self.root.join(path))
+    }
+
+    /// Like [`Vault::resolve_relative`], but only for Markdown notes outside
+    /// dot-directories, so remote callers cannot append to `.git/config`,
+    /// `.a4/config.toml` or `.gitattributes`.
+    pub fn resolve_appendable(&self, relative: &str) -> Result<PathBuf, A4Error> {
+        let path = self.resolve_relative(relative)?;
+        let hidden = Path::new(relative)
+            .components()
+            .any(|c| c.as_os_str().to_string_lossy().starts_with('.'));
+        if hidden || path.extension().is_none_or(|ext| ext != "md") {
+            return Err(A4Error::NotANote {
+                path: PathBuf::from(relative),
+            });
+        }
+        Ok(path)
+    }
+
+    /// Resolve a wikilink target (`capture/2025/2025-09/2025-09-14`, or the
+    /// short `capture/2025-09-14` form) to an existing note in the vault.
+    pub fn resolve_link(&self, target: &str) -> Option<PathBuf> {
+        let target = target.trim().trim_end_matches(".md");
+        if target.is_empty()
+            || Path::new(target)
+                .components()
+                .any(|c| !matches!(c, std::path::Component::Normal(_)))
+        {
+            return None;
+        }
+
+        let path = self.root.join(format!("{target}.md"));
+        if path.is_file() {
+            return Some(path);
+        }
+
+        let day = UtcDay::parse(target.strip_prefix("capture/")?).ok()?;
+        Some(self.capture_day_path(day)).filter(|path| path.is_file())
+    }
+
     pub fn ensure_parents(&self, path: &Path) -> Result<(), A4Error> {
         // For absolute paths, just use them as-is
         // For relative paths, join with vault root
@@ -142,9 +343,194 @@ impl Vault {
     }
 
     pub fn template_path(&self) -> PathBuf {
+        self.named_template_path("daily")
+    }
+
+    /// `routines/templates/<name>.md`
+    pub fn named_template_path(&self, name: &str) -> PathBuf {
         self.root
             .join("routines")
             .join("templates")
-            .join("daily.md")
+            .join(format!("{name}.md"))
+    }
+
+    /// The daily note for `day`, created from its template if absent (see
+    /// [`obsidian::daily_note`] for where it lives and which template it
+    /// uses). Creation is atomic: when several processes race, one creates
+    /// the note and the others get [`Created::No`].
+    pub fn ensure_daily(
+        &self,
+        day: UtcDay,
+        clock: &dyn Clock,
+    ) -> Result<(PathBuf, Created), A4Error> {
+        let config = Config::load(self)?;
+        let daily = obsidian::daily_note(self, &config, day)?;
+
+        sparse::ensure_checked_out(self, &daily.path)?;
+        if daily.path.exists() {
+            return Ok((daily.path, Created::No));
+        }
+
+        let mut content = if daily.template.exists() {
+            fs::read_to_string(&daily.template)?
+        } else {
+            "\n".to_string()
+        };
+        if daily.obsidian_template {
+            let converted =
+                obsidian::convert_template(&content, &obsidian::ObsidianSettings::detect(self)?);
+            for tag in &converted.unmapped {
+                tracing::warn!(
+                    "No A4 equivalent for {tag} in {}; left as is",
+                    daily.template.display()
+                );
+            }
+            content = converted.text;
+        }
+        let content = template::render(&content, &TemplateContext::at(self, day, clock))?;
+
+        let created = if create_note(&daily.path, &content)? {
+            Created::Yes
+        } else {
+            Created::No
+        };
+        Ok((daily.path, created))
+    }
+}
+
+/// Whether [`Vault::ensure_daily`] created the note or found it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Created {
+    Yes,
+    No,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use time::{OffsetDateTime, UtcOffset};
+
+    fn clock() -> OffsetDateTime {
+        OffsetDateTime::from_unix_timestamp(1_757_836_692)
+            .unwrap()
+            .to_offset(UtcOffset::from_hms(2, 0, 0).unwrap())
+    }
+
+    #[test]
+    fn test_resolve_appendable() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+
+        assert_eq!(
+            vault.resolve_appendable("notes/memo.md").unwrap(),
+            vault.root().join("notes/memo.md")
+        );
+        for path in [
+            ".git/config",
+            ".a4/config.toml",
+            ".a4/secrets-allowlist",
+            ".gitattributes",
+            ".obsidian/notes.md",
+            "notes/memo.txt",
+        ] {
+            assert!(
+                matches!(
+                    vault.resolve_appendable(path),
+                    Err(A4Error::NotANote { .. })
+                ),
+                "{path}"
+            );
+        }
+        assert!(matches!(
+            vault.resolve_appendable("../escape.md"),
+            Err(A4Error::PathTraversal { .. })
+        ));
+    }
+
+    #[test]
+    fn test_ignore_private_appends_once() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+        let ignore = temp_dir.path().join(".a4/.gitignore");
+
+        assert_eq!(
+            vault.private_file_path("serve-token"),
+            temp_dir.path().join(".a4/serve-token")
+        );
+        assert!(!ignore.exists());
+
+        std::fs::create_dir_all(ignore.parent().unwrap()).unwrap();
+        std::fs::write(&ignore, "age.key").unwrap();
+        vault.ignore_private("cache").unwrap();
+        vault.ignore_private("cache").unwrap();
+        vault.ignore_private("age.key").unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&ignore).unwrap(),
+            "age.key\ncache\n"
+        );
+    }
+
+    #[test]
+    fn test_ensure_daily_from_template() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+        let template = vault.template_path();
+        std::fs::create_dir_all(template.parent().unwrap()).unwrap();
+        std::fs::write(&template, "# {{date}}\n^intent-{{hhmm}}\n").unwrap();
+        let day = UtcDay::parse("2025-09-14").unwrap();
+
+        let (path, created) = vault.ensure_daily(day, &clock()).unwrap();
+        assert_eq!(path, vault.capture_day_path(day));
+        assert_eq!(created, Created::Yes);
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "# 2025-09-14\n^intent-0958\n"
+        );
+
+        std::fs::write(&path, "edited\n").unwrap();
+        assert_eq!(
+            vault.ensure_daily(day, &clock()).unwrap(),
+            (path.clone(), Created::No)
+        );
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "edited\n");
+    }
+
+    #[test]
+    fn test_ensure_daily_blank_without_template() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+        let day = UtcDay::parse("2025-09-14").unwrap();
+
+        let (path, created) = vault.ensure_daily(day, &clock()).unwrap();
+        assert_eq!(created, Created::Yes);
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "\n");
+    }
+
+    #[test]
+    fn test_ensure_daily_races_create_once() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().to_path_buf();
+        let day = UtcDay::parse("2025-09-14").unwrap();
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let root = root.clone();
+                std::thread::spawn(move || {
+                    let vault = Vault::open(&root, VaultOpts::default()).unwrap();
+                    vault.ensure_daily(day, &clock()).unwrap().1
+                })
+            })
+            .collect();
+        let created = handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .filter(|created| *created == Created::Yes)
+            .count();
+
+        assert_eq!(created, 1);
+        let vault = Vault::open(&root, VaultOpts::default()).unwrap();
+        let dir = vault.capture_day_path(day).parent().unwrap().to_path_buf();
+        assert_eq!(std::fs::read_dir(dir).unwrap().count(), 1);
     }
 }
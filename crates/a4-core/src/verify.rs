@@ -0,0 +1,240 @@
+use crate::commit_message::Area;
+use crate::config::RewritePolicy;
+use crate::crypt;
+use crate::error::A4Error;
+use crate::git_backend::{FileChange, GitBackend};
+use crate::vault::Vault;
+use serde::Serialize;
+use similar::{capture_diff_slices, Algorithm, DiffTag};
+
+/// How a modified note changed relative to HEAD.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EditKind {
+    /// Old content is kept intact and new content follows it.
+    Append,
+    /// Old lines are kept in order; new sections were inserted mid-file,
+    /// each starting with a heading.
+    HeadingInsert,
+    /// Existing lines were changed, removed or reordered, plain text was
+    /// inserted into an existing section, or the note was deleted.
+    Rewrite,
+}
+
+/// One offending region of a rewrite, with a 1-based line number in HEAD.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct Hunk {
+    pub line: usize,
+    pub removed: Vec<String>,
+    pub added: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct FileEdit {
+    /// Vault-relative path, `/`-separated.
+    pub path: String,
+    pub kind: EditKind,
+    /// Empty unless `kind` is [`EditKind::Rewrite`].
+    pub hunks: Vec<Hunk>,
+}
+
+/// Compare a note at HEAD with its working-tree content.
+pub fn classify(old: &str, new: &str) -> (EditKind, Vec<Hunk>) {
+    if new.starts_with(old) {
+        return (EditKind::Append, Vec::new());
+    }
+
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+
+    let mut kind = EditKind::Append;
+    let mut hunks = Vec::new();
+    for op in capture_diff_slices(Algorithm::Myers, &old_lines, &new_lines) {
+        let (tag, old_range, new_range) = op.as_tag_tuple();
+        let added = &new_lines[new_range];
+        let mid_file = old_range.start < old_lines.len();
+
+        match tag {
+            DiffTag::Equal => continue,
+            DiffTag::Insert if !mid_file => continue,
+            DiffTag::Insert if starts_with_heading(added) => {
+                kind = EditKind::HeadingInsert;
+                continue;
+            }
+            _ => {}
+        }
+
+        hunks.push(Hunk {
+            line: old_range.start + 1,
+            removed: old_lines[old_range].iter().map(|l| l.to_string()).collect(),
+            added: added.iter().map(|l| l.to_string()).collect(),
+        });
+    }
+
+    if hunks.is_empty() {
+        (kind, hunks)
+    } else {
+        (EditKind::Rewrite, hunks)
+    }
+}
+
+fn starts_with_heading(lines: &[&str]) -> bool {
+    lines
+        .iter()
+        .find(|line| !line.trim().is_empty())
+        .is_some_and(|line| line.starts_with('#'))
+}
+
+/// Classify every modified or deleted Markdown file in the working tree
+/// against HEAD.
+pub fn verify<B: GitBackend>(vault: &Vault, backend: &B) -> Result<Vec<FileEdit>, A4Error> {
+    let mut edits = Vec::new();
+    for entry in backend.status_entries()? {
+        if entry.change == FileChange::Added || !entry.path.ends_with(".md") {
+            continue;
+        }
+        let Some(old) = backend.head_file(&entry.path)? else {
+            continue;
+        };
+        let path = vault.root().join(&entry.path);
+        let old = crypt::reveal(vault, &path, old)?;
+
+        if entry.change == FileChange::Deleted {
+            // Deleting a note removes everything ever appended to it.
+            edits.push(FileEdit {
+                path: entry.path,
+                kind: EditKind::Rewrite,
+                hunks: vec![Hunk {
+                    line: 1,
+                    removed: String::from_utf8_lossy(&old)
+                        .lines()
+                        .map(|l| l.to_string())
+                        .collect(),
+                    added: Vec::new(),
+                }],
+            });
+            continue;
+        }
+        let new = std::fs::read(&path)?;
+
+        let (kind, hunks) = classify(
+            &String::from_utf8_lossy(&old),
+            &String::from_utf8_lossy(&new),
+        );
+        edits.push(FileEdit {
+            path: entry.path,
+            kind,
+            hunks,
+        });
+    }
+    Ok(edits)
+}
+
+/// Check capture notes before a sync. Rewrites are an error under
+/// [`RewritePolicy::Refuse`]; under [`RewritePolicy::Warn`] they are returned
+/// for the caller to report.
+pub fn guard<B: GitBackend>(
+    vault: &Vault,
+    backend: &B,
+    policy: RewritePolicy,
+) -> Result<Vec<FileEdit>, A4Error> {
+    let rewrites: Vec<FileEdit> = verify(vault, backend)?
+        .into_iter()
+        .filter(|edit| edit.kind == EditKind::Rewrite)
+        .filter(|edit| Area::of(&edit.path) == Some(Area::Capture))
+        .collect();
+
+    if policy == RewritePolicy::Refuse && !rewrites.is_empty() {
+        return Err(A4Error::CaptureRewritten {
+            report: render(&rewrites),
+        });
+    }
+    Ok(rewrites)
+}
+
+/// Render rewrites as `path` followed by `-`/`+` hunks.
+pub fn render(edits: &[FileEdit]) -> String {
+    let mut out = String::new();
+    for edit in edits {
+        out.push_str(&edit.path);
+        out.push('\n');
+        for hunk in &edit.hunks {
+            out.push_str(&format!("  @@ line {} @@\n", hunk.line));
+            for line in &hunk.removed {
+                out.push_str(&format!("  -{line}\n"));
+            }
+            for line in &hunk.added {
+                out.push_str(&format!("  +{line}\n"));
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTE: &str =
+        "## Focus\n\n^focus-0930\n\nRefactor the parser\n\n## End of Day\n\n^eod-2215\n\nShipped\n";
+
+    #[test]
+    fn test_classify_append() {
+        let new = format!("{NOTE}\n^eod-2230\n\nAlso tests\n");
+        assert_eq!(classify(NOTE, &new), (EditKind::Append, Vec::new()));
+
+        // Missing trailing newline before the append is still an append
+        assert_eq!(
+            classify("## Focus\nold", "## Focus\nold\n\n^focus-0930\n\nnew\n").0,
+            EditKind::Append
+        );
+    }
+
+    #[test]
+    fn test_classify_heading_insert() {
+        let new = NOTE.replace(
+            "## End of Day",
+            "## Plans\n\n^plan-1200\n\nAfternoon\n\n## End of Day",
+        );
+        assert_eq!(classify(NOTE, &new), (EditKind::HeadingInsert, Vec::new()));
+    }
+
+    #[test]
+    fn test_classify_rewrite() {
+        let new = NOTE.replace("Refactor the parser", "Rewrite the lexer");
+        let (kind, hunks) = classify(NOTE, &new);
+        assert_eq!(kind, EditKind::Rewrite);
+        assert_eq!(
+            hunks,
+            vec![Hunk {
+                line: 5,
+                removed: vec!["Refactor the parser".to_string()],
+                added: vec!["Rewrite the lexer".to_string()],
+            }]
+        );
+
+        // Plain text slipped into an existing section is not an append either
+        let new = NOTE.replace("Refactor the parser\n", "Refactor the parser\nand more\n");
+        let (kind, hunks) = classify(NOTE, &new);
+        assert_eq!(kind, EditKind::Rewrite);
+        assert_eq!(hunks[0].line, 6);
+        assert!(hunks[0].removed.is_empty());
+    }
+
+    #[test]
+    fn test_render() {
+        let edits = vec![FileEdit {
+            path: "capture/2025/2025-09/2025-09-14.md".to_string(),
+            kind: EditKind::Rewrite,
+            hunks: vec![Hunk {
+                line: 5,
+                removed: vec!["old".to_string()],
+                added: vec!["new".to_string()],
+            }],
+        }];
+        assert_eq!(
+            render(&edits),
+            "capture/2025/2025-09/2025-09-14.md\n  @@ line 5 @@\n  -old\n  +new\n"
+        );
+    }
+}
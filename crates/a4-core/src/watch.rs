@@ -0,0 +1,91 @@
+use crate::notes::is_temp_path;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Vault-relative paths whose changes never trigger a sync.
+pub const IGNORED_PATHS: &[&str] = &[".git", ".a4/cache"];
+
+/// Whether a change at `path` should be ignored by `a4 watch`: outside the
+/// vault, under [`IGNORED_PATHS`], or a temporary file a note is written
+/// through.
+pub fn is_ignored(root: &Path, path: &Path) -> bool {
+    let Ok(relative) = path.strip_prefix(root) else {
+        return true;
+    };
+    is_temp_path(relative)
+        || IGNORED_PATHS
+            .iter()
+            .any(|ignored| relative.starts_with(PathBuf::from(ignored)))
+}
+
+/// Exponential back-off between failed sync attempts.
+#[derive(Debug)]
+pub struct Backoff {
+    initial: Duration,
+    max: Duration,
+    current: Option<Duration>,
+}
+
+impl Backoff {
+    pub fn new(initial: Duration, max: Duration) -> Self {
+        Backoff {
+            initial,
+            max,
+            current: None,
+        }
+    }
+
+    /// Record a failure and return how long to wait before retrying.
+    pub fn failure(&mut self) -> Duration {
+        let next = match self.current {
+            Some(current) => (current * 2).min(self.max),
+            None => self.initial,
+        };
+        self.current = Some(next);
+        next
+    }
+
+    pub fn reset(&mut self) {
+        self.current = None;
+    }
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Backoff::new(Duration::from_secs(30), Duration::from_secs(15 * 60))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_ignored() {
+        let root = Path::new("/vault");
+        assert!(is_ignored(root, Path::new("/vault/.git/index")));
+        assert!(is_ignored(root, Path::new("/vault/.a4/cache/locks/x")));
+        assert!(is_ignored(root, Path::new("/elsewhere/note.md")));
+        assert!(is_ignored(
+            root,
+            Path::new("/vault/capture/2025/2025-09/.2025-09-14.md.4242-0123456789abcdef.tmp")
+        ));
+        assert!(!is_ignored(root, Path::new("/vault/.a4/config.toml")));
+        assert!(!is_ignored(root, Path::new("/vault/.github/notes.md")));
+        assert!(!is_ignored(
+            root,
+            Path::new("/vault/capture/2025/2025-09/2025-09-14.md")
+        ));
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_max() {
+        let mut backoff = Backoff::new(Duration::from_secs(30), Duration::from_secs(100));
+        assert_eq!(backoff.failure(), Duration::from_secs(30));
+        assert_eq!(backoff.failure(), Duration::from_secs(60));
+        assert_eq!(backoff.failure(), Duration::from_secs(100));
+        assert_eq!(backoff.failure(), Duration::from_secs(100));
+        backoff.reset();
+        assert_eq!(backoff.failure(), Duration::from_secs(30));
+    }
+}